@@ -1,9 +1,11 @@
 use anyhow::{Context, Result};
 use colored::*;
 use isa_runtime::{DeviceRuntime, FilePersistence};
-use isa_core::CircularDistance;
+use isa_core::{DimensionDivergence, DimensionStatus, Divergence256};
 use serde_json::json;
 
+const AXES: [&str; 3] = ["finance", "time", "hardware"];
+
 pub fn run(file1: String, file2: String, format: String) -> Result<()> {
     // Load both states
     let persistence1 = FilePersistence::new(&file1);
@@ -15,40 +17,41 @@ pub fn run(file1: String, file2: String, format: String) -> Result<()> {
     let runtime2 = DeviceRuntime::load_or_create(dummy_seed, persistence2)
         .context(format!("Failed to load state from {}", file2))?;
     
-    let vector1 = runtime1.state_vector();
-    let vector2 = runtime2.state_vector();
-    
-    // Calculate divergence
-    let finance_div = CircularDistance::compute(&vector1.finance, &vector2.finance);
-    let time_div = CircularDistance::compute(&vector1.time, &vector2.time);
-    let hardware_div = CircularDistance::compute(&vector1.hardware, &vector2.hardware);
+    // Compare file 1 against file 2, counters included
+    let report = runtime1.state().divergence_report(runtime2.state());
+    let dims = report.dimensions();
     
-    // Calculate magnitude (sum of first 8 bytes as rough estimate)
-    let finance_mag = magnitude(&finance_div);
-    let time_mag = magnitude(&time_div);
-    let hardware_mag = magnitude(&hardware_div);
-    let total_mag = finance_mag + time_mag + hardware_mag;
+    // Magnitude is the bit length of the divergence (0-256)
+    let total_mag = dims.iter()
+        .fold(Divergence256::ZERO, |sum, dim| sum.saturating_add(dim.distance))
+        .bits();
     
     match format.as_str() {
         "json" => {
+            let field = |value: &dyn Fn(&DimensionDivergence) -> serde_json::Value| {
+                AXES.iter()
+                    .zip(dims)
+                    .map(|(axis, dim)| (axis.to_string(), value(dim)))
+                    .collect::<serde_json::Map<_, _>>()
+            };
+            let mut magnitude = field(&|dim| json!(dim.distance.bits()));
+            magnitude.insert("total".to_string(), json!(total_mag));
+            
             let output = json!({
                 "file1": file1,
                 "file2": file2,
-                "divergence": {
-                    "finance": hex::encode(&finance_div),
-                    "time": hex::encode(&time_div),
-                    "hardware": hex::encode(&hardware_div),
-                },
-                "magnitude": {
-                    "finance": finance_mag,
-                    "time": time_mag,
-                    "hardware": hardware_mag,
-                    "total": total_mag,
-                }
+                "divergence": field(&|dim| json!(format!("{:x}", dim.distance))),
+                "magnitude": magnitude,
+                "counters": field(&|dim| json!({
+                    "file1": dim.counter,
+                    "file2": dim.reference_counter,
+                    "delta": dim.counter_delta().clamp(i64::MIN as i128, i64::MAX as i128) as i64,
+                    "status": dim.status().name(),
+                })),
             });
             println!("{}", serde_json::to_string_pretty(&output)?);
         }
-        "text" | _ => {
+        _ => {
             println!("{}", "State Comparison".bold());
             println!("{}", "=".repeat(60).dimmed());
             println!();
@@ -56,27 +59,31 @@ pub fn run(file1: String, file2: String, format: String) -> Result<()> {
             println!("  File 2: {}", file2.yellow());
             println!();
             println!("{}", "Divergence:".cyan().bold());
-            println!("  Finance:  {} (magnitude: {})", 
-                hex::encode(&finance_div[..8]).dimmed(), 
-                format_magnitude(finance_mag));
-            println!("  Time:     {} (magnitude: {})", 
-                hex::encode(&time_div[..8]).dimmed(), 
-                format_magnitude(time_mag));
-            println!("  Hardware: {} (magnitude: {})", 
-                hex::encode(&hardware_div[..8]).dimmed(), 
-                format_magnitude(hardware_mag));
+            for (axis, dim) in AXES.iter().zip(dims) {
+                let label = format!("{}{}:", &axis[..1].to_uppercase(), &axis[1..]);
+                println!("  {:<9} {} (magnitude: {})",
+                    label,
+                    format!("{:x}", dim.distance).dimmed(),
+                    format_magnitude(dim.distance.bits()));
+                println!("            events: {} vs {} ({})",
+                    dim.counter,
+                    dim.reference_counter,
+                    format_status(dim));
+            }
             println!();
             println!("  Total Magnitude: {}", format_magnitude(total_mag).bold());
             
-            if total_mag == 0 {
-                println!();
+            println!();
+            if report.is_identical() {
                 println!("{} States are identical", "✓".green().bold());
-            } else if total_mag < 1000 {
-                println!();
-                println!("{} States are very similar", "~".yellow().bold());
+            } else if report.has_conflict() {
+                println!("{} States conflict: same event count, different state", "!".red().bold());
+            } else if report.is_lagging() {
+                println!("{} File 1 is behind file 2 (missing events, no conflict)", "~".yellow().bold());
+            } else if runtime2.state().divergence_report(runtime1.state()).is_lagging() {
+                println!("{} File 1 is ahead of file 2 (extra events, no conflict)", "~".yellow().bold());
             } else {
-                println!();
-                println!("{} States have diverged significantly", "!".red().bold());
+                println!("{} States have diverged in both directions", "!".red().bold());
             }
         }
     }
@@ -84,19 +91,20 @@ pub fn run(file1: String, file2: String, format: String) -> Result<()> {
     Ok(())
 }
 
-fn magnitude(data: &[u8; 32]) -> u64 {
-    // Sum first 8 bytes as u64 for rough magnitude estimate
-    let mut sum = 0u64;
-    for &byte in &data[..8] {
-        sum = sum.wrapping_add(byte as u64);
+fn format_status(dim: &DimensionDivergence) -> ColoredString {
+    let delta = dim.counter_delta().unsigned_abs();
+    match dim.status() {
+        DimensionStatus::Identical => "identical".green(),
+        DimensionStatus::Lagging => format!("lagging by {}", delta).yellow(),
+        DimensionStatus::Ahead => format!("ahead by {}", delta).yellow(),
+        DimensionStatus::Conflicting => "conflicting".red(),
     }
-    sum
 }
 
-fn format_magnitude(mag: u64) -> ColoredString {
+fn format_magnitude(mag: u32) -> ColoredString {
     if mag == 0 {
         "0".green()
-    } else if mag < 100 {
+    } else if mag <= 10 {
         mag.to_string().yellow()
     } else {
         mag.to_string().red()
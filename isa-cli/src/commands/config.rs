@@ -368,7 +368,7 @@ pub fn generate(format: String, output: Option<String>) -> Result<()> {
     let content = match format.as_str() {
         "json" => serde_json::to_string_pretty(&config)?,
         "toml" => toml::to_string_pretty(&config)?,
-        "yaml" | _ => {
+        _ => {
             // Simple YAML generation
             let mut yaml = String::new();
             yaml.push_str(&format!("name: {}\n", config.name));
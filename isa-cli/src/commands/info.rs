@@ -28,7 +28,7 @@ pub fn run() -> Result<()> {
     println!("    {} Cryptographic accumulation (SHA-256 + BLAKE3)", "✓".green());
     println!("    {} Multi-axis integrity tracking", "✓".green());
     println!("    {} Circular distance divergence detection", "✓".green());
-    println!("    {} State serialization (bincode)", "✓".green());
+    println!("    {} State serialization (canonical format)", "✓".green());
     println!("    {} File persistence", "✓".green());
     println!("    {} WASM bindings (build with wasm-pack)", "✓".green());
     println!("    {} C FFI bindings", "✓".green());
@@ -13,7 +13,9 @@ pub fn run(device_id: String, output: Option<String>, seed_hex: Option<String>)
     };
     
     // Create runtime with persistence
-    let persistence = FilePersistence::new(&output_path);
+    let persistence = FilePersistence::new(&output_path)
+        .with_exclusive_lock()
+        .context(format!("Failed to lock state file {}", output_path))?;
     let runtime = DeviceRuntime::load_or_create(seed, persistence)
         .context("Failed to create device runtime")?;
     
@@ -13,7 +13,7 @@ pub fn run(target: Option<String>, output: Option<String>) -> Result<()> {
     let target_str = if let Some(t) = target {
         t
     } else {
-        let targets = vec!["nestjs", "tauri", "rust", "wasm"];
+        let targets = ["nestjs", "tauri", "rust", "wasm"];
         let descriptions = vec![
             "NestJS — TypeScript backend with WASM bindings",
             "Tauri — Rust backend + React frontend",
@@ -172,8 +172,7 @@ fn generate_wasm_guide() -> String {
 }
 
 fn strip_ansi(s: &str) -> String {
-    let re = regex_lite(s);
-    re
+    regex_lite(s)
 }
 
 // Simple ANSI stripping without regex dependency
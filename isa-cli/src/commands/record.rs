@@ -3,8 +3,10 @@ use colored::*;
 use isa_runtime::{DeviceRuntime, FilePersistence, EventAxis};
 
 pub fn run(file: String, event: String, entropy: Option<String>, delta_t: u64) -> Result<()> {
-    // Load existing runtime
-    let persistence = FilePersistence::new(&file);
+    // Load existing runtime, holding the state lock until the update is saved
+    let persistence = FilePersistence::new(&file)
+        .with_exclusive_lock()
+        .context(format!("Failed to lock state file {}", file))?;
     let dummy_seed = [0u8; 32];
     let mut runtime = DeviceRuntime::load_or_create(dummy_seed, persistence)
         .context(format!("Failed to load state from {}", file))?;
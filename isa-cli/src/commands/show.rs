@@ -15,18 +15,18 @@ pub fn run(file: String, format: String) -> Result<()> {
     match format.as_str() {
         "json" => {
             let output = json!({
-                "finance": hex::encode(&vector.finance),
-                "time": hex::encode(&vector.time),
-                "hardware": hex::encode(&vector.hardware),
+                "finance": hex::encode(vector.finance),
+                "time": hex::encode(vector.time),
+                "hardware": hex::encode(vector.hardware),
             });
             println!("{}", serde_json::to_string_pretty(&output)?);
         }
         "hex" => {
-            println!("{}", hex::encode(&vector.finance));
-            println!("{}", hex::encode(&vector.time));
-            println!("{}", hex::encode(&vector.hardware));
+            println!("{}", hex::encode(vector.finance));
+            println!("{}", hex::encode(vector.time));
+            println!("{}", hex::encode(vector.hardware));
         }
-        "text" | _ => {
+        _ => {
             println!("{}", "Device State".bold());
             println!("{}", "=".repeat(60).dimmed());
             println!();
@@ -74,7 +74,7 @@ pub fn run(path: Option<String>) -> Result<()> {
         .into_iter()
         .flatten()
         .filter_map(|e| e.ok())
-        .filter(|e| e.path().extension().map_or(false, |ext| ext == "state"))
+        .filter(|e| e.path().extension().is_some_and(|ext| ext == "state"))
         .collect();
 
     if !state_files.is_empty() {
@@ -17,9 +17,9 @@ pub fn run(file: String, verbose: bool) -> Result<()> {
         let vector = runtime.state_vector();
         println!();
         println!("  State Vector:");
-        println!("    Finance:  {}", hex::encode(&vector.finance).dimmed());
-        println!("    Time:     {}", hex::encode(&vector.time).dimmed());
-        println!("    Hardware: {}", hex::encode(&vector.hardware).dimmed());
+        println!("    Finance:  {}", hex::encode(vector.finance).dimmed());
+        println!("    Time:     {}", hex::encode(vector.time).dimmed());
+        println!("    Hardware: {}", hex::encode(vector.hardware).dimmed());
     }
     
     Ok(())
@@ -13,33 +13,204 @@
 //! - Implementations SHALL NOT use randomness, time, or IO operations
 //! - State mixing SHALL use only pure cryptographic functions
 //! - Counter increments SHALL be wrapping (no overflow panics)
+//!
+//! ## Accumulation Modes
+//!
+//! - `Chained` (default): S_n = KDF("axis-accumulate", S_{n-1} ‖ event ‖ entropy ‖ Δt).
+//!   The state is a hash chain; circular distance between two states carries
+//!   no information about how far apart their histories are.
+//! - `Additive`: S_n = (S_{n-1} + Φ(event, entropy, Δt)) mod 2^256 with
+//!   Φ = KDF("axis-phi", event ‖ entropy ‖ Δt). Divergence and convergence
+//!   constants are then differences of accumulated increments, as in the paper.
+//! - `Multiset`: S_n = (S_{n-1} + D(event)) mod 2^256 with
+//!   D = KDF("axis-multiset", event). Entropy and Δt are ignored, so the state
+//!   depends only on the multiset of events seen, not on their order, and an
+//!   event can be removed again by subtracting D(event).
+//!
+//! ## Multiset Security
+//!
+//! Multiset digests are combined by an unkeyed sum modulo 2^256. Wagner's
+//! generalized birthday algorithm finds event multisets with a chosen sum far
+//! below the 128-bit collision resistance of the hash: an adversary who
+//! chooses the events can make two different multisets (of about 2^16 events
+//! each) reach the same state with on the order of 2^32 digest evaluations.
+//! Multiset mode SHALL NOT be relied on to bind events chosen by an untrusted
+//! party; use `Chained` mode or authenticate the events in that case.
 
-use crate::kdf::mix_state;
+use crate::divergence::{modular_add, modular_sub};
+use crate::integrity_state::IntegrityStateError;
+use crate::receipt::{event_digest, removal_digest, state_commitment, AccumulationReceipt};
+use crate::kdf::{mix_state_with_suite, multiset_digest_with_suite, phi_with_suite};
+use crate::suite::{Blake3Suite, HashSuite};
 use crate::STATE_SIZE;
+use core::marker::PhantomData;
 use zeroize::{Zeroize, ZeroizeOnDrop};
 
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 
+/// How an accumulator folds events into its state.
+///
+/// The mode is recorded in serialized state (see [`crate::canonical`]).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[repr(u8)]
+pub enum AccumulationMode {
+    /// Hash-chain the previous state with the event (original behaviour).
+    #[default]
+    Chained = 0,
+    /// Add Φ(event, entropy, Δt) to the state modulo 2^256.
+    Additive = 1,
+    /// Add a digest of the event alone; order-independent and removable.
+    Multiset = 2,
+}
+
+impl AccumulationMode {
+    /// Identifier recorded in serialized state.
+    pub fn id(self) -> u8 {
+        self as u8
+    }
+
+    /// Look up a mode by its serialized identifier.
+    pub fn from_id(id: u8) -> Option<Self> {
+        match id {
+            0 => Some(AccumulationMode::Chained),
+            1 => Some(AccumulationMode::Additive),
+            2 => Some(AccumulationMode::Multiset),
+            _ => None,
+        }
+    }
+
+    /// Name used in configuration files.
+    pub fn name(self) -> &'static str {
+        match self {
+            AccumulationMode::Chained => "chained",
+            AccumulationMode::Additive => "additive",
+            AccumulationMode::Multiset => "multiset",
+        }
+    }
+
+    /// Look up a mode by its configuration name.
+    pub fn from_name(name: &str) -> Option<Self> {
+        [AccumulationMode::Chained, AccumulationMode::Additive, AccumulationMode::Multiset]
+            .into_iter()
+            .find(|mode| mode.name() == name)
+    }
+}
+
+// Representations without a `mode` field (v0.1.0) load as chained.
 #[derive(Clone, Zeroize, ZeroizeOnDrop)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-pub struct AxisAccumulator {
+#[cfg_attr(feature = "serde", serde(bound = ""))]
+pub struct AxisAccumulator<H: HashSuite = Blake3Suite> {
     state: [u8; STATE_SIZE],
     #[zeroize(skip)]
     counter: u64,
+    #[zeroize(skip)]
+    #[cfg_attr(feature = "serde", serde(default))]
+    mode: AccumulationMode,
+    #[zeroize(skip)]
+    #[cfg_attr(feature = "serde", serde(skip))]
+    suite: PhantomData<H>,
 }
 
 impl AxisAccumulator {
     pub fn new(seed: [u8; 32]) -> Self {
+        Self::new_with_suite(seed)
+    }
+
+    pub fn from_state(state: [u8; 32], counter: u64) -> Self {
+        Self::from_state_with_suite(state, counter)
+    }
+}
+
+impl<H: HashSuite> AxisAccumulator<H> {
+    pub fn new_with_suite(seed: [u8; 32]) -> Self {
+        Self::from_state_with_suite(seed, 0)
+    }
+
+    pub fn from_state_with_suite(state: [u8; 32], counter: u64) -> Self {
         Self {
-            state: seed,
-            counter: 0,
+            state,
+            counter,
+            mode: AccumulationMode::Chained,
+            suite: PhantomData,
         }
     }
 
-    pub fn accumulate(&mut self, event: &[u8], entropy: &[u8], delta_t: u64) {
-        self.state = mix_state(&self.state, event, entropy, delta_t);
+    /// Use the given accumulation mode for subsequent events.
+    pub fn with_mode(mut self, mode: AccumulationMode) -> Self {
+        self.set_mode(mode);
+        self
+    }
+
+    pub(crate) fn set_mode(&mut self, mode: AccumulationMode) {
+        self.mode = mode;
+    }
+
+    /// Fold an event into the state and return a receipt for it (see [`crate::receipt`]).
+    pub fn accumulate(&mut self, event: &[u8], entropy: &[u8], delta_t: u64) -> AccumulationReceipt {
+        let previous_commitment = self.commitment();
+        self.state = match self.mode {
+            AccumulationMode::Chained => {
+                mix_state_with_suite::<H>(&self.state, event, entropy, delta_t)
+            }
+            AccumulationMode::Additive => {
+                modular_add(&self.state, &phi_with_suite::<H>(event, entropy, delta_t))
+            }
+            AccumulationMode::Multiset => {
+                modular_add(&self.state, &multiset_digest_with_suite::<H>(event))
+            }
+        };
         self.counter = self.counter.wrapping_add(1);
+
+        AccumulationReceipt {
+            counter: self.counter,
+            previous_commitment,
+            event_digest: event_digest::<H>(event),
+            new_commitment: self.commitment(),
+        }
+    }
+
+    /// Remove a previously accumulated event (multiset mode only) and return
+    /// a receipt for the removal (see [`crate::receipt`]).
+    ///
+    /// The counter is decremented, so it tracks the size of the multiset.
+    /// Fails with `RemovalNotSupported` in other modes and with
+    /// `EmptyMultiset` if the counter is already zero.
+    pub fn remove(&mut self, event: &[u8]) -> Result<AccumulationReceipt, IntegrityStateError> {
+        if self.mode != AccumulationMode::Multiset {
+            return Err(IntegrityStateError::RemovalNotSupported(self.mode));
+        }
+        if self.counter == 0 {
+            return Err(IntegrityStateError::EmptyMultiset);
+        }
+        let previous_commitment = self.commitment();
+        self.state = modular_sub(&self.state, &multiset_digest_with_suite::<H>(event));
+        self.counter -= 1;
+
+        Ok(AccumulationReceipt {
+            counter: self.counter,
+            previous_commitment,
+            event_digest: removal_digest::<H>(event),
+            new_commitment: self.commitment(),
+        })
+    }
+
+    /// Add a convergence constant to the state: S = (S + K) mod 2^256.
+    ///
+    /// The counter and accumulation mode are kept (see [`crate::divergence`]).
+    pub fn apply_convergence(&mut self, convergence_constant: &[u8; 32]) {
+        self.state = modular_add(&self.state, convergence_constant);
+    }
+
+    pub fn mode(&self) -> AccumulationMode {
+        self.mode
+    }
+
+    /// Commitment to the current state and counter, as used in receipts.
+    pub fn commitment(&self) -> [u8; 32] {
+        state_commitment::<H>(&self.state, self.counter)
     }
 
     pub fn state(&self) -> [u8; 32] {
@@ -49,29 +220,29 @@ impl AxisAccumulator {
     pub fn counter(&self) -> u64 {
         self.counter
     }
-
-    pub fn from_state(state: [u8; 32], counter: u64) -> Self {
-        Self { state, counter }
-    }
 }
 
-impl core::fmt::Debug for AxisAccumulator {
+impl<H: HashSuite> core::fmt::Debug for AxisAccumulator<H> {
     fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         f.debug_struct("AxisAccumulator")
             .field("state", &"[REDACTED]")
             .field("counter", &self.counter)
+            .field("mode", &self.mode)
+            .field("suite", &H::NAME)
             .finish()
     }
 }
 
-impl PartialEq for AxisAccumulator {
+impl<H: HashSuite> PartialEq for AxisAccumulator<H> {
     fn eq(&self, other: &Self) -> bool {
         use subtle::ConstantTimeEq;
-        self.state.ct_eq(&other.state).into() && self.counter == other.counter
+        self.state.ct_eq(&other.state).into()
+            && self.counter == other.counter
+            && self.mode == other.mode
     }
 }
 
-impl Eq for AxisAccumulator {}
+impl<H: HashSuite> Eq for AxisAccumulator<H> {}
 
 #[cfg(test)]
 mod tests {
@@ -120,4 +291,126 @@ mod tests {
 
         assert_ne!(state1, state2);
     }
+
+    #[test]
+    fn test_accumulator_suites_diverge() {
+        use crate::suite::{Sha256HkdfSuite, Shake256Suite};
+
+        let seed = [0u8; 32];
+        let mut blake3 = AxisAccumulator::new(seed);
+        let mut sha256 = AxisAccumulator::<Sha256HkdfSuite>::new_with_suite(seed);
+        let mut shake = AxisAccumulator::<Shake256Suite>::new_with_suite(seed);
+
+        blake3.accumulate(b"event1", b"entropy1", 100);
+        sha256.accumulate(b"event1", b"entropy1", 100);
+        shake.accumulate(b"event1", b"entropy1", 100);
+
+        assert_ne!(blake3.state(), sha256.state());
+        assert_ne!(blake3.state(), shake.state());
+        assert_ne!(sha256.state(), shake.state());
+    }
+
+    #[test]
+    fn test_additive_mode_adds_phi() {
+        use crate::divergence::CircularDistance;
+
+        let seed = [0u8; 32];
+        let mut chained = AxisAccumulator::new(seed);
+        let mut additive = AxisAccumulator::new(seed).with_mode(AccumulationMode::Additive);
+
+        chained.accumulate(b"event1", b"entropy1", 100);
+        additive.accumulate(b"event1", b"entropy1", 100);
+
+        assert_ne!(chained.state(), additive.state());
+        assert_eq!(additive.state(), phi_with_suite::<Blake3Suite>(b"event1", b"entropy1", 100));
+        assert_eq!(additive.mode(), AccumulationMode::Additive);
+
+        // Divergence between two additive replicas is exactly the missed increment
+        let replica = additive.clone();
+        additive.accumulate(b"event2", b"entropy2", 200);
+        assert_eq!(
+            CircularDistance::compute(&additive.state(), &replica.state()),
+            phi_with_suite::<Blake3Suite>(b"event2", b"entropy2", 200)
+        );
+    }
+
+    #[test]
+    fn test_accumulation_mode_ids() {
+        for mode in [AccumulationMode::Chained, AccumulationMode::Additive, AccumulationMode::Multiset] {
+            assert_eq!(AccumulationMode::from_id(mode.id()), Some(mode));
+            assert_eq!(AccumulationMode::from_name(mode.name()), Some(mode));
+        }
+        assert_eq!(AccumulationMode::from_name("sorted"), None);
+        assert_eq!(AccumulationMode::from_id(0xFF), None);
+        assert_eq!(AccumulationMode::default(), AccumulationMode::Chained);
+    }
+
+    #[test]
+    fn test_multiset_mode_is_order_independent() {
+        let seed = [3u8; 32];
+        let mut replica_a = AxisAccumulator::new(seed).with_mode(AccumulationMode::Multiset);
+        let mut replica_b = AxisAccumulator::new(seed).with_mode(AccumulationMode::Multiset);
+
+        replica_a.accumulate(b"receipt:1", b"till-a", 10);
+        replica_a.accumulate(b"receipt:2", b"till-a", 20);
+        replica_a.accumulate(b"receipt:2", b"till-a", 30);
+
+        replica_b.accumulate(b"receipt:2", b"till-b", 7);
+        replica_b.accumulate(b"receipt:1", b"till-b", 1);
+        replica_b.accumulate(b"receipt:2", b"till-b", 9);
+
+        assert_eq!(replica_a, replica_b);
+    }
+
+    #[test]
+    fn test_multiset_remove() {
+        let seed = [3u8; 32];
+        let mut acc = AxisAccumulator::new(seed).with_mode(AccumulationMode::Multiset);
+        let empty = acc.clone();
+
+        acc.accumulate(b"receipt:1", b"", 0);
+        acc.accumulate(b"receipt:2", b"", 0);
+        acc.remove(b"receipt:1").unwrap();
+
+        let mut only_second = empty.clone();
+        only_second.accumulate(b"receipt:2", b"", 0);
+        assert_eq!(acc, only_second);
+
+        acc.remove(b"receipt:2").unwrap();
+        assert_eq!(acc, empty);
+    }
+
+    #[test]
+    fn test_remove_from_empty_multiset() {
+        let mut acc = AxisAccumulator::new([3u8; 32]).with_mode(AccumulationMode::Multiset);
+        let empty = acc.clone();
+        assert!(matches!(acc.remove(b"receipt:1"), Err(IntegrityStateError::EmptyMultiset)));
+        assert_eq!(acc, empty);
+    }
+
+    #[test]
+    fn test_remove_requires_multiset_mode() {
+        let mut acc = AxisAccumulator::new([0u8; 32]).with_mode(AccumulationMode::Additive);
+        assert!(matches!(
+            acc.remove(b"event"),
+            Err(IntegrityStateError::RemovalNotSupported(AccumulationMode::Additive))
+        ));
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn test_serde_keeps_mode() {
+        let mut acc = AxisAccumulator::new([0u8; 32]).with_mode(AccumulationMode::Multiset);
+        acc.accumulate(b"event", b"", 0);
+
+        let restored: AxisAccumulator = serde_json::from_str(&serde_json::to_string(&acc).unwrap()).unwrap();
+        assert_eq!(restored.mode(), AccumulationMode::Multiset);
+        assert_eq!(restored, acc);
+
+        // v0.1.0 representation without a mode
+        let legacy = format!(r#"{{"state":{:?},"counter":1}}"#, acc.state());
+        let restored: AxisAccumulator = serde_json::from_str(&legacy).unwrap();
+        assert_eq!(restored.mode(), AccumulationMode::Chained);
+        assert_eq!(restored.state(), acc.state());
+    }
 }
@@ -0,0 +1,528 @@
+//! Canonical binary encoding of integrity states.
+//!
+//! ## Conformance Classification
+//!
+//! **NORMATIVE** - This module defines the interchange format for persisted
+//! integrity states. Implementations in other languages SHALL produce and accept
+//! exactly this layout.
+//!
+//! ## Layout
+//!
+//! All multi-byte integers are little-endian. There is no padding.
+//!
+//! | Offset      | Size | Field                                           |
+//! |-------------|------|-------------------------------------------------|
+//! | 0           | 4    | Magic bytes `"MISA"` (`4D 49 53 41`)            |
+//! | 4           | 2    | Format version (u16, currently `2`)             |
+//! | 6           | 1    | Hash suite id (u8, see [`crate::suite`])        |
+//! | 7           | 1    | Accumulation mode (u8, see below)               |
+//! | 8           | 1    | Entropy mode (u8, see [`crate::entropy`])       |
+//! | 9           | 6    | State version: major, minor, patch (3 × u16)    |
+//! | 15          | 4    | Dimension count `n` (u32)                       |
+//! | 19 + 40·i   | 32   | Dimension `i` state (256-bit little-endian)     |
+//! | 51 + 40·i   | 8    | Dimension `i` event counter (u64)               |
+//! | 19 + 40·n   | 4    | CRC-32 (IEEE 802.3) of all preceding bytes (u32)|
+//!
+//! The total length of an encoded state is therefore `23 + 40·n` bytes.
+//!
+//! ## Format Versions
+//!
+//! Writers SHALL produce version 2. Readers SHALL also accept version 1,
+//! which differs from version 2 as follows:
+//!
+//! - Byte 7 is a reserved flags byte that SHALL be `0`; readers SHALL reject
+//!   a version 1 encoding with any other value. Version 2 reuses it for the
+//!   accumulation mode, so a version 1 encoding never carries a mode table
+//!   and every dimension is chained.
+//! - There is no entropy mode byte: the state version starts at offset 8,
+//!   the dimension count at offset 14 and the dimension records at offset 18.
+//!   A version 1 state was accumulated with random entropy.
+//!
+//! The total length of a version 1 encoding is `22 + 40·n` bytes.
+//!
+//! ## Accumulation Mode
+//!
+//! The mode byte holds the [`AccumulationMode`] id shared by every dimension
+//! (`0` chained, `1` additive, `2` multiset). If dimensions use different modes it is
+//! [`MODE_PER_DIMENSION`] (`0xFF`), and a table of `n` mode ids (one byte per
+//! dimension) follows the dimension count; the records and checksum then
+//! start `n` bytes later.
+//!
+//! ## Decoding Rules
+//!
+//! Readers SHALL reject an encoding when:
+//! - it is shorter than its declared length (`Truncated`)
+//! - the magic bytes differ (`BadMagic`)
+//! - the format version is not supported (`UnsupportedFormat`)
+//! - the checksum does not match (`ChecksumMismatch`)
+//! - the hash suite id is unknown (`UnknownHashSuite`) or differs from the
+//!   suite of the requested state type (`HashSuiteMismatch`)
+//! - the state version is incompatible (`IncompatibleVersion`)
+//! - an accumulation mode id is unknown (`UnknownAccumulationMode`)
+//! - the entropy mode id is unknown (`UnknownEntropyMode`)
+//! - a version 1 flags byte is non-zero (`DeserializationFailed`)
+//! - trailing bytes follow the checksum
+//!
+//! The checksum detects accidental corruption only. It provides no protection
+//! against deliberate modification.
+
+use crate::axis::AccumulationMode;
+use crate::dimension::DimensionAccumulator;
+use crate::entropy::EntropyMode;
+use crate::integrity_state::{check_hash_suite, IntegrityStateError};
+use crate::suite::HashSuite;
+use crate::version::Version;
+use crate::STATE_SIZE;
+
+#[cfg(not(feature = "std"))]
+use alloc::vec::Vec;
+
+/// Magic bytes at the start of every canonical encoding.
+pub const MAGIC: [u8; 4] = *b"MISA";
+
+/// Current canonical format version.
+pub const FORMAT_VERSION: u16 = 2;
+
+/// Length of the fixed header preceding the dimension records.
+pub const HEADER_LEN: usize = 19;
+
+/// Length of the fixed header in format version 1.
+pub const HEADER_LEN_V1: usize = 18;
+
+/// Length of a single dimension record (state followed by counter).
+pub const RECORD_LEN: usize = STATE_SIZE + 8;
+
+/// Length of the trailing checksum.
+pub const CHECKSUM_LEN: usize = 4;
+
+/// Mode byte value announcing a per-dimension mode table.
+pub const MODE_PER_DIMENSION: u8 = 0xFF;
+
+/// Decoded canonical header.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CanonicalHeader {
+    /// Canonical format version.
+    pub format_version: u16,
+    /// Hash suite used to produce the dimension states.
+    pub hash_suite: u8,
+    /// Accumulation mode id shared by all dimensions, or `MODE_PER_DIMENSION`.
+    pub accumulation_mode: u8,
+    /// Source of the entropy inputs accumulated into the state.
+    pub entropy_mode: EntropyMode,
+    /// Version of the state.
+    pub version: Version,
+    /// Number of dimension records that follow the header.
+    pub dimension_count: u32,
+}
+
+impl CanonicalHeader {
+    /// Length of the fixed header in this header's format version.
+    pub fn header_len(&self) -> usize {
+        if self.format_version == 1 {
+            HEADER_LEN_V1
+        } else {
+            HEADER_LEN
+        }
+    }
+}
+
+/// Total encoded length for a state with `dimension_count` dimensions that
+/// share one accumulation mode.
+pub const fn encoded_len(dimension_count: usize) -> usize {
+    HEADER_LEN + dimension_count * RECORD_LEN + CHECKSUM_LEN
+}
+
+/// Check whether `bytes` start with the canonical magic.
+pub fn is_canonical(bytes: &[u8]) -> bool {
+    bytes.len() >= MAGIC.len() && bytes[..MAGIC.len()] == MAGIC
+}
+
+/// Encode dimensions in the canonical format, recording the id of suite `H`.
+///
+/// # Panics
+///
+/// Panics if there are more than `u32::MAX` dimensions.
+pub fn encode<H: HashSuite>(
+    version: Version,
+    entropy_mode: EntropyMode,
+    dimensions: &[DimensionAccumulator<H>],
+) -> Vec<u8> {
+    let dimension_count = u32::try_from(dimensions.len())
+        .expect("dimension count exceeds canonical format limit");
+
+    let shared_mode = match dimensions.first() {
+        Some(first) if dimensions.iter().all(|dim| dim.mode() == first.mode()) => Some(first.mode()),
+        Some(_) => None,
+        None => Some(AccumulationMode::Chained),
+    };
+
+    let mut bytes = Vec::with_capacity(encoded_len(dimensions.len()) + dimensions.len());
+    bytes.extend_from_slice(&MAGIC);
+    bytes.extend_from_slice(&FORMAT_VERSION.to_le_bytes());
+    bytes.push(H::ID);
+    bytes.push(shared_mode.map_or(MODE_PER_DIMENSION, AccumulationMode::id));
+    bytes.push(entropy_mode.id());
+    bytes.extend_from_slice(&version.to_bytes());
+    bytes.extend_from_slice(&dimension_count.to_le_bytes());
+    if shared_mode.is_none() {
+        bytes.extend(dimensions.iter().map(|dim| dim.mode().id()));
+    }
+
+    for dimension in dimensions {
+        bytes.extend_from_slice(&dimension.state());
+        bytes.extend_from_slice(&dimension.counter().to_le_bytes());
+    }
+
+    let checksum = crc32(&bytes);
+    bytes.extend_from_slice(&checksum.to_le_bytes());
+    bytes
+}
+
+/// Decode and validate the canonical header.
+///
+/// Only the header fields are checked; the dimension records and checksum
+/// are validated by [`decode`].
+pub fn decode_header(bytes: &[u8]) -> Result<CanonicalHeader, IntegrityStateError> {
+    if bytes.len() < MAGIC.len() + 2 {
+        return Err(IntegrityStateError::Truncated);
+    }
+    if !is_canonical(bytes) {
+        return Err(IntegrityStateError::BadMagic);
+    }
+
+    let format_version = u16::from_le_bytes([bytes[4], bytes[5]]);
+    let header_len = match format_version {
+        1 => HEADER_LEN_V1,
+        FORMAT_VERSION => HEADER_LEN,
+        _ => return Err(IntegrityStateError::UnsupportedFormat(format_version)),
+    };
+    if bytes.len() < header_len {
+        return Err(IntegrityStateError::Truncated);
+    }
+    if format_version == 1 {
+        return decode_header_v1(bytes);
+    }
+
+    let entropy_mode = EntropyMode::from_id(bytes[8])
+        .ok_or(IntegrityStateError::UnknownEntropyMode(bytes[8]))?;
+    let mut version = [0u8; 6];
+    version.copy_from_slice(&bytes[9..15]);
+
+    Ok(CanonicalHeader {
+        format_version,
+        hash_suite: bytes[6],
+        accumulation_mode: bytes[7],
+        entropy_mode,
+        version: Version::from_bytes(&version),
+        dimension_count: u32::from_le_bytes([bytes[15], bytes[16], bytes[17], bytes[18]]),
+    })
+}
+
+/// Version 1 header: byte 7 is a reserved flags byte and there is no entropy
+/// mode, so every dimension is chained and the entropy was random.
+fn decode_header_v1(bytes: &[u8]) -> Result<CanonicalHeader, IntegrityStateError> {
+    if bytes[7] != 0 {
+        return Err(IntegrityStateError::DeserializationFailed);
+    }
+    let mut version = [0u8; 6];
+    version.copy_from_slice(&bytes[8..14]);
+
+    Ok(CanonicalHeader {
+        format_version: 1,
+        hash_suite: bytes[6],
+        accumulation_mode: AccumulationMode::Chained.id(),
+        entropy_mode: EntropyMode::Random,
+        version: Version::from_bytes(&version),
+        dimension_count: u32::from_le_bytes([bytes[14], bytes[15], bytes[16], bytes[17]]),
+    })
+}
+
+/// Decode a canonical encoding produced with hash suite `H`.
+#[allow(clippy::type_complexity)]
+pub fn decode<H: HashSuite>(
+    bytes: &[u8],
+) -> Result<(CanonicalHeader, Vec<DimensionAccumulator<H>>), IntegrityStateError> {
+    let header = decode_header(bytes)?;
+    let header_len = header.header_len();
+
+    let dimension_count = header.dimension_count as usize;
+    let table_len = if header.accumulation_mode == MODE_PER_DIMENSION { dimension_count } else { 0 };
+    let expected_len = dimension_count
+        .checked_mul(RECORD_LEN)
+        .and_then(|len| len.checked_add(header_len + table_len + CHECKSUM_LEN))
+        .ok_or(IntegrityStateError::Truncated)?;
+    if bytes.len() < expected_len {
+        return Err(IntegrityStateError::Truncated);
+    }
+    if bytes.len() > expected_len {
+        return Err(IntegrityStateError::DeserializationFailed);
+    }
+
+    let (body, checksum) = bytes.split_at(expected_len - CHECKSUM_LEN);
+    let checksum = u32::from_le_bytes([checksum[0], checksum[1], checksum[2], checksum[3]]);
+    if crc32(body) != checksum {
+        return Err(IntegrityStateError::ChecksumMismatch);
+    }
+
+    check_hash_suite::<H>(header.hash_suite)?;
+    let mode_table = &body[header_len..header_len + table_len];
+    let mode_of = |i: usize| {
+        let id = if table_len == 0 { header.accumulation_mode } else { mode_table[i] };
+        AccumulationMode::from_id(id).ok_or(IntegrityStateError::UnknownAccumulationMode(id))
+    };
+    if !header.version.is_compatible(&Version::current()) {
+        return Err(IntegrityStateError::IncompatibleVersion {
+            found: header.version,
+            expected: Version::current(),
+        });
+    }
+
+    let dimensions = body[header_len + table_len..]
+        .chunks_exact(RECORD_LEN)
+        .enumerate()
+        .map(|(i, record)| {
+            let mut state = [0u8; STATE_SIZE];
+            state.copy_from_slice(&record[..STATE_SIZE]);
+            let mut counter = [0u8; 8];
+            counter.copy_from_slice(&record[STATE_SIZE..]);
+            Ok(DimensionAccumulator::from_state_with_suite(state, u64::from_le_bytes(counter))
+                .with_mode(mode_of(i)?))
+        })
+        .collect::<Result<Vec<_>, IntegrityStateError>>()?;
+
+    Ok((header, dimensions))
+}
+
+/// CRC-32 (IEEE 802.3, reflected, polynomial `0xEDB88320`).
+///
+/// This is the checksum used by zlib, PNG and Go's `hash/crc32.ChecksumIEEE`.
+pub fn crc32(bytes: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for &byte in bytes {
+        crc = CRC32_TABLE[((crc ^ byte as u32) & 0xFF) as usize] ^ (crc >> 8);
+    }
+    !crc
+}
+
+const CRC32_TABLE: [u32; 256] = {
+    let mut table = [0u32; 256];
+    let mut i = 0;
+    while i < 256 {
+        let mut crc = i as u32;
+        let mut bit = 0;
+        while bit < 8 {
+            crc = if crc & 1 != 0 { (crc >> 1) ^ 0xEDB8_8320 } else { crc >> 1 };
+            bit += 1;
+        }
+        table[i] = crc;
+        i += 1;
+    }
+    table
+};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::suite::{Blake3Suite, Sha256HkdfSuite};
+
+    fn sample_dimensions() -> [DimensionAccumulator; 2] {
+        [
+            DimensionAccumulator::from_state([0x11u8; 32], 7),
+            DimensionAccumulator::from_state([0x22u8; 32], u64::MAX),
+        ]
+    }
+
+    #[test]
+    fn test_crc32_check_value() {
+        assert_eq!(crc32(b"123456789"), 0xCBF4_3926);
+        assert_eq!(crc32(b""), 0);
+    }
+
+    #[test]
+    fn test_encode_layout() {
+        let bytes = encode(Version::new(0, 1, 0), EntropyMode::Random, &sample_dimensions());
+
+        assert_eq!(bytes.len(), encoded_len(2));
+        assert_eq!(&bytes[0..4], b"MISA");
+        assert_eq!(&bytes[4..6], &[2, 0]);
+        assert_eq!(bytes[6], Blake3Suite::ID);
+        assert_eq!(bytes[7], 0);
+        assert_eq!(bytes[8], 0);
+        assert_eq!(&bytes[9..15], &[0, 0, 1, 0, 0, 0]);
+        assert_eq!(&bytes[15..19], &[2, 0, 0, 0]);
+        assert_eq!(&bytes[19..51], &[0x11u8; 32]);
+        assert_eq!(&bytes[51..59], &7u64.to_le_bytes());
+        assert_eq!(&bytes[59..91], &[0x22u8; 32]);
+        assert_eq!(&bytes[91..99], &u64::MAX.to_le_bytes());
+        assert_eq!(&bytes[99..], &crc32(&bytes[..99]).to_le_bytes());
+    }
+
+    #[test]
+    fn test_roundtrip() {
+        let dimensions = sample_dimensions();
+        let bytes = encode(Version::current(), EntropyMode::Random, &dimensions);
+
+        let (header, decoded) = decode::<Blake3Suite>(&bytes).unwrap();
+        assert_eq!(header.dimension_count, 2);
+        assert_eq!(header.version, Version::current());
+        for (decoded, original) in decoded.iter().zip(dimensions.iter()) {
+            assert_eq!(decoded.state(), original.state());
+            assert_eq!(decoded.counter(), original.counter());
+        }
+    }
+
+    #[test]
+    fn test_empty_state() {
+        let bytes = encode::<Blake3Suite>(Version::current(), EntropyMode::Random, &[]);
+        assert_eq!(bytes.len(), HEADER_LEN + CHECKSUM_LEN);
+        assert!(decode::<Blake3Suite>(&bytes).unwrap().1.is_empty());
+    }
+
+    #[test]
+    fn test_truncated() {
+        let bytes = encode(Version::current(), EntropyMode::Random, &sample_dimensions());
+
+        for len in [0, 3, HEADER_LEN - 1, HEADER_LEN, bytes.len() - 1] {
+            assert!(matches!(
+                decode::<Blake3Suite>(&bytes[..len]),
+                Err(IntegrityStateError::Truncated)
+            ), "length {} should be truncated", len);
+        }
+    }
+
+    #[test]
+    fn test_bad_magic() {
+        let mut bytes = encode(Version::current(), EntropyMode::Random, &sample_dimensions());
+        bytes[0] = b'X';
+        assert!(matches!(decode::<Blake3Suite>(&bytes), Err(IntegrityStateError::BadMagic)));
+    }
+
+    #[test]
+    fn test_unsupported_format() {
+        let mut bytes = encode(Version::current(), EntropyMode::Random, &sample_dimensions());
+        bytes[4] = 9;
+        assert!(matches!(decode::<Blake3Suite>(&bytes), Err(IntegrityStateError::UnsupportedFormat(9))));
+    }
+
+    #[test]
+    fn test_checksum_mismatch() {
+        let mut bytes = encode(Version::current(), EntropyMode::Random, &sample_dimensions());
+        bytes[20] ^= 0x01;
+        assert!(matches!(decode::<Blake3Suite>(&bytes), Err(IntegrityStateError::ChecksumMismatch)));
+    }
+
+    #[test]
+    fn test_unknown_hash_suite() {
+        let mut bytes = encode(Version::current(), EntropyMode::Random, &sample_dimensions());
+        bytes[6] = 0xEE;
+        let body_len = bytes.len() - CHECKSUM_LEN;
+        let checksum = crc32(&bytes[..body_len]);
+        bytes[body_len..].copy_from_slice(&checksum.to_le_bytes());
+
+        assert!(matches!(decode::<Blake3Suite>(&bytes), Err(IntegrityStateError::UnknownHashSuite(0xEE))));
+    }
+
+    #[test]
+    fn test_hash_suite_mismatch() {
+        let bytes = encode(Version::current(), EntropyMode::Random, &sample_dimensions());
+        assert!(matches!(
+            decode::<Sha256HkdfSuite>(&bytes),
+            Err(IntegrityStateError::HashSuiteMismatch { found: 1, expected: 2 })
+        ));
+    }
+
+    #[test]
+    fn test_trailing_bytes_rejected() {
+        let mut bytes = encode(Version::current(), EntropyMode::Random, &sample_dimensions());
+        bytes.push(0);
+        assert!(decode::<Blake3Suite>(&bytes).is_err());
+    }
+
+    #[test]
+    fn test_shared_accumulation_mode() {
+        let dimensions = sample_dimensions().map(|dim| dim.with_mode(AccumulationMode::Additive));
+        let bytes = encode(Version::current(), EntropyMode::Random, &dimensions);
+
+        assert_eq!(bytes.len(), encoded_len(2));
+        assert_eq!(bytes[7], AccumulationMode::Additive.id());
+        let (header, decoded) = decode::<Blake3Suite>(&bytes).unwrap();
+        assert_eq!(header.accumulation_mode, AccumulationMode::Additive.id());
+        assert!(decoded.iter().all(|dim| dim.mode() == AccumulationMode::Additive));
+    }
+
+    #[test]
+    fn test_per_dimension_accumulation_modes() {
+        let [first, second] = sample_dimensions();
+        let dimensions = [first, second.with_mode(AccumulationMode::Additive)];
+        let bytes = encode(Version::current(), EntropyMode::Random, &dimensions);
+
+        assert_eq!(bytes.len(), encoded_len(2) + 2);
+        assert_eq!(bytes[7], MODE_PER_DIMENSION);
+        assert_eq!(&bytes[19..21], &[0, 1]);
+        assert_eq!(&bytes[21..53], &[0x11u8; 32]);
+
+        let (_, decoded) = decode::<Blake3Suite>(&bytes).unwrap();
+        assert_eq!(decoded[0].mode(), AccumulationMode::Chained);
+        assert_eq!(decoded[1].mode(), AccumulationMode::Additive);
+        assert_eq!(decoded[1].state(), [0x22u8; 32]);
+    }
+
+    #[test]
+    fn test_unknown_accumulation_mode() {
+        let mut bytes = encode(Version::current(), EntropyMode::Random, &sample_dimensions());
+        bytes[7] = 0x42;
+        let body_len = bytes.len() - CHECKSUM_LEN;
+        let checksum = crc32(&bytes[..body_len]);
+        bytes[body_len..].copy_from_slice(&checksum.to_le_bytes());
+
+        assert!(matches!(
+            decode::<Blake3Suite>(&bytes),
+            Err(IntegrityStateError::UnknownAccumulationMode(0x42))
+        ));
+    }
+
+    #[test]
+    fn test_format_version_1_flags() {
+        let mut v1 = encode(Version::current(), EntropyMode::Random, &sample_dimensions());
+        v1.remove(8);
+        v1[4] = 1;
+        let body_len = v1.len() - CHECKSUM_LEN;
+        let checksum = crc32(&v1[..body_len]);
+        v1[body_len..].copy_from_slice(&checksum.to_le_bytes());
+
+        let (header, decoded) = decode::<Blake3Suite>(&v1).unwrap();
+        assert_eq!((header.format_version, header.header_len()), (1, HEADER_LEN_V1));
+        assert_eq!(decoded[1].counter(), u64::MAX);
+
+        // Byte 7 is reserved in version 1, including the per-dimension table marker
+        for flags in [AccumulationMode::Additive.id(), MODE_PER_DIMENSION] {
+            v1[7] = flags;
+            let checksum = crc32(&v1[..body_len]);
+            v1[body_len..].copy_from_slice(&checksum.to_le_bytes());
+            assert!(matches!(decode::<Blake3Suite>(&v1), Err(IntegrityStateError::DeserializationFailed)));
+        }
+    }
+
+    #[test]
+    fn test_entropy_mode() {
+        let bytes = encode(Version::current(), EntropyMode::Deterministic, &sample_dimensions());
+
+        assert_eq!(bytes.len(), encoded_len(2));
+        assert_eq!(bytes[8], EntropyMode::Deterministic.id());
+        assert_eq!(decode::<Blake3Suite>(&bytes).unwrap().0.entropy_mode, EntropyMode::Deterministic);
+    }
+
+    #[test]
+    fn test_unknown_entropy_mode() {
+        let mut bytes = encode(Version::current(), EntropyMode::Random, &sample_dimensions());
+        bytes[8] = 0x42;
+        let body_len = bytes.len() - CHECKSUM_LEN;
+        let checksum = crc32(&bytes[..body_len]);
+        bytes[body_len..].copy_from_slice(&checksum.to_le_bytes());
+
+        assert!(matches!(
+            decode::<Blake3Suite>(&bytes),
+            Err(IntegrityStateError::UnknownEntropyMode(0x42))
+        ));
+    }
+}
@@ -0,0 +1,96 @@
+//! Whole-state commitment digest.
+//!
+//! ## Conformance Classification
+//!
+//! **NORMATIVE** - The commitment encoding SHALL be implemented exactly as
+//! specified here.
+//!
+//! A state commitment is a single 32-byte digest binding the version, the
+//! dimension count, and the state and counter of every dimension. It is the
+//! value signed, placed in Merkle leaves and exchanged during sync, so two
+//! states have equal commitments exactly when all of those fields are equal.
+//!
+//! ## Construction
+//!
+//! ```text
+//! commitment = KDF("isa.state.commitment",
+//!                  major || minor || patch || n || S_0 || c_0 || ... || S_{n-1} || c_{n-1})
+//! ```
+//!
+//! with `major`, `minor` and `patch` little-endian u16, `n` the dimension count
+//! as a little-endian u32, `S_i` the 32-byte state and `c_i` the counter of
+//! dimension `i` as a little-endian u64. `KDF` is the state's hash suite.
+//! Accumulation modes, the entropy mode and the suite id are not covered.
+
+use crate::dimension::DimensionAccumulator;
+use crate::kdf::Kdf;
+use crate::suite::HashSuite;
+use crate::version::Version;
+use crate::STATE_SIZE;
+
+/// Domain separation context for state commitments.
+pub const COMMITMENT_CONTEXT: &[u8] = b"isa.state.commitment";
+
+/// Commit to a version and a sequence of dimensions, using hash suite `H`.
+pub fn commitment_with_suite<H: HashSuite>(
+    version: Version,
+    dimensions: &[DimensionAccumulator<H>],
+) -> [u8; STATE_SIZE] {
+    let mut kdf = Kdf::<H>::new_with_suite(COMMITMENT_CONTEXT);
+    kdf.update(&version.to_bytes());
+    kdf.update(&(dimensions.len() as u32).to_le_bytes());
+    for dim in dimensions {
+        kdf.update(&dim.state());
+        kdf.update(&dim.counter().to_le_bytes());
+    }
+    kdf.finalize()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::suite::{Blake3Suite, Sha256HkdfSuite};
+
+    fn dimensions<H: HashSuite>() -> [DimensionAccumulator<H>; 2] {
+        [
+            DimensionAccumulator::from_state_with_suite([0x11; 32], 1),
+            DimensionAccumulator::from_state_with_suite([0x22; 32], 2),
+        ]
+    }
+
+    #[test]
+    fn test_commitment_vector_sha256_hkdf() {
+        // HKDF-SHA256(salt = "isa.state.commitment",
+        //             IKM = 0000 0100 0000 || 02000000 || [0x11; 32] || 0100000000000000
+        //                   || [0x22; 32] || 0200000000000000)
+        let expected = [
+            0x5b, 0x94, 0x5e, 0xed, 0xfb, 0xd8, 0x7e, 0xee,
+            0xd4, 0xce, 0xa7, 0xe4, 0xd3, 0xa9, 0x2a, 0xf6,
+            0x4c, 0x35, 0x89, 0x01, 0x3a, 0xca, 0x24, 0x1a,
+            0x69, 0xc2, 0x9c, 0xee, 0xfb, 0x34, 0x50, 0x9c,
+        ];
+        assert_eq!(
+            commitment_with_suite(Version::new(0, 1, 0), &dimensions::<Sha256HkdfSuite>()),
+            expected
+        );
+    }
+
+    #[test]
+    fn test_commitment_binds_every_field() {
+        let version = Version::new(0, 1, 0);
+        let base = commitment_with_suite(version, &dimensions::<Blake3Suite>());
+
+        assert_ne!(base, commitment_with_suite(Version::new(0, 2, 0), &dimensions::<Blake3Suite>()));
+        assert_ne!(base, commitment_with_suite(version, &dimensions::<Blake3Suite>()[..1]));
+
+        let mut states = dimensions::<Blake3Suite>();
+        states[1] = DimensionAccumulator::from_state_with_suite([0x23; 32], 2);
+        assert_ne!(base, commitment_with_suite(version, &states));
+
+        let mut counters = dimensions::<Blake3Suite>();
+        counters[1] = DimensionAccumulator::from_state_with_suite([0x22; 32], 3);
+        assert_ne!(base, commitment_with_suite(version, &counters));
+
+        assert_ne!(base, commitment_with_suite(version, &dimensions::<Sha256HkdfSuite>()));
+    }
+}
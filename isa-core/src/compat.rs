@@ -11,6 +11,7 @@
 
 use crate::integrity_state::{IntegrityState, DimensionVector, DivergenceVector, IntegrityStateError, DimensionId};
 use crate::dimension::DimensionAccumulator;
+use crate::suite::HashSuite;
 use crate::STATE_SIZE;
 
 /// Backward-compatible alias for 3-dimensional integrity state.
@@ -63,9 +64,9 @@ pub struct DivergenceMetric {
 impl From<DivergenceVector<3>> for DivergenceMetric {
     fn from(vec: DivergenceVector<3>) -> Self {
         Self {
-            finance: vec.values[0],
-            time: vec.values[1],
-            hardware: vec.values[2],
+            finance: vec.values[0].to_le_bytes(),
+            time: vec.values[1].to_le_bytes(),
+            hardware: vec.values[2].to_le_bytes(),
         }
     }
 }
@@ -78,38 +79,43 @@ pub type StateError = IntegrityStateError;
 /// This allows old code to continue using `.finance`, `.time`, `.hardware`
 /// while the underlying implementation uses dimension indices.
 pub trait MultiAxisStateExt {
-    fn finance(&self) -> &DimensionAccumulator;
-    fn time(&self) -> &DimensionAccumulator;
-    fn hardware(&self) -> &DimensionAccumulator;
-    fn finance_mut(&mut self) -> &mut DimensionAccumulator;
-    fn time_mut(&mut self) -> &mut DimensionAccumulator;
-    fn hardware_mut(&mut self) -> &mut DimensionAccumulator;
+    /// Hash suite of the underlying state.
+    type Suite: HashSuite;
+
+    fn finance(&self) -> &DimensionAccumulator<Self::Suite>;
+    fn time(&self) -> &DimensionAccumulator<Self::Suite>;
+    fn hardware(&self) -> &DimensionAccumulator<Self::Suite>;
+    fn finance_mut(&mut self) -> &mut DimensionAccumulator<Self::Suite>;
+    fn time_mut(&mut self) -> &mut DimensionAccumulator<Self::Suite>;
+    fn hardware_mut(&mut self) -> &mut DimensionAccumulator<Self::Suite>;
     fn state_vector_compat(&self) -> StateVector;
     fn divergence_compat(&self, other: &Self) -> DivergenceMetric;
 }
 
-impl MultiAxisStateExt for IntegrityState<3> {
-    fn finance(&self) -> &DimensionAccumulator {
+impl<H: HashSuite> MultiAxisStateExt for IntegrityState<3, H> {
+    type Suite = H;
+
+    fn finance(&self) -> &DimensionAccumulator<H> {
         self.dimension(0).expect("dimension 0 always exists")
     }
 
-    fn time(&self) -> &DimensionAccumulator {
+    fn time(&self) -> &DimensionAccumulator<H> {
         self.dimension(1).expect("dimension 1 always exists")
     }
 
-    fn hardware(&self) -> &DimensionAccumulator {
+    fn hardware(&self) -> &DimensionAccumulator<H> {
         self.dimension(2).expect("dimension 2 always exists")
     }
 
-    fn finance_mut(&mut self) -> &mut DimensionAccumulator {
+    fn finance_mut(&mut self) -> &mut DimensionAccumulator<H> {
         self.dimension_mut(0).expect("dimension 0 always exists")
     }
 
-    fn time_mut(&mut self) -> &mut DimensionAccumulator {
+    fn time_mut(&mut self) -> &mut DimensionAccumulator<H> {
         self.dimension_mut(1).expect("dimension 1 always exists")
     }
 
-    fn hardware_mut(&mut self) -> &mut DimensionAccumulator {
+    fn hardware_mut(&mut self) -> &mut DimensionAccumulator<H> {
         self.dimension_mut(2).expect("dimension 2 always exists")
     }
 
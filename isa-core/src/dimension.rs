@@ -10,7 +10,10 @@
 //! - All operations are deterministic and platform-independent
 //! - Cryptographic logic is unchanged from original AxisAccumulator
 
-use crate::axis::AxisAccumulator;
+use crate::axis::{AccumulationMode, AxisAccumulator};
+use crate::integrity_state::IntegrityStateError;
+use crate::receipt::AccumulationReceipt;
+use crate::suite::{Blake3Suite, HashSuite};
 use crate::STATE_SIZE;
 use zeroize::{Zeroize, ZeroizeOnDrop};
 
@@ -23,24 +26,71 @@ use serde::{Deserialize, Serialize};
 /// The underlying cryptographic accumulation logic is identical.
 #[derive(Clone, Zeroize, ZeroizeOnDrop)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-pub struct DimensionAccumulator {
-    inner: AxisAccumulator,
+#[cfg_attr(feature = "serde", serde(bound = ""))]
+pub struct DimensionAccumulator<H: HashSuite = Blake3Suite> {
+    inner: AxisAccumulator<H>,
 }
 
 impl DimensionAccumulator {
     /// Create a new dimension accumulator from a seed.
     pub fn new(seed: [u8; STATE_SIZE]) -> Self {
+        Self::new_with_suite(seed)
+    }
+
+    /// Create a dimension accumulator from an existing state and counter.
+    ///
+    /// Used for state restoration and recovery protocols.
+    pub fn from_state(state: [u8; STATE_SIZE], counter: u64) -> Self {
+        Self::from_state_with_suite(state, counter)
+    }
+}
+
+impl<H: HashSuite> DimensionAccumulator<H> {
+    /// Create a new dimension accumulator using hash suite `H`.
+    pub fn new_with_suite(seed: [u8; STATE_SIZE]) -> Self {
+        Self {
+            inner: AxisAccumulator::new_with_suite(seed),
+        }
+    }
+
+    /// Restore a dimension accumulator using hash suite `H`.
+    pub fn from_state_with_suite(state: [u8; STATE_SIZE], counter: u64) -> Self {
         Self {
-            inner: AxisAccumulator::new(seed),
+            inner: AxisAccumulator::from_state_with_suite(state, counter),
         }
     }
 
+    /// Use the given accumulation mode for subsequent events.
+    pub fn with_mode(mut self, mode: AccumulationMode) -> Self {
+        self.set_mode(mode);
+        self
+    }
+
+    /// Change the accumulation mode of this dimension.
+    ///
+    /// The state is kept as is; replicas must switch at the same point in
+    /// their event streams to stay comparable.
+    pub fn set_mode(&mut self, mode: AccumulationMode) {
+        self.inner.set_mode(mode);
+    }
+
     /// Accumulate an event into this dimension.
     ///
-    /// This is mathematically identical to the original axis accumulation:
-    /// S_n = (S_{n-1} + Φ(event, entropy, delta_t)) mod 2^256
-    pub fn accumulate(&mut self, event: &[u8], entropy: &[u8], delta_t: u64) {
-        self.inner.accumulate(event, entropy, delta_t);
+    /// In `AccumulationMode::Additive` this computes
+    /// S_n = (S_{n-1} + Φ(event, entropy, delta_t)) mod 2^256; in the default
+    /// `Chained` mode the state is hash-chained instead (see [`crate::axis`]).
+    ///
+    /// Returns a receipt binding the event to the new counter value.
+    pub fn accumulate(&mut self, event: &[u8], entropy: &[u8], delta_t: u64) -> AccumulationReceipt {
+        self.inner.accumulate(event, entropy, delta_t)
+    }
+
+    /// Remove a previously accumulated event and return a receipt for the removal.
+    ///
+    /// Only supported in `AccumulationMode::Multiset`; other modes fail with
+    /// `RemovalNotSupported`, and an empty multiset with `EmptyMultiset`.
+    pub fn remove(&mut self, event: &[u8]) -> Result<AccumulationReceipt, IntegrityStateError> {
+        self.inner.remove(event)
     }
 
     /// Get the current state of this dimension.
@@ -53,17 +103,25 @@ impl DimensionAccumulator {
         self.inner.counter()
     }
 
-    /// Create a dimension accumulator from an existing state and counter.
+    /// Commitment to the current state and counter (see [`crate::receipt`]).
+    pub fn commitment(&self) -> [u8; STATE_SIZE] {
+        self.inner.commitment()
+    }
+
+    /// Get the accumulation mode of this dimension.
+    pub fn mode(&self) -> AccumulationMode {
+        self.inner.mode()
+    }
+
+    /// Add a convergence constant K to the state: S = (S + K) mod 2^256.
     ///
-    /// Used for state restoration and recovery protocols.
-    pub fn from_state(state: [u8; STATE_SIZE], counter: u64) -> Self {
-        Self {
-            inner: AxisAccumulator::from_state(state, counter),
-        }
+    /// The counter and accumulation mode are kept.
+    pub fn apply_convergence(&mut self, convergence_constant: &[u8; STATE_SIZE]) {
+        self.inner.apply_convergence(convergence_constant);
     }
 }
 
-impl core::fmt::Debug for DimensionAccumulator {
+impl<H: HashSuite> core::fmt::Debug for DimensionAccumulator<H> {
     fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         f.debug_struct("DimensionAccumulator")
             .field("inner", &self.inner)
@@ -9,12 +9,32 @@
 //! - Distance calculation is deterministic
 //! - Handles wraparound correctly via borrow propagation
 //!
+//! ## Convergence
+//!
+//! [`modular_add`] and [`modular_sub`] implement the arithmetic of the recovery
+//! protocol (Equation 8): the convergence constant is
+//! `K = (S_trusted - S_drifted) mod 2^256` and applying it computes
+//! `S_restored = (S_drifted + K) mod 2^256`, which equals `S_trusted`.
+//!
+//! ## Divergence Values
+//!
+//! [`Divergence256`] carries a divergence as a full 256-bit unsigned integer
+//! (little-endian, like the states it is computed from). Thresholds and
+//! statistics SHALL compare and combine divergences through this type rather
+//! than reinterpreting a prefix of the bytes.
+//!
 //! ## SIMD Acceleration
 //!
 //! When the `simd` feature is enabled, uses platform-specific SIMD instructions
 //! for 2-4x performance improvement on supported hardware.
 
+use crate::STATE_SIZE;
 use core::cmp::Ordering;
+use core::fmt;
+use core::ops::{Add, Sub};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 
 #[cfg(feature = "simd")]
 mod divergence_simd;
@@ -103,6 +123,340 @@ impl CircularDistance {
     }
 }
 
+/// (a + b) mod 2^256 on little-endian values.
+pub fn modular_add(a: &[u8; STATE_SIZE], b: &[u8; STATE_SIZE]) -> [u8; STATE_SIZE] {
+    let mut result = [0u8; STATE_SIZE];
+    let mut carry = 0u16;
+    for (r, (x, y)) in result.iter_mut().zip(a.iter().zip(b)) {
+        let sum = *x as u16 + *y as u16 + carry;
+        *r = sum as u8;
+        carry = sum >> 8;
+    }
+    result
+}
+
+/// (a - b) mod 2^256 on little-endian values.
+pub fn modular_sub(a: &[u8; STATE_SIZE], b: &[u8; STATE_SIZE]) -> [u8; STATE_SIZE] {
+    CircularDistance::compute(a, b)
+}
+
+/// A 256-bit unsigned divergence value.
+///
+/// Ordering compares the full 256-bit value. `+` and `-` wrap modulo 2^256,
+/// matching the state space; use the `checked_` and `saturating_` variants for
+/// magnitudes that must not wrap. Formatted and serialized (in human-readable
+/// formats) as `0x` followed by 64 big-endian hex digits.
+#[derive(Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct Divergence256([u8; STATE_SIZE]);
+
+impl Divergence256 {
+    /// Zero divergence.
+    pub const ZERO: Self = Self([0u8; STATE_SIZE]);
+
+    /// Largest representable divergence, 2^256 - 1.
+    pub const MAX: Self = Self([0xFFu8; STATE_SIZE]);
+
+    /// Interpret 32 little-endian bytes as a divergence.
+    pub const fn from_le_bytes(bytes: [u8; STATE_SIZE]) -> Self {
+        Self(bytes)
+    }
+
+    /// Little-endian byte representation.
+    pub const fn to_le_bytes(self) -> [u8; STATE_SIZE] {
+        self.0
+    }
+
+    /// Borrow the little-endian byte representation.
+    pub fn as_bytes(&self) -> &[u8; STATE_SIZE] {
+        &self.0
+    }
+
+    /// Minimum circular distance between two states.
+    pub fn between(a: &[u8; STATE_SIZE], b: &[u8; STATE_SIZE]) -> Self {
+        Self(CircularDistance::min_distance(a, b))
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.0 == [0u8; STATE_SIZE]
+    }
+
+    /// Number of leading zero bits (256 for zero).
+    pub fn leading_zeros(&self) -> u32 {
+        let limbs = self.limbs();
+        for (i, limb) in limbs.iter().enumerate().rev() {
+            if *limb != 0 {
+                return (3 - i as u32) * 64 + limb.leading_zeros();
+            }
+        }
+        256
+    }
+
+    /// Number of significant bits (0 for zero).
+    pub fn bits(&self) -> u32 {
+        256 - self.leading_zeros()
+    }
+
+    /// Floor of the base-2 logarithm, or `None` for zero.
+    pub fn log2(&self) -> Option<u32> {
+        self.bits().checked_sub(1)
+    }
+
+    /// Add, returning `None` on overflow.
+    pub fn checked_add(self, rhs: Self) -> Option<Self> {
+        match self.overflowing_add(rhs) {
+            (sum, false) => Some(sum),
+            (_, true) => None,
+        }
+    }
+
+    /// Subtract, returning `None` if `rhs > self`.
+    pub fn checked_sub(self, rhs: Self) -> Option<Self> {
+        match self.overflowing_sub(rhs) {
+            (difference, false) => Some(difference),
+            (_, true) => None,
+        }
+    }
+
+    /// Add, clamping at [`Divergence256::MAX`].
+    pub fn saturating_add(self, rhs: Self) -> Self {
+        self.checked_add(rhs).unwrap_or(Self::MAX)
+    }
+
+    /// Subtract, clamping at zero.
+    pub fn saturating_sub(self, rhs: Self) -> Self {
+        self.checked_sub(rhs).unwrap_or(Self::ZERO)
+    }
+
+    /// Add modulo 2^256.
+    pub fn wrapping_add(self, rhs: Self) -> Self {
+        self.overflowing_add(rhs).0
+    }
+
+    /// Subtract modulo 2^256.
+    pub fn wrapping_sub(self, rhs: Self) -> Self {
+        self.overflowing_sub(rhs).0
+    }
+
+    /// Multiply by a small factor, clamping at [`Divergence256::MAX`].
+    pub fn saturating_mul(self, rhs: u64) -> Self {
+        let mut product = [0u64; 4];
+        let mut carry = 0u128;
+        for (out, limb) in product.iter_mut().zip(self.limbs()) {
+            let wide = limb as u128 * rhs as u128 + carry;
+            *out = wide as u64;
+            carry = wide >> 64;
+        }
+        if carry != 0 {
+            return Self::MAX;
+        }
+        Self::from_limbs(product)
+    }
+
+    /// Convert to `u64`, clamping values above `u64::MAX`.
+    pub fn saturating_to_u64(&self) -> u64 {
+        let limbs = self.limbs();
+        if limbs[1..].iter().any(|&limb| limb != 0) {
+            return u64::MAX;
+        }
+        limbs[0]
+    }
+
+    /// Convert to `u128`, clamping values above `u128::MAX`.
+    pub fn saturating_to_u128(&self) -> u128 {
+        let limbs = self.limbs();
+        if limbs[2..].iter().any(|&limb| limb != 0) {
+            return u128::MAX;
+        }
+        (limbs[1] as u128) << 64 | limbs[0] as u128
+    }
+
+    /// Parse up to 64 big-endian hex digits, with or without a `0x` prefix.
+    pub fn from_hex(hex: &str) -> Option<Self> {
+        let digits = hex.strip_prefix("0x").unwrap_or(hex).as_bytes();
+        if digits.is_empty() || digits.len() > 2 * STATE_SIZE {
+            return None;
+        }
+
+        let mut bytes = [0u8; STATE_SIZE];
+        for (i, &digit) in digits.iter().rev().enumerate() {
+            let nibble = (digit as char).to_digit(16)? as u8;
+            bytes[i / 2] |= nibble << (4 * (i % 2));
+        }
+        Some(Self(bytes))
+    }
+
+    fn overflowing_add(self, rhs: Self) -> (Self, bool) {
+        let mut sum = [0u64; 4];
+        let mut carry = false;
+        for ((out, a), b) in sum.iter_mut().zip(self.limbs()).zip(rhs.limbs()) {
+            let (partial, overflow_a) = a.overflowing_add(b);
+            let (total, overflow_b) = partial.overflowing_add(carry as u64);
+            *out = total;
+            carry = overflow_a || overflow_b;
+        }
+        (Self::from_limbs(sum), carry)
+    }
+
+    fn overflowing_sub(self, rhs: Self) -> (Self, bool) {
+        let mut difference = [0u64; 4];
+        let mut borrow = false;
+        for ((out, a), b) in difference.iter_mut().zip(self.limbs()).zip(rhs.limbs()) {
+            let (partial, underflow_a) = a.overflowing_sub(b);
+            let (total, underflow_b) = partial.overflowing_sub(borrow as u64);
+            *out = total;
+            borrow = underflow_a || underflow_b;
+        }
+        (Self::from_limbs(difference), borrow)
+    }
+
+    fn limbs(&self) -> [u64; 4] {
+        core::array::from_fn(|i| {
+            let mut limb = [0u8; 8];
+            limb.copy_from_slice(&self.0[i * 8..i * 8 + 8]);
+            u64::from_le_bytes(limb)
+        })
+    }
+
+    fn from_limbs(limbs: [u64; 4]) -> Self {
+        let mut bytes = [0u8; STATE_SIZE];
+        for (chunk, limb) in bytes.chunks_exact_mut(8).zip(limbs) {
+            chunk.copy_from_slice(&limb.to_le_bytes());
+        }
+        Self(bytes)
+    }
+}
+
+impl Ord for Divergence256 {
+    fn cmp(&self, other: &Self) -> Ordering {
+        CircularDistance::compare(&self.0, &other.0)
+    }
+}
+
+impl PartialOrd for Divergence256 {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq<[u8; STATE_SIZE]> for Divergence256 {
+    fn eq(&self, other: &[u8; STATE_SIZE]) -> bool {
+        self.0 == *other
+    }
+}
+
+impl Add for Divergence256 {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self {
+        self.wrapping_add(rhs)
+    }
+}
+
+impl Sub for Divergence256 {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self {
+        self.wrapping_sub(rhs)
+    }
+}
+
+impl From<u64> for Divergence256 {
+    fn from(value: u64) -> Self {
+        Self::from_limbs([value, 0, 0, 0])
+    }
+}
+
+impl From<u128> for Divergence256 {
+    fn from(value: u128) -> Self {
+        Self::from_limbs([value as u64, (value >> 64) as u64, 0, 0])
+    }
+}
+
+impl From<[u8; STATE_SIZE]> for Divergence256 {
+    fn from(bytes: [u8; STATE_SIZE]) -> Self {
+        Self(bytes)
+    }
+}
+
+impl From<Divergence256> for [u8; STATE_SIZE] {
+    fn from(divergence: Divergence256) -> Self {
+        divergence.0
+    }
+}
+
+impl fmt::LowerHex for Divergence256 {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if f.alternate() {
+            f.write_str("0x")?;
+        }
+        for byte in self.0.iter().rev() {
+            write!(f, "{:02x}", byte)?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for Divergence256 {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:#x}", self)
+    }
+}
+
+impl fmt::Debug for Divergence256 {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Divergence256({:#x})", self)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl Serialize for Divergence256 {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        if !serializer.is_human_readable() {
+            return self.0.serialize(serializer);
+        }
+
+        const DIGITS: &[u8; 16] = b"0123456789abcdef";
+        let mut hex = [0u8; 2 + 2 * STATE_SIZE];
+        hex[..2].copy_from_slice(b"0x");
+        for (i, byte) in self.0.iter().rev().enumerate() {
+            hex[2 + 2 * i] = DIGITS[(byte >> 4) as usize];
+            hex[3 + 2 * i] = DIGITS[(byte & 0x0F) as usize];
+        }
+        let hex = core::str::from_utf8(&hex).map_err(serde::ser::Error::custom)?;
+        serializer.serialize_str(hex)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> Deserialize<'de> for Divergence256 {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        if !deserializer.is_human_readable() {
+            return <[u8; STATE_SIZE]>::deserialize(deserializer).map(Self);
+        }
+
+        struct HexVisitor;
+
+        impl<'de> serde::de::Visitor<'de> for HexVisitor {
+            type Value = Divergence256;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("a hex-encoded 256-bit divergence")
+            }
+
+            fn visit_str<E: serde::de::Error>(self, value: &str) -> Result<Divergence256, E> {
+                Divergence256::from_hex(value)
+                    .ok_or_else(|| E::invalid_value(serde::de::Unexpected::Str(value), &self))
+            }
+
+            fn visit_u64<E: serde::de::Error>(self, value: u64) -> Result<Divergence256, E> {
+                Ok(Divergence256::from(value))
+            }
+        }
+
+        deserializer.deserialize_any(HexVisitor)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -185,4 +539,108 @@ mod tests {
         b[31] = 2;
         assert_eq!(CircularDistance::compare(&a, &b), Ordering::Less);
     }
+
+    #[test]
+    fn test_modular_add_sub() {
+        let mut max = [0xFFu8; 32];
+        let one = {
+            let mut one = [0u8; 32];
+            one[0] = 1;
+            one
+        };
+
+        assert_eq!(modular_add(&max, &one), [0u8; 32]);
+        assert_eq!(modular_sub(&[0u8; 32], &one), max);
+
+        max[31] = 0x7F;
+        let mut carried = [0u8; 32];
+        carried[31] = 0x80;
+        assert_eq!(modular_add(&max, &one), carried);
+        assert_eq!(modular_sub(&carried, &one), max);
+
+        let a = [0xA5u8; 32];
+        let b = [0x3Cu8; 32];
+        assert_eq!(modular_add(&modular_sub(&a, &b), &b), a);
+    }
+
+    #[test]
+    fn test_divergence256_ordering_uses_all_bytes() {
+        let mut high = [0u8; 32];
+        high[31] = 1;
+        let low = Divergence256::from(u64::MAX);
+
+        assert!(Divergence256::from(high) > low);
+        assert!(Divergence256::ZERO < low);
+        assert_eq!(Divergence256::from(5u64).cmp(&Divergence256::from(5u64)), Ordering::Equal);
+    }
+
+    #[test]
+    fn test_divergence256_arithmetic() {
+        let one = Divergence256::from(1u64);
+        let carry = Divergence256::from(u64::MAX) + one;
+        assert_eq!(carry, Divergence256::from(1u128 << 64));
+        assert_eq!(carry - one, Divergence256::from(u64::MAX));
+
+        assert_eq!(Divergence256::MAX + one, Divergence256::ZERO);
+        assert_eq!(Divergence256::ZERO - one, Divergence256::MAX);
+        assert_eq!(Divergence256::MAX.checked_add(one), None);
+        assert_eq!(Divergence256::ZERO.checked_sub(one), None);
+        assert_eq!(Divergence256::MAX.saturating_add(one), Divergence256::MAX);
+        assert_eq!(Divergence256::ZERO.saturating_sub(one), Divergence256::ZERO);
+
+        assert_eq!(Divergence256::from(u64::MAX).saturating_mul(2), Divergence256::from(u64::MAX as u128 * 2));
+        assert_eq!(Divergence256::MAX.saturating_mul(2), Divergence256::MAX);
+    }
+
+    #[test]
+    fn test_divergence256_saturating_conversion() {
+        assert_eq!(Divergence256::from(42u64).saturating_to_u64(), 42);
+        assert_eq!(Divergence256::from(1u128 << 64).saturating_to_u64(), u64::MAX);
+        assert_eq!(Divergence256::from(1u128 << 64).saturating_to_u128(), 1u128 << 64);
+        assert_eq!(Divergence256::MAX.saturating_to_u128(), u128::MAX);
+    }
+
+    #[test]
+    fn test_divergence256_magnitude() {
+        assert_eq!(Divergence256::ZERO.leading_zeros(), 256);
+        assert_eq!(Divergence256::ZERO.log2(), None);
+        assert_eq!(Divergence256::from(1u64).log2(), Some(0));
+        assert_eq!(Divergence256::from(1024u64).log2(), Some(10));
+        assert_eq!(Divergence256::from(1u128 << 100).bits(), 101);
+        assert_eq!(Divergence256::MAX.leading_zeros(), 0);
+        assert_eq!(Divergence256::MAX.log2(), Some(255));
+    }
+
+    #[test]
+    fn test_divergence256_hex() {
+        let value = Divergence256::from(0x1234u64);
+        let hex = format!("{}", value);
+        assert_eq!(hex.len(), 66);
+        assert!(hex.starts_with("0x0000"));
+        assert!(hex.ends_with("1234"));
+        assert_eq!(Divergence256::from_hex(&hex), Some(value));
+        assert_eq!(Divergence256::from_hex("1234"), Some(value));
+        assert_eq!(Divergence256::from_hex("0xzz"), None);
+        assert_eq!(Divergence256::from_hex(""), None);
+        assert_eq!(Divergence256::from_hex(&"f".repeat(65)), None);
+        assert_eq!(Divergence256::from_hex(&"f".repeat(64)), Some(Divergence256::MAX));
+    }
+
+    #[test]
+    fn test_divergence256_between() {
+        let a = [10u8; 32];
+        let b = [12u8; 32];
+        assert_eq!(Divergence256::between(&a, &b), CircularDistance::min_distance(&a, &b));
+        assert_eq!(Divergence256::between(&a, &b), Divergence256::between(&b, &a));
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn test_divergence256_serde() {
+        let value = Divergence256::from(u128::MAX) + Divergence256::from(1u64);
+
+        let bytes = bincode::serialize(&value).unwrap();
+        assert_eq!(bytes.len(), 32);
+        assert_eq!(bincode::deserialize::<Divergence256>(&bytes).unwrap(), value);
+    }
 }
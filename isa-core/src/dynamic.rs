@@ -3,9 +3,16 @@
 //! This module provides `DynamicIntegrityState` which allows the number of dimensions
 //! to be determined at runtime rather than compile-time.
 
+use crate::axis::AccumulationMode;
+use crate::canonical;
+use crate::commitment;
+use crate::report::{DimensionDivergence, DivergenceReport};
 use crate::dimension::DimensionAccumulator;
-use crate::integrity_state::DimensionId;
+use crate::divergence::{modular_sub, Divergence256};
+use crate::entropy::EntropyMode;
+use crate::integrity_state::{DimensionId, IntegrityStateError};
 use crate::kdf::Kdf;
+use crate::suite::{Blake3Suite, HashSuite};
 use crate::version::Version;
 use crate::STATE_SIZE;
 use zeroize::{Zeroize, ZeroizeOnDrop};
@@ -22,47 +29,107 @@ use std::vec::Vec;
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 
+#[cfg(feature = "serde")]
+use crate::integrity_state::{decode_versioned, encode_versioned};
+
 /// Dynamic integrity state with runtime-configurable dimension count.
 ///
 /// Unlike `IntegrityState<N>`, this type stores dimensions in a `Vec`,
-/// allowing the dimension count to be determined at runtime.
+/// allowing the dimension count to be determined at runtime. As with
+/// `IntegrityState`, the hash suite `H` is part of the type.
 #[derive(Clone, Zeroize, ZeroizeOnDrop)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-pub struct DynamicIntegrityState {
-    dimensions: Vec<DimensionAccumulator>,
+#[cfg_attr(feature = "serde", serde(bound = ""))]
+pub struct DynamicIntegrityState<H: HashSuite = Blake3Suite> {
+    dimensions: Vec<DimensionAccumulator<H>>,
     #[zeroize(skip)]
     version: Version,
+    #[zeroize(skip)]
+    #[cfg_attr(feature = "serde", serde(default))]
+    entropy_mode: EntropyMode,
 }
 
 impl DynamicIntegrityState {
     /// Create a new dynamic integrity state with the specified number of dimensions.
     pub fn new(dimension_count: usize, master_seed: [u8; STATE_SIZE]) -> Self {
+        Self::new_with_suite(dimension_count, master_seed)
+    }
+}
+
+impl<H: HashSuite> DynamicIntegrityState<H> {
+    /// Create a new dynamic integrity state using hash suite `H`.
+    pub fn new_with_suite(dimension_count: usize, master_seed: [u8; STATE_SIZE]) -> Self {
         let mut dimensions = Vec::with_capacity(dimension_count);
         
         for i in 0..dimension_count {
-            let dimension_id = DimensionId::from_index(i);
-            let seed = Kdf::derive_key(&dimension_id.to_kdf_label(), &[&master_seed]);
-            dimensions.push(DimensionAccumulator::new(seed));
+            dimensions.push(Self::derive_dimension(i, &master_seed));
         }
         
         Self {
             dimensions,
             version: Version::current(),
+            entropy_mode: EntropyMode::Random,
+        }
+    }
+    
+    /// Build a state from already-positioned dimensions at the current version.
+    pub(crate) fn from_dimensions(dimensions: Vec<DimensionAccumulator<H>>) -> Self {
+        Self {
+            dimensions,
+            version: Version::current(),
+            entropy_mode: EntropyMode::Random,
         }
     }
     
+    fn derive_dimension(index: usize, master_seed: &[u8; STATE_SIZE]) -> DimensionAccumulator<H> {
+        let dimension_id = DimensionId::from_index(index);
+        let seed = Kdf::<H>::derive_key_with_suite(&dimension_id.to_kdf_label(), &[master_seed]);
+        DimensionAccumulator::new_with_suite(seed)
+    }
+    
+    /// Get the id of the hash suite this state was produced with.
+    pub fn hash_suite(&self) -> u8 {
+        H::ID
+    }
+    
     /// Get the number of dimensions in this state.
     pub fn dimension_count(&self) -> usize {
         self.dimensions.len()
     }
     
+    /// Use the given accumulation mode in every dimension.
+    ///
+    /// Dimensions added later with `add_dimension` use the default mode.
+    pub fn with_accumulation_mode(mut self, mode: AccumulationMode) -> Self {
+        for dimension in self.dimensions.iter_mut() {
+            dimension.set_mode(mode);
+        }
+        self
+    }
+    
+    /// Record that entropy inputs are produced in the given mode (see [`crate::entropy`]).
+    pub fn with_entropy_mode(mut self, mode: EntropyMode) -> Self {
+        self.entropy_mode = mode;
+        self
+    }
+    
+    /// Get the entropy mode recorded for this state.
+    pub fn entropy_mode(&self) -> EntropyMode {
+        self.entropy_mode
+    }
+    
+    /// Get all dimensions in index order.
+    pub fn dimensions(&self) -> &[DimensionAccumulator<H>] {
+        &self.dimensions
+    }
+    
     /// Get a reference to a specific dimension by index.
-    pub fn dimension(&self, index: usize) -> Option<&DimensionAccumulator> {
+    pub fn dimension(&self, index: usize) -> Option<&DimensionAccumulator<H>> {
         self.dimensions.get(index)
     }
     
     /// Get a mutable reference to a specific dimension by index.
-    pub fn dimension_mut(&mut self, index: usize) -> Option<&mut DimensionAccumulator> {
+    pub fn dimension_mut(&mut self, index: usize) -> Option<&mut DimensionAccumulator<H>> {
         self.dimensions.get_mut(index)
     }
     
@@ -76,19 +143,25 @@ impl DynamicIntegrityState {
         self.dimensions.iter().map(|dim| dim.state()).collect()
     }
     
+    /// Commitment to the version, dimension count, states and counters.
+    ///
+    /// Equal to the commitment of an `IntegrityState<N>` with the same
+    /// dimensions (see [`crate::commitment`]).
+    pub fn commitment(&self) -> [u8; STATE_SIZE] {
+        commitment::commitment_with_suite(self.version, &self.dimensions)
+    }
+    
     /// Calculate divergence between this state and another.
     ///
     /// Returns None if the states have different dimension counts.
-    pub fn divergence(&self, other: &Self) -> Option<Vec<[u8; STATE_SIZE]>> {
+    pub fn divergence(&self, other: &Self) -> Option<Vec<Divergence256>> {
         if self.dimension_count() != other.dimension_count() {
             return None;
         }
         
-        use crate::divergence::CircularDistance;
-        
         let mut divergences = Vec::with_capacity(self.dimension_count());
         for i in 0..self.dimension_count() {
-            divergences.push(CircularDistance::min_distance(
+            divergences.push(Divergence256::between(
                 &self.dimensions[i].state(),
                 &other.dimensions[i].state(),
             ));
@@ -97,23 +170,128 @@ impl DynamicIntegrityState {
         Some(divergences)
     }
     
+    /// Compare this state against `reference`, dimension by dimension.
+    ///
+    /// Returns None if the states have different dimension counts. See
+    /// [`crate::report`] for the classification.
+    pub fn divergence_report(&self, reference: &Self) -> Option<DivergenceReport> {
+        if self.dimension_count() != reference.dimension_count() {
+            return None;
+        }
+        
+        Some(DivergenceReport::new(
+            self.dimensions.iter()
+                .zip(&reference.dimensions)
+                .map(|(dim, other)| {
+                    DimensionDivergence::new(&dim.state(), dim.counter(), &other.state(), other.counter())
+                })
+                .collect(),
+        ))
+    }
+    
+    /// Calculate the convergence constant that restores this state to `trusted`.
+    ///
+    /// Computes K = (S_trusted - S) mod 2^256 for each dimension (Equation 8).
+    /// Fails with `DimensionMismatch` if `trusted` has a different number of
+    /// dimensions.
+    pub fn convergence_constant(
+        &self,
+        trusted: &[[u8; STATE_SIZE]],
+    ) -> Result<Vec<[u8; STATE_SIZE]>, IntegrityStateError> {
+        self.check_dimension_count(trusted.len())?;
+        
+        Ok(self.dimensions.iter()
+            .zip(trusted)
+            .map(|(dim, trusted)| modular_sub(trusted, &dim.state()))
+            .collect())
+    }
+    
+    /// Apply a convergence constant: S = (S + K) mod 2^256 for each dimension.
+    ///
+    /// Counters and accumulation modes are kept. Fails with `DimensionMismatch`,
+    /// leaving the state untouched, if `convergence_constant` has a different
+    /// number of dimensions.
+    pub fn apply_convergence(
+        &mut self,
+        convergence_constant: &[[u8; STATE_SIZE]],
+    ) -> Result<(), IntegrityStateError> {
+        self.check_dimension_count(convergence_constant.len())?;
+        
+        for (dim, k) in self.dimensions.iter_mut().zip(convergence_constant) {
+            dim.apply_convergence(k);
+        }
+        Ok(())
+    }
+    
+    fn check_dimension_count(&self, found: usize) -> Result<(), IntegrityStateError> {
+        if found != self.dimension_count() {
+            return Err(IntegrityStateError::DimensionMismatch {
+                found,
+                expected: self.dimension_count(),
+            });
+        }
+        Ok(())
+    }
+    
     /// Add a new dimension to this state.
     ///
     /// The new dimension is initialized with a seed derived from the master seed
     /// and the new dimension index.
     pub fn add_dimension(&mut self, master_seed: [u8; STATE_SIZE]) {
-        let new_index = self.dimensions.len();
-        let dimension_id = DimensionId::from_index(new_index);
-        let seed = Kdf::derive_key(&dimension_id.to_kdf_label(), &[&master_seed]);
-        self.dimensions.push(DimensionAccumulator::new(seed));
+        let dimension = Self::derive_dimension(self.dimensions.len(), &master_seed);
+        self.dimensions.push(dimension);
     }
     
     /// Remove the last dimension from this state.
     ///
     /// Returns None if the state has no dimensions.
-    pub fn remove_dimension(&mut self) -> Option<DimensionAccumulator> {
+    pub fn remove_dimension(&mut self) -> Option<DimensionAccumulator<H>> {
         self.dimensions.pop()
     }
+    
+    /// Encode this state in the canonical format (see [`crate::canonical`]).
+    pub fn to_canonical_bytes(&self) -> Vec<u8> {
+        canonical::encode(self.version, self.entropy_mode, &self.dimensions)
+    }
+    
+    /// Decode a state with any number of dimensions from the canonical format.
+    ///
+    /// Fails with `HashSuiteMismatch` if the encoding was produced by a suite
+    /// other than `H`.
+    pub fn from_canonical_bytes(bytes: &[u8]) -> Result<Self, IntegrityStateError> {
+        let (header, dimensions) = canonical::decode::<H>(bytes)?;
+        Ok(Self { dimensions, version: header.version, entropy_mode: header.entropy_mode })
+    }
+    
+    /// Serialize this state as a versioned payload.
+    ///
+    /// Uses the same layout as `IntegrityState::<N>::to_bytes`, with the
+    /// dimension count recorded in the payload.
+    #[cfg(feature = "serde")]
+    pub fn to_bytes(&self) -> Result<Vec<u8>, bincode::Error> {
+        encode_versioned(self.version, self.entropy_mode, &self.dimensions)
+    }
+    
+    /// Deserialize a versioned payload with any number of dimensions.
+    ///
+    /// Canonical encodings are also accepted.
+    #[cfg(feature = "serde")]
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, IntegrityStateError> {
+        let (version, entropy_mode, dimensions) = decode_versioned(bytes, None)?;
+        Ok(Self { dimensions, version, entropy_mode })
+    }
+    
+    /// Deserialize a versioned payload that must hold exactly `dimension_count` dimensions.
+    ///
+    /// Fails with `DimensionMismatch` if the stored count differs.
+    #[cfg(feature = "serde")]
+    pub fn from_bytes_with_dimensions(
+        bytes: &[u8],
+        dimension_count: usize,
+    ) -> Result<Self, IntegrityStateError> {
+        let (version, entropy_mode, dimensions) = decode_versioned(bytes, Some(dimension_count))?;
+        Ok(Self { dimensions, version, entropy_mode })
+    }
 }
 
 #[cfg(test)]
@@ -154,6 +332,47 @@ mod tests {
         assert_eq!(div.unwrap().len(), 3);
     }
     
+    #[test]
+    #[cfg(feature = "serde")]
+    fn test_serialization_roundtrip() {
+        let mut state = DynamicIntegrityState::new(7, [1u8; 32]);
+        state.dimension_mut(6).unwrap().accumulate(b"event", b"entropy", 10);
+        
+        let restored: DynamicIntegrityState = DynamicIntegrityState::from_bytes(&state.to_bytes().unwrap()).unwrap();
+        assert_eq!(restored.dimension_count(), 7);
+        assert_eq!(restored.state_vector(), state.state_vector());
+        assert_eq!(restored.dimension(6).unwrap().counter(), 1);
+    }
+    
+    #[test]
+    #[cfg(feature = "serde")]
+    fn test_serialization_shared_with_fixed_state() {
+        use crate::IntegrityState;
+        
+        let fixed: IntegrityState<5> = IntegrityState::from_master_seed([1u8; 32]);
+        let dynamic: DynamicIntegrityState = DynamicIntegrityState::from_bytes(&fixed.to_bytes().unwrap()).unwrap();
+        assert_eq!(dynamic.dimension_count(), 5);
+        assert_eq!(dynamic.state_vector(), fixed.state_vector().values.to_vec());
+        
+        let bytes = dynamic.to_bytes().unwrap();
+        assert!(IntegrityState::<5>::from_bytes(&bytes).is_ok());
+        assert!(matches!(
+            DynamicIntegrityState::<Blake3Suite>::from_bytes_with_dimensions(&bytes, 3),
+            Err(IntegrityStateError::DimensionMismatch { found: 5, expected: 3 })
+        ));
+    }
+    
+    #[test]
+    fn test_canonical_roundtrip() {
+        let mut state = DynamicIntegrityState::new(4, [1u8; 32]);
+        state.dimension_mut(2).unwrap().accumulate(b"event", b"entropy", 10);
+        
+        let restored: DynamicIntegrityState = DynamicIntegrityState::from_canonical_bytes(&state.to_canonical_bytes()).unwrap();
+        assert_eq!(restored.dimension_count(), 4);
+        assert_eq!(restored.state_vector(), state.state_vector());
+        assert_eq!(restored.dimension(2).unwrap().counter(), 1);
+    }
+    
     #[test]
     fn test_divergence_different_count() {
         let master_seed = [1u8; 32];
@@ -164,4 +383,65 @@ mod tests {
         let div = state1.divergence(&state2);
         assert!(div.is_none());
     }
+    
+    #[test]
+    fn test_commitment_matches_fixed_state() {
+        use crate::IntegrityState;
+        
+        let fixed: IntegrityState<4> = IntegrityState::from_master_seed([1u8; 32]);
+        let mut dynamic = DynamicIntegrityState::new(4, [1u8; 32]);
+        assert_eq!(dynamic.commitment(), fixed.commitment());
+        
+        dynamic.dimension_mut(3).unwrap().accumulate(b"event", b"entropy", 1);
+        assert_ne!(dynamic.commitment(), fixed.commitment());
+        
+        dynamic.add_dimension([1u8; 32]);
+        assert_ne!(dynamic.commitment(), DynamicIntegrityState::new(4, [1u8; 32]).commitment());
+    }
+    
+    #[test]
+    fn test_divergence_report() {
+        let reference = DynamicIntegrityState::new(4, [1u8; 32]);
+        let mut device = reference.clone();
+        device.dimension_mut(2).unwrap().accumulate(b"event", b"entropy", 1);
+        
+        let report = device.divergence_report(&reference).unwrap();
+        assert_eq!(report.dimensions().len(), 4);
+        assert_eq!(report.get(2).unwrap().counter_delta(), 1);
+        assert!(!report.is_identical() && !report.is_lagging());
+        assert!(device.divergence_report(&DynamicIntegrityState::new(3, [1u8; 32])).is_none());
+    }
+    
+    #[test]
+    fn test_convergence_restores_trusted_state() {
+        let mut trusted = DynamicIntegrityState::new(7, [1u8; 32]);
+        let mut device = trusted.clone();
+        
+        for i in 0..7 {
+            trusted.dimension_mut(i).unwrap().accumulate(b"honest", b"entropy", 1);
+            device.dimension_mut(i).unwrap().accumulate(b"tampered", b"entropy", 1);
+        }
+        
+        let k = device.convergence_constant(&trusted.state_vector()).unwrap();
+        device.apply_convergence(&k).unwrap();
+        
+        assert_eq!(device.state_vector(), trusted.state_vector());
+        assert!(device.dimensions().iter().all(|dim| dim.counter() == 1));
+    }
+    
+    #[test]
+    fn test_convergence_dimension_mismatch() {
+        let mut state = DynamicIntegrityState::new(3, [1u8; 32]);
+        let before = state.state_vector();
+        
+        assert!(matches!(
+            state.convergence_constant(&[[0u8; 32]; 4]),
+            Err(IntegrityStateError::DimensionMismatch { found: 4, expected: 3 })
+        ));
+        assert!(matches!(
+            state.apply_convergence(&[[1u8; 32]; 2]),
+            Err(IntegrityStateError::DimensionMismatch { found: 2, expected: 3 })
+        ));
+        assert_eq!(state.state_vector(), before);
+    }
 }
@@ -0,0 +1,127 @@
+//! Entropy modes and deterministic entropy derivation.
+//!
+//! ## Conformance Classification
+//!
+//! **NORMATIVE** - Mode identifiers and the deterministic derivation SHALL be
+//! implemented exactly as specified here.
+//!
+//! Every accumulation takes an entropy input. A runtime either draws it from
+//! the operating system (`Random`) or derives it from the master seed
+//! (`Deterministic`):
+//!
+//! ```text
+//! entropy(d, c) = KDF("isa.entropy.drbg", master_seed || d || c)
+//! ```
+//!
+//! with `d` the dimension index as a little-endian u32 and `c` the counter the
+//! dimension reaches with the event as a little-endian u64. `KDF` is the
+//! state's hash suite.
+//!
+//! In deterministic mode anyone holding the master seed and the event stream
+//! can recompute the state exactly, which lets a trusted authority shadow a
+//! device. The same party can also predict every future entropy input, so the
+//! mode trades unpredictability for verifiability. The mode is recorded in
+//! serialized state (see [`crate::canonical`]).
+
+use crate::kdf::Kdf;
+use crate::suite::HashSuite;
+use crate::STATE_SIZE;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// Where the entropy input of each accumulation comes from.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[repr(u8)]
+pub enum EntropyMode {
+    /// Fresh randomness from the platform for every event.
+    #[default]
+    Random = 0,
+    /// Derived from the master seed, dimension index and counter.
+    Deterministic = 1,
+}
+
+impl EntropyMode {
+    /// Identifier recorded in serialized state.
+    pub fn id(self) -> u8 {
+        self as u8
+    }
+
+    /// Look up a mode by its serialized identifier.
+    pub fn from_id(id: u8) -> Option<Self> {
+        match id {
+            0 => Some(EntropyMode::Random),
+            1 => Some(EntropyMode::Deterministic),
+            _ => None,
+        }
+    }
+
+    /// Name used in configuration files.
+    pub fn name(self) -> &'static str {
+        match self {
+            EntropyMode::Random => "random",
+            EntropyMode::Deterministic => "deterministic",
+        }
+    }
+
+    /// Look up a mode by its configuration name.
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "random" => Some(EntropyMode::Random),
+            "deterministic" => Some(EntropyMode::Deterministic),
+            _ => None,
+        }
+    }
+}
+
+/// Entropy for the event that takes dimension `dimension` to counter `counter`.
+pub fn deterministic_entropy_with_suite<H: HashSuite>(
+    master_seed: &[u8; STATE_SIZE],
+    dimension: u32,
+    counter: u64,
+) -> [u8; STATE_SIZE] {
+    Kdf::<H>::derive_key_with_suite(
+        b"isa.entropy.drbg",
+        &[master_seed, &dimension.to_le_bytes(), &counter.to_le_bytes()],
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::suite::{Blake3Suite, Sha256HkdfSuite};
+
+    #[test]
+    fn test_mode_ids_and_names() {
+        for mode in [EntropyMode::Random, EntropyMode::Deterministic] {
+            assert_eq!(EntropyMode::from_id(mode.id()), Some(mode));
+            assert_eq!(EntropyMode::from_name(mode.name()), Some(mode));
+        }
+        assert_eq!(EntropyMode::from_id(2), None);
+        assert_eq!(EntropyMode::default(), EntropyMode::Random);
+    }
+
+    #[test]
+    fn test_entropy_separates_dimensions_and_counters() {
+        let seed = [1u8; 32];
+        let base = deterministic_entropy_with_suite::<Blake3Suite>(&seed, 0, 1);
+
+        assert_eq!(base, deterministic_entropy_with_suite::<Blake3Suite>(&seed, 0, 1));
+        assert_ne!(base, deterministic_entropy_with_suite::<Blake3Suite>(&seed, 1, 1));
+        assert_ne!(base, deterministic_entropy_with_suite::<Blake3Suite>(&seed, 0, 2));
+        assert_ne!(base, deterministic_entropy_with_suite::<Blake3Suite>(&[2u8; 32], 0, 1));
+    }
+
+    #[test]
+    fn test_entropy_vector_sha256_hkdf() {
+        // HKDF-SHA256(salt = "isa.entropy.drbg", IKM = [0x01; 32] || 02000000 || 0700000000000000)
+        let expected = [
+            0x7c, 0xd5, 0xa0, 0xda, 0x90, 0x0a, 0xff, 0x27,
+            0x77, 0xc2, 0xdd, 0x03, 0x50, 0x13, 0x6d, 0x1e,
+            0x74, 0xa4, 0xe9, 0x5e, 0xb6, 0x0e, 0x88, 0x70,
+            0x2c, 0xde, 0xdb, 0x9b, 0xfa, 0x1b, 0xfc, 0xb7,
+        ];
+        assert_eq!(deterministic_entropy_with_suite::<Sha256HkdfSuite>(&[1u8; 32], 2, 7), expected);
+    }
+}
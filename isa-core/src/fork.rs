@@ -0,0 +1,255 @@
+//! Fork and clone evidence.
+//!
+//! ## Conformance Classification
+//!
+//! **NORMATIVE** - Evidence construction, encoding and verification SHALL be
+//! implemented exactly as specified here.
+//!
+//! A dimension's history is a single chain of receipts. Two receipts that
+//! start from the same commitment at the same counter but end at different
+//! commitments prove that the state was continued twice from one point: the
+//! device was cloned, or its history was rewritten. [`ForkEvidence`] packages
+//! the common ancestor and both receipts so that anyone can check it offline,
+//! without the master seed or the raw states.
+//!
+//! ## Verification
+//!
+//! Evidence is valid when both receipts are at counter `ancestor_counter + 1`,
+//! both start at `ancestor_commitment`, and their new commitments differ.
+//! Receipts are not signed, so evidence proves a fork only for receipts known
+//! to come from the device (for example, receipts chained to commitments the
+//! device published or signed).
+//!
+//! ## Encoding
+//!
+//! ```text
+//! ancestor_counter (8) || ancestor_commitment (32) || left receipt (104) || right receipt (104)
+//! ```
+//!
+//! with receipts encoded by [`AccumulationReceipt::to_bytes`] and all integers
+//! little-endian.
+
+use crate::integrity_state::IntegrityStateError;
+use crate::receipt::AccumulationReceipt;
+use crate::STATE_SIZE;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// Portable proof that one dimension was continued twice from the same state.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ForkEvidence {
+    /// Counter of the common ancestor state.
+    pub ancestor_counter: u64,
+    /// Commitment to the common ancestor state.
+    pub ancestor_commitment: [u8; STATE_SIZE],
+    /// Receipt continuing the ancestor on one side of the fork.
+    pub left: AccumulationReceipt,
+    /// Receipt continuing the ancestor on the other side.
+    pub right: AccumulationReceipt,
+}
+
+/// Reason fork evidence failed verification.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ForkFailure {
+    /// A receipt is not at the counter following the ancestor.
+    CounterMismatch,
+    /// A receipt does not start at the ancestor commitment.
+    AncestorMismatch,
+    /// Both receipts end at the same commitment.
+    NoConflict,
+}
+
+impl core::fmt::Display for ForkFailure {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let reason = match self {
+            ForkFailure::CounterMismatch => "receipt counter does not follow the ancestor",
+            ForkFailure::AncestorMismatch => "receipt does not start at the ancestor",
+            ForkFailure::NoConflict => "receipts end at the same commitment",
+        };
+        f.write_str(reason)
+    }
+}
+
+impl ForkEvidence {
+    /// Length of the encoding produced by [`ForkEvidence::to_bytes`].
+    pub const ENCODED_LEN: usize = 8 + STATE_SIZE + 2 * AccumulationReceipt::ENCODED_LEN;
+
+    /// Check that the evidence proves a fork.
+    pub fn verify(&self) -> Result<(), IntegrityStateError> {
+        let fail = |failure| Err(IntegrityStateError::InvalidForkEvidence(failure));
+        let counter = self.ancestor_counter.wrapping_add(1);
+
+        if self.left.counter != counter || self.right.counter != counter {
+            return fail(ForkFailure::CounterMismatch);
+        }
+        if self.left.previous_commitment != self.ancestor_commitment
+            || self.right.previous_commitment != self.ancestor_commitment
+        {
+            return fail(ForkFailure::AncestorMismatch);
+        }
+        if self.left.new_commitment == self.right.new_commitment {
+            return fail(ForkFailure::NoConflict);
+        }
+        Ok(())
+    }
+
+    /// Encode the evidence as a fixed-size byte string (see the module docs).
+    pub fn to_bytes(&self) -> [u8; Self::ENCODED_LEN] {
+        let mut bytes = [0u8; Self::ENCODED_LEN];
+        bytes[0..8].copy_from_slice(&self.ancestor_counter.to_le_bytes());
+        bytes[8..8 + STATE_SIZE].copy_from_slice(&self.ancestor_commitment);
+        let (left, right) = bytes[8 + STATE_SIZE..].split_at_mut(AccumulationReceipt::ENCODED_LEN);
+        left.copy_from_slice(&self.left.to_bytes());
+        right.copy_from_slice(&self.right.to_bytes());
+        bytes
+    }
+
+    /// Decode evidence produced by [`ForkEvidence::to_bytes`].
+    ///
+    /// Decoding does not verify the evidence; call [`ForkEvidence::verify`].
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, IntegrityStateError> {
+        if bytes.len() != Self::ENCODED_LEN {
+            return Err(IntegrityStateError::Truncated);
+        }
+
+        let mut counter = [0u8; 8];
+        counter.copy_from_slice(&bytes[0..8]);
+        let mut ancestor_commitment = [0u8; STATE_SIZE];
+        ancestor_commitment.copy_from_slice(&bytes[8..8 + STATE_SIZE]);
+        let (left, right) = bytes[8 + STATE_SIZE..].split_at(AccumulationReceipt::ENCODED_LEN);
+
+        Ok(Self {
+            ancestor_counter: u64::from_le_bytes(counter),
+            ancestor_commitment,
+            left: AccumulationReceipt::from_bytes(left)?,
+            right: AccumulationReceipt::from_bytes(right)?,
+        })
+    }
+}
+
+/// Look for a fork between two receipt chains observed for the same dimension.
+///
+/// The chains may cover different windows of history; they are aligned by
+/// counter. Returns evidence for the earliest point (in `right`'s order) where
+/// both chains continue the same commitment differently, or `None` if the
+/// chains agree wherever they overlap. A fork that happened before both
+/// windows shows up as differing previous commitments and cannot be
+/// attributed to a common ancestor, so it is not reported.
+pub fn find_fork(left: &[AccumulationReceipt], right: &[AccumulationReceipt]) -> Option<ForkEvidence> {
+    right.iter().find_map(|r| {
+        let l = left.iter().find(|l| {
+            l.counter == r.counter && l.previous_commitment == r.previous_commitment
+        })?;
+        let evidence = ForkEvidence {
+            ancestor_counter: r.counter.wrapping_sub(1),
+            ancestor_commitment: r.previous_commitment,
+            left: *l,
+            right: *r,
+        };
+        evidence.verify().ok().map(|()| evidence)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::axis::AxisAccumulator;
+
+    #[cfg(not(feature = "std"))]
+    use alloc::vec::Vec;
+
+    fn accumulate(acc: &mut AxisAccumulator, events: &[&[u8]]) -> Vec<AccumulationReceipt> {
+        events.iter().map(|event| acc.accumulate(event, b"entropy", 1)).collect()
+    }
+
+    fn forked() -> (Vec<AccumulationReceipt>, Vec<AccumulationReceipt>) {
+        let mut device = AxisAccumulator::new([5u8; 32]);
+        let mut history = accumulate(&mut device, &[b"tx:1", b"tx:2"]);
+        let mut clone = device.clone();
+
+        let mut left = history.clone();
+        left.extend(accumulate(&mut device, &[b"tx:3", b"tx:4"]));
+        history.extend(accumulate(&mut clone, &[b"tx:3'", b"tx:4'"]));
+        (left, history)
+    }
+
+    #[test]
+    fn test_fork_detected() {
+        let (left, right) = forked();
+        let evidence = find_fork(&left, &right).unwrap();
+
+        assert_eq!(evidence.ancestor_counter, 2);
+        assert_eq!(evidence.ancestor_commitment, left[1].new_commitment);
+        assert_eq!(evidence.left, left[2]);
+        assert_eq!(evidence.right, right[2]);
+        evidence.verify().unwrap();
+    }
+
+    #[test]
+    fn test_fork_in_overlapping_windows() {
+        let (left, right) = forked();
+        let evidence = find_fork(&left[1..], &right[2..]).unwrap();
+        assert_eq!(evidence.ancestor_counter, 2);
+
+        // Both windows start after the fork: no common ancestor
+        assert_eq!(find_fork(&left[3..], &right[3..]), None);
+    }
+
+    #[test]
+    fn test_lagging_chain_is_not_a_fork() {
+        let mut device = AxisAccumulator::new([5u8; 32]);
+        let receipts = accumulate(&mut device, &[b"tx:1", b"tx:2", b"tx:3"]);
+
+        assert_eq!(find_fork(&receipts, &receipts[..1]), None);
+        assert_eq!(find_fork(&receipts, &receipts), None);
+    }
+
+    #[test]
+    fn test_replay_with_other_entropy_is_a_fork() {
+        let mut device = AxisAccumulator::new([5u8; 32]);
+        let mut clone = device.clone();
+        let left = [device.accumulate(b"tx:1", b"entropy-a", 1)];
+        let right = [clone.accumulate(b"tx:1", b"entropy-b", 1)];
+
+        let evidence = find_fork(&left, &right).unwrap();
+        assert_eq!(evidence.left.event_digest, evidence.right.event_digest);
+    }
+
+    #[test]
+    fn test_tampered_evidence_rejected() {
+        let (left, right) = forked();
+        let evidence = find_fork(&left, &right).unwrap();
+        let rejects = |evidence: ForkEvidence, failure| {
+            assert!(matches!(
+                evidence.verify(),
+                Err(IntegrityStateError::InvalidForkEvidence(found)) if found == failure
+            ));
+        };
+
+        let mut counter = evidence;
+        counter.ancestor_counter = 5;
+        rejects(counter, ForkFailure::CounterMismatch);
+
+        let mut ancestor = evidence;
+        ancestor.right = right[3];
+        ancestor.right.counter = 3;
+        rejects(ancestor, ForkFailure::AncestorMismatch);
+
+        let mut same = evidence;
+        same.right = same.left;
+        rejects(same, ForkFailure::NoConflict);
+    }
+
+    #[test]
+    fn test_evidence_bytes_roundtrip() {
+        let (left, right) = forked();
+        let evidence = find_fork(&left, &right).unwrap();
+        let bytes = evidence.to_bytes();
+
+        assert_eq!(bytes.len(), 248);
+        assert_eq!(ForkEvidence::from_bytes(&bytes).unwrap(), evidence);
+        assert!(matches!(ForkEvidence::from_bytes(&bytes[..200]), Err(IntegrityStateError::Truncated)));
+    }
+}
@@ -17,19 +17,24 @@
 //! - No domain semantics in type system or API
 //! - Backward compatible via type aliases
 
+use crate::axis::AccumulationMode;
+use crate::canonical;
+use crate::commitment;
 use crate::dimension::DimensionAccumulator;
-use crate::divergence::CircularDistance;
+use crate::divergence::{modular_sub, Divergence256};
+use crate::entropy::EntropyMode;
+use crate::fork::ForkFailure;
+use crate::migration::MigrationFailure;
+use crate::report::{DimensionDivergence, DivergenceReport};
 use crate::version::Version;
+use crate::suite::{suite_name, Blake3Suite, HashSuite};
 use crate::STATE_SIZE;
 use zeroize::{Zeroize, ZeroizeOnDrop};
 
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 
-#[cfg(all(feature = "serde", not(feature = "std")))]
-extern crate alloc;
-
-#[cfg(all(feature = "serde", not(feature = "std")))]
+#[cfg(not(feature = "std"))]
 use alloc::vec::Vec;
 
 /// Opaque dimension identifier.
@@ -78,80 +83,88 @@ impl DimensionId {
 ///
 /// This is the domain-agnostic replacement for `MultiAxisState`.
 /// Dimensions are indexed 0..N and have no semantic meaning at this layer.
+///
+/// The hash suite `H` (BLAKE3 by default) is part of the type, so states
+/// produced by different suites cannot be compared or combined.
 #[derive(Clone, Zeroize, ZeroizeOnDrop)]
-pub struct IntegrityState<const N: usize> {
-    dimensions: [DimensionAccumulator; N],
+pub struct IntegrityState<const N: usize, H: HashSuite = Blake3Suite> {
+    dimensions: [DimensionAccumulator<H>; N],
     #[zeroize(skip)]
     version: Version,
+    #[zeroize(skip)]
+    entropy_mode: EntropyMode,
 }
 
-// Serde implementation for IntegrityState<3>
-// Bincode works better with derive than manual implementation
+// Serialized as a tuple of N dimensions followed by the version and the
+// entropy mode. Tuples without the entropy mode (v0.1.0) load as random.
 #[cfg(feature = "serde")]
-impl Serialize for IntegrityState<3> {
+impl<const N: usize, H: HashSuite> Serialize for IntegrityState<N, H> {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
     where
         S: serde::Serializer,
     {
         use serde::ser::SerializeTuple;
-        let mut tuple = serializer.serialize_tuple(4)?;
-        tuple.serialize_element(&self.dimensions[0])?;
-        tuple.serialize_element(&self.dimensions[1])?;
-        tuple.serialize_element(&self.dimensions[2])?;
+        let mut tuple = serializer.serialize_tuple(N + 2)?;
+        for dimension in &self.dimensions {
+            tuple.serialize_element(dimension)?;
+        }
         tuple.serialize_element(&self.version)?;
+        tuple.serialize_element(&self.entropy_mode)?;
         tuple.end()
     }
 }
 
 #[cfg(feature = "serde")]
-impl<'de> Deserialize<'de> for IntegrityState<3> {
+impl<'de, const N: usize, H: HashSuite> Deserialize<'de> for IntegrityState<N, H> {
     fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
     where
         D: serde::Deserializer<'de>,
     {
         use serde::de::{self, Visitor, SeqAccess};
         
-        struct IntegrityStateVisitor;
+        struct IntegrityStateVisitor<const N: usize, H>(core::marker::PhantomData<H>);
         
-        impl<'de> Visitor<'de> for IntegrityStateVisitor {
-            type Value = IntegrityState<3>;
+        impl<'de, const N: usize, H: HashSuite> Visitor<'de> for IntegrityStateVisitor<N, H> {
+            type Value = IntegrityState<N, H>;
             
             fn expecting(&self, formatter: &mut core::fmt::Formatter) -> core::fmt::Result {
-                formatter.write_str("IntegrityState<3> as tuple")
+                write!(formatter, "IntegrityState<{}> as tuple", N)
             }
             
-            fn visit_seq<V>(self, mut seq: V) -> Result<IntegrityState<3>, V::Error>
+            fn visit_seq<V>(self, mut seq: V) -> Result<IntegrityState<N, H>, V::Error>
             where
                 V: SeqAccess<'de>,
             {
-                let dim0 = seq.next_element()?
-                    .ok_or_else(|| de::Error::invalid_length(0, &self))?;
-                let dim1 = seq.next_element()?
-                    .ok_or_else(|| de::Error::invalid_length(1, &self))?;
-                let dim2 = seq.next_element()?
-                    .ok_or_else(|| de::Error::invalid_length(2, &self))?;
+                let mut dimensions = Vec::with_capacity(N);
+                for i in 0..N {
+                    let dimension = seq.next_element()?
+                        .ok_or_else(|| de::Error::invalid_length(i, &self))?;
+                    dimensions.push(dimension);
+                }
                 let version = seq.next_element()?
-                    .ok_or_else(|| de::Error::invalid_length(3, &self))?;
+                    .ok_or_else(|| de::Error::invalid_length(N, &self))?;
+                let entropy_mode = seq.next_element()?.unwrap_or_default();
                 
                 Ok(IntegrityState {
-                    dimensions: [dim0, dim1, dim2],
+                    dimensions: into_array(dimensions)
+                        .map_err(|_| de::Error::invalid_length(N, &self))?,
                     version,
+                    entropy_mode,
                 })
             }
         }
         
-        deserializer.deserialize_tuple(4, IntegrityStateVisitor)
+        deserializer.deserialize_tuple(
+            N + 2,
+            IntegrityStateVisitor::<N, H>(core::marker::PhantomData),
+        )
     }
 }
 
 impl<const N: usize> IntegrityState<N> {
     /// Create a new integrity state from per-dimension seeds.
     pub fn new(seeds: [[u8; STATE_SIZE]; N]) -> Self {
-        let dimensions = seeds.map(|seed| DimensionAccumulator::new(seed));
-        Self {
-            dimensions,
-            version: Version::current(),
-        }
+        Self::new_with_suite(seeds)
     }
 
     /// Create integrity state from a master seed using KDF.
@@ -161,29 +174,90 @@ impl<const N: usize> IntegrityState<N> {
     ///
     /// Where dimension_id is an opaque 16-byte identifier with no semantic meaning.
     pub fn from_master_seed(master_seed: [u8; STATE_SIZE]) -> Self {
+        Self::from_master_seed_with_suite(master_seed)
+    }
+}
+
+impl<const N: usize, H: HashSuite> IntegrityState<N, H> {
+    /// Create a new integrity state using hash suite `H`.
+    pub fn new_with_suite(seeds: [[u8; STATE_SIZE]; N]) -> Self {
+        let dimensions = seeds.map(DimensionAccumulator::new_with_suite);
+        Self {
+            dimensions,
+            version: Version::current(),
+            entropy_mode: EntropyMode::Random,
+        }
+    }
+
+    /// Create integrity state from a master seed using hash suite `H`.
+    ///
+    /// Seeds are derived as in `from_master_seed`, with `H` as the KDF.
+    pub fn from_master_seed_with_suite(master_seed: [u8; STATE_SIZE]) -> Self {
         use crate::kdf::Kdf;
 
-        let mut dimensions = core::array::from_fn(|_| DimensionAccumulator::new([0u8; STATE_SIZE]));
-        
-        for i in 0..N {
+        let dimensions = core::array::from_fn(|i| {
             let dimension_id = DimensionId::from_index(i);
-            let seed = Kdf::derive_key(&dimension_id.to_kdf_label(), &[&master_seed]);
-            dimensions[i] = DimensionAccumulator::new(seed);
+            let seed = Kdf::<H>::derive_key_with_suite(&dimension_id.to_kdf_label(), &[&master_seed]);
+            DimensionAccumulator::new_with_suite(seed)
+        });
+
+        Self {
+            dimensions,
+            version: Version::current(),
+            entropy_mode: EntropyMode::Random,
         }
+    }
 
+    /// Build a state from already-positioned dimensions at the current version.
+    pub(crate) fn from_dimensions(dimensions: [DimensionAccumulator<H>; N]) -> Self {
         Self {
             dimensions,
             version: Version::current(),
+            entropy_mode: EntropyMode::Random,
+        }
+    }
+
+    /// Get the id of the hash suite this state was produced with.
+    pub fn hash_suite(&self) -> u8 {
+        H::ID
+    }
+
+    /// Use the given accumulation mode in every dimension.
+    ///
+    /// Intended for freshly created states; the mode is persisted with the state.
+    pub fn with_accumulation_mode(mut self, mode: AccumulationMode) -> Self {
+        for dimension in self.dimensions.iter_mut() {
+            dimension.set_mode(mode);
         }
+        self
+    }
+
+    /// Record that entropy inputs are produced in the given mode (see [`crate::entropy`]).
+    ///
+    /// The mode does not affect accumulation; it is persisted with the state so
+    /// verifiers know whether the state can be recomputed from the master seed.
+    pub fn with_entropy_mode(mut self, mode: EntropyMode) -> Self {
+        self.entropy_mode = mode;
+        self
+    }
+
+    /// Get the entropy mode recorded for this state.
+    pub fn entropy_mode(&self) -> EntropyMode {
+        self.entropy_mode
+    }
+
+    /// Get all dimensions in index order.
+    pub fn dimensions(&self) -> &[DimensionAccumulator<H>] {
+        &self.dimensions
     }
 
     /// Get a reference to a specific dimension by index.
-    pub fn dimension(&self, index: usize) -> Option<&DimensionAccumulator> {
+    pub fn dimension(&self, index: usize) -> Option<&DimensionAccumulator<H>> {
         self.dimensions.get(index)
     }
 
     /// Get a mutable reference to a specific dimension by index.
-    pub fn dimension_mut(&mut self, index: usize) -> Option<&mut DimensionAccumulator> {
+    pub fn dimension_mut(&mut self, index: usize) -> Option<&mut DimensionAccumulator<H>> {
         self.dimensions.get_mut(index)
     }
 
@@ -201,64 +275,118 @@ impl<const N: usize> IntegrityState<N> {
         DimensionVector { values }
     }
 
+    /// Commitment to the version, dimension count, states and counters.
+    ///
+    /// Two states have the same commitment exactly when all of those agree
+    /// (see [`crate::commitment`]).
+    pub fn commitment(&self) -> [u8; STATE_SIZE] {
+        commitment::commitment_with_suite(self.version, &self.dimensions)
+    }
+
     /// Calculate divergence between this state and another.
     ///
     /// Returns the circular distance for each dimension independently.
     pub fn divergence(&self, other: &Self) -> DivergenceVector<N> {
-        let mut values = [[0u8; STATE_SIZE]; N];
-        for i in 0..N {
-            values[i] = CircularDistance::min_distance(
-                &self.dimensions[i].state(),
-                &other.dimensions[i].state(),
-            );
-        }
+        let values = core::array::from_fn(|i| {
+            Divergence256::between(&self.dimensions[i].state(), &other.dimensions[i].state())
+        });
         DivergenceVector { values }
     }
+
+    /// Compare this state against `reference`, dimension by dimension.
+    ///
+    /// Unlike [`divergence`](Self::divergence), the report takes counters into
+    /// account and tells a lagging dimension from a conflicting one (see
+    /// [`crate::report`]).
+    pub fn divergence_report(&self, reference: &Self) -> DivergenceReport {
+        DivergenceReport::new(
+            self.dimensions.iter()
+                .zip(&reference.dimensions)
+                .map(|(dim, other)| {
+                    DimensionDivergence::new(&dim.state(), dim.counter(), &other.state(), other.counter())
+                })
+                .collect(),
+        )
+    }
+
+    /// Calculate the convergence constant that restores this state to `trusted`.
+    ///
+    /// Computes K = (S_trusted - S) mod 2^256 for each dimension (Equation 8).
+    pub fn convergence_constant(&self, trusted: &DimensionVector<N>) -> DimensionVector<N> {
+        let values = core::array::from_fn(|i| {
+            modular_sub(&trusted.values[i], &self.dimensions[i].state())
+        });
+        DimensionVector { values }
+    }
+
+    /// Apply a convergence constant: S = (S + K) mod 2^256 for each dimension.
+    ///
+    /// Counters and accumulation modes are kept, so a state restored with the
+    /// constant from [`convergence_constant`](Self::convergence_constant)
+    /// continues on the trusted trajectory.
+    pub fn apply_convergence(&mut self, convergence_constant: &DimensionVector<N>) {
+        for (dim, k) in self.dimensions.iter_mut().zip(&convergence_constant.values) {
+            dim.apply_convergence(k);
+        }
+    }
 }
 
-impl<const N: usize> core::fmt::Debug for IntegrityState<N> {
+impl<const N: usize, H: HashSuite> core::fmt::Debug for IntegrityState<N, H> {
     fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         f.debug_struct("IntegrityState")
             .field("dimension_count", &N)
+            .field("hash_suite", &H::NAME)
             .field("dimensions", &self.dimensions)
             .field("version", &self.version)
+            .field("entropy_mode", &self.entropy_mode)
             .finish()
     }
 }
 
-// Serialization methods for IntegrityState<3>
-impl IntegrityState<3> {
-    #[cfg(all(feature = "serde", feature = "std"))]
-    pub fn to_bytes(&self) -> Result<Vec<u8>, bincode::Error> {
-        let versioned = VersionedIntegrityState {
-            version: self.version,
-            state: self.clone(),
-        };
-        bincode::serialize(&versioned)
+// Versioned serialization for any dimension count
+impl<const N: usize, H: HashSuite> IntegrityState<N, H> {
+    /// Encode this state in the canonical format (see [`crate::canonical`]).
+    pub fn to_canonical_bytes(&self) -> Vec<u8> {
+        canonical::encode(self.version, self.entropy_mode, &self.dimensions)
     }
 
-    #[cfg(all(feature = "serde", not(feature = "std")))]
+    /// Decode a state from the canonical format.
+    ///
+    /// Fails with `DimensionMismatch` if the encoding holds a different number
+    /// of dimensions than `N`, and with `HashSuiteMismatch` if it was produced
+    /// by a suite other than `H`.
+    pub fn from_canonical_bytes(bytes: &[u8]) -> Result<Self, IntegrityStateError> {
+        let (header, dimensions) = canonical::decode::<H>(bytes)?;
+        check_dimension_count(dimensions.len(), Some(N))?;
+        let dimensions: [DimensionAccumulator<H>; N] = dimensions
+            .try_into()
+            .map_err(|_| IntegrityStateError::DeserializationFailed)?;
+        Ok(Self { dimensions, version: header.version, entropy_mode: header.entropy_mode })
+    }
+
+    /// Serialize this state as a versioned payload.
+    ///
+    /// The payload records the state version and the dimension count, so it can
+    /// be loaded back as `IntegrityState<N>` or as a `DynamicIntegrityState`.
+    #[cfg(feature = "serde")]
     pub fn to_bytes(&self) -> Result<Vec<u8>, bincode::Error> {
-        let versioned = VersionedIntegrityState {
-            version: self.version,
-            state: self.clone(),
-        };
-        bincode::serialize(&versioned)
+        encode_versioned(self.version, self.entropy_mode, &self.dimensions)
     }
 
+    /// Deserialize a versioned payload produced by `to_bytes`.
+    ///
+    /// Canonical encodings produced by `to_canonical_bytes` are also accepted.
+    ///
+    /// Fails with `IncompatibleVersion` if the payload was written by an
+    /// incompatible version, with `DimensionMismatch` if it holds a
+    /// different number of dimensions than `N`, and with `HashSuiteMismatch`
+    /// if it was produced by a suite other than `H`.
     #[cfg(feature = "serde")]
     pub fn from_bytes(bytes: &[u8]) -> Result<Self, IntegrityStateError> {
-        let versioned: VersionedIntegrityState = bincode::deserialize(bytes)
+        let (version, entropy_mode, dimensions) = decode_versioned(bytes, Some(N))?;
+        let dimensions = into_array(dimensions)
             .map_err(|_| IntegrityStateError::DeserializationFailed)?;
-
-        if !versioned.version.is_compatible(&Version::current()) {
-            return Err(IntegrityStateError::IncompatibleVersion {
-                found: versioned.version,
-                expected: Version::current(),
-            });
-        }
-
-        Ok(versioned.state)
+        Ok(Self { dimensions, version, entropy_mode })
     }
 }
 
@@ -346,27 +474,235 @@ impl<const N: usize> DimensionVector<N> {
 /// This is the domain-agnostic replacement for `DivergenceMetric`.
 #[derive(Debug, Clone, Copy)]
 pub struct DivergenceVector<const N: usize> {
-    pub values: [[u8; STATE_SIZE]; N],
+    pub values: [Divergence256; N],
 }
 
 impl<const N: usize> DivergenceVector<N> {
     /// Get the divergence for a specific dimension.
-    pub fn get(&self, index: usize) -> Option<&[u8; STATE_SIZE]> {
+    pub fn get(&self, index: usize) -> Option<&Divergence256> {
         self.values.get(index)
     }
 }
 
+/// Size of a v0.1.0 `IntegrityState<3>` payload, which predates the
+/// dimension count field: version (6) + 3 × (state 32 + counter 8) + version (6).
+#[cfg(feature = "serde")]
+const LEGACY_PAYLOAD_LEN: usize = 132;
+
+/// Whether `bytes` has the structure of a v0.1.0 payload: its length, and
+/// the same version written before and after the dimensions.
+#[cfg(feature = "serde")]
+fn is_legacy_payload(bytes: &[u8]) -> bool {
+    bytes.len() == LEGACY_PAYLOAD_LEN && bytes[..6] == bytes[LEGACY_PAYLOAD_LEN - 6..]
+}
+
+/// State and counter of one dimension in a versioned payload. Payloads keep
+/// this fixed layout whatever the serde representation of the accumulator.
+#[cfg(feature = "serde")]
+#[derive(Serialize, Deserialize)]
+struct DimensionRecord {
+    state: [u8; STATE_SIZE],
+    counter: u64,
+}
+
+#[cfg(feature = "serde")]
+impl DimensionRecord {
+    fn new<H: HashSuite>(dimension: &DimensionAccumulator<H>) -> Self {
+        Self { state: dimension.state(), counter: dimension.counter() }
+    }
+
+    fn into_dimension<H: HashSuite>(self) -> DimensionAccumulator<H> {
+        DimensionAccumulator::from_state_with_suite(self.state, self.counter)
+    }
+}
+
 #[cfg(feature = "serde")]
 #[derive(Serialize, Deserialize)]
 struct VersionedIntegrityState {
     version: Version,
-    state: IntegrityState<3>,
+    #[allow(dead_code)] // checked via VersionedIntegrityStateHeader
+    hash_suite: u8,
+    dimension_count: u32,
+    dimensions: Vec<DimensionRecord>,
+    accumulation_modes: Vec<u8>,
+    entropy_mode: u8,
+}
+
+#[cfg(feature = "serde")]
+#[derive(Deserialize)]
+struct VersionedIntegrityStateHeader {
+    version: Version,
+    hash_suite: u8,
+    dimension_count: u32,
+}
+
+/// v0.1.0 payload: the version, then `IntegrityState<3>` as a tuple of
+/// three dimensions followed by the version again.
+#[cfg(feature = "serde")]
+#[derive(Deserialize)]
+struct LegacyVersionedIntegrityState {
+    version: Version,
+    dimensions: [DimensionRecord; 3],
+    #[allow(dead_code)]
+    state_version: Version,
+}
+
+/// Encode dimensions as a versioned payload (shared with `DynamicIntegrityState`).
+#[cfg(feature = "serde")]
+pub(crate) fn encode_versioned<H: HashSuite>(
+    version: Version,
+    entropy_mode: EntropyMode,
+    dimensions: &[DimensionAccumulator<H>],
+) -> Result<Vec<u8>, bincode::Error> {
+    let dimension_count = u32::try_from(dimensions.len())
+        .map_err(|_| bincode::ErrorKind::SizeLimit)?;
+    bincode::serialize(&VersionedIntegrityState {
+        version,
+        hash_suite: H::ID,
+        dimension_count,
+        dimensions: dimensions.iter().map(DimensionRecord::new).collect(),
+        accumulation_modes: dimensions.iter().map(|dim| dim.mode().id()).collect(),
+        entropy_mode: entropy_mode.id(),
+    })
+}
+
+/// Decode a versioned payload, checking version compatibility, the hash
+/// suite and, if `expected_dimensions` is given, the stored dimension count.
+///
+/// Canonical encodings and v0.1.0 payloads (three BLAKE3 dimensions, no
+/// suite or count field) are also accepted.
+#[cfg(feature = "serde")]
+pub(crate) fn decode_versioned<H: HashSuite>(
+    bytes: &[u8],
+    expected_dimensions: Option<usize>,
+) -> Result<(Version, EntropyMode, Vec<DimensionAccumulator<H>>), IntegrityStateError> {
+    let (version, entropy_mode, dimensions) = if canonical::is_canonical(bytes) {
+        let (header, dimensions) = canonical::decode::<H>(bytes)?;
+        (header.version, header.entropy_mode, dimensions)
+    } else if is_legacy_payload(bytes) {
+        let legacy: LegacyVersionedIntegrityState = bincode::deserialize(bytes)
+            .map_err(|_| IntegrityStateError::DeserializationFailed)?;
+        check_compatible(legacy.version)?;
+        check_hash_suite::<H>(Blake3Suite::ID)?;
+        let dimensions = legacy.dimensions.into_iter()
+            .map(DimensionRecord::into_dimension)
+            .collect();
+        (legacy.version, EntropyMode::Random, dimensions)
+    } else {
+        let header: VersionedIntegrityStateHeader = bincode::deserialize(bytes)
+            .map_err(|_| IntegrityStateError::DeserializationFailed)?;
+        check_compatible(header.version)?;
+        check_hash_suite::<H>(header.hash_suite)?;
+        check_dimension_count(header.dimension_count as usize, expected_dimensions)?;
+
+        let versioned: VersionedIntegrityState = bincode::deserialize(bytes)
+            .map_err(|_| IntegrityStateError::DeserializationFailed)?;
+        if versioned.dimensions.len() != versioned.dimension_count as usize
+            || versioned.accumulation_modes.len() != versioned.dimensions.len()
+        {
+            return Err(IntegrityStateError::DeserializationFailed);
+        }
+        let dimensions = versioned.dimensions.into_iter()
+            .zip(&versioned.accumulation_modes)
+            .map(|(record, &id)| {
+                let mode = AccumulationMode::from_id(id)
+                    .ok_or(IntegrityStateError::UnknownAccumulationMode(id))?;
+                Ok(record.into_dimension::<H>().with_mode(mode))
+            })
+            .collect::<Result<Vec<_>, IntegrityStateError>>()?;
+        let entropy_mode = EntropyMode::from_id(versioned.entropy_mode)
+            .ok_or(IntegrityStateError::UnknownEntropyMode(versioned.entropy_mode))?;
+        (versioned.version, entropy_mode, dimensions)
+    };
+
+    check_dimension_count(dimensions.len(), expected_dimensions)?;
+    Ok((version, entropy_mode, dimensions))
+}
+
+/// Read the hash suite id recorded in an encoded state without decoding it.
+///
+/// Accepts the same encodings as `IntegrityState::from_bytes`; v0.1.0
+/// payloads predate suite selection and report BLAKE3. Fails with
+/// `UnknownHashSuite` if the recorded id is not a known suite.
+#[cfg(feature = "serde")]
+pub fn encoded_hash_suite(bytes: &[u8]) -> Result<u8, IntegrityStateError> {
+    let id = if canonical::is_canonical(bytes) {
+        canonical::decode_header(bytes)?.hash_suite
+    } else if is_legacy_payload(bytes) {
+        Blake3Suite::ID
+    } else {
+        let header: VersionedIntegrityStateHeader = bincode::deserialize(bytes)
+            .map_err(|_| IntegrityStateError::DeserializationFailed)?;
+        header.hash_suite
+    };
+
+    if suite_name(id).is_none() {
+        return Err(IntegrityStateError::UnknownHashSuite(id));
+    }
+    Ok(id)
+}
+
+/// Check that a stored suite id is known and matches `H`.
+pub(crate) fn check_hash_suite<H: HashSuite>(found: u8) -> Result<(), IntegrityStateError> {
+    if suite_name(found).is_none() {
+        return Err(IntegrityStateError::UnknownHashSuite(found));
+    }
+    if found != H::ID {
+        return Err(IntegrityStateError::HashSuiteMismatch {
+            found,
+            expected: H::ID,
+        });
+    }
+    Ok(())
+}
+
+#[cfg(feature = "serde")]
+fn check_compatible(found: Version) -> Result<(), IntegrityStateError> {
+    if !found.is_compatible(&Version::current()) {
+        return Err(IntegrityStateError::IncompatibleVersion {
+            found,
+            expected: Version::current(),
+        });
+    }
+    Ok(())
+}
+
+fn check_dimension_count(found: usize, expected: Option<usize>) -> Result<(), IntegrityStateError> {
+    match expected {
+        Some(expected) if expected != found => {
+            Err(IntegrityStateError::DimensionMismatch { found, expected })
+        }
+        _ => Ok(()),
+    }
+}
+
+#[cfg(feature = "serde")]
+#[allow(clippy::type_complexity)]
+fn into_array<const N: usize, H: HashSuite>(
+    dimensions: Vec<DimensionAccumulator<H>>,
+) -> Result<[DimensionAccumulator<H>; N], Vec<DimensionAccumulator<H>>> {
+    dimensions.try_into()
 }
 
 #[derive(Debug)]
 pub enum IntegrityStateError {
     DeserializationFailed,
     IncompatibleVersion { found: Version, expected: Version },
+    DimensionMismatch { found: usize, expected: usize },
+    Truncated,
+    BadMagic,
+    UnsupportedFormat(u16),
+    ChecksumMismatch,
+    UnknownHashSuite(u8),
+    HashSuiteMismatch { found: u8, expected: u8 },
+    InvalidMigration(MigrationFailure),
+    UnknownAccumulationMode(u8),
+    UnknownEntropyMode(u8),
+    RemovalNotSupported(AccumulationMode),
+    EmptyMultiset,
+    BrokenReceiptChain { index: usize },
+    EventNotIncluded { counter: u64 },
+    InvalidForkEvidence(ForkFailure),
 }
 
 impl core::fmt::Display for IntegrityStateError {
@@ -381,6 +717,50 @@ impl core::fmt::Display for IntegrityStateError {
                     expected.major, expected.minor, expected.patch
                 )
             }
+            IntegrityStateError::DimensionMismatch { found, expected } => {
+                write!(
+                    f,
+                    "Dimension count mismatch: found {}, expected {}",
+                    found, expected
+                )
+            }
+            IntegrityStateError::Truncated => write!(f, "Encoded state is truncated"),
+            IntegrityStateError::BadMagic => write!(f, "Encoded state has invalid magic bytes"),
+            IntegrityStateError::UnsupportedFormat(format) => {
+                write!(f, "Unsupported state format version: {}", format)
+            }
+            IntegrityStateError::ChecksumMismatch => write!(f, "Encoded state checksum mismatch"),
+            IntegrityStateError::UnknownHashSuite(id) => write!(f, "Unknown hash suite id: {}", id),
+            IntegrityStateError::HashSuiteMismatch { found, expected } => {
+                write!(
+                    f,
+                    "Hash suite mismatch: found {}, expected {}",
+                    suite_name(*found).unwrap_or("unknown"),
+                    suite_name(*expected).unwrap_or("unknown")
+                )
+            }
+            IntegrityStateError::InvalidMigration(failure) => {
+                write!(f, "Invalid migration record: {}", failure)
+            }
+            IntegrityStateError::UnknownAccumulationMode(id) => {
+                write!(f, "Unknown accumulation mode id: {}", id)
+            }
+            IntegrityStateError::UnknownEntropyMode(id) => {
+                write!(f, "Unknown entropy mode id: {}", id)
+            }
+            IntegrityStateError::RemovalNotSupported(mode) => {
+                write!(f, "Events cannot be removed in {} accumulation mode", mode.name())
+            }
+            IntegrityStateError::EmptyMultiset => write!(f, "Cannot remove an event from an empty multiset"),
+            IntegrityStateError::BrokenReceiptChain { index } => {
+                write!(f, "Receipt chain broken at receipt {}", index)
+            }
+            IntegrityStateError::EventNotIncluded { counter } => {
+                write!(f, "No receipt covers the event at counter {}", counter)
+            }
+            IntegrityStateError::InvalidForkEvidence(failure) => {
+                write!(f, "Invalid fork evidence: {}", failure)
+            }
         }
     }
 }
@@ -392,6 +772,12 @@ impl std::error::Error for IntegrityStateError {}
 mod tests {
     use super::*;
 
+    /// `device.state` written by v0.1.0 after one sale and one finance event,
+    /// from master seed `[7; 32]`: (version, state) with no dimension count,
+    /// where the state is three (state, counter) dimensions then the version.
+    #[cfg(feature = "serde")]
+    const LEGACY_FIXTURE: &str = "000001000000fbb2ae61a5ad574ac8456f4333e47de45deb00399b44909580e48dc7f33e4b9b0200000000000000da429a10243a94b1e1eabe023ed8a15927c792b2b0a6936dd41669d8eee21a350100000000000000531dfe3b027e9ec3691b0f0df058615be701e24567b663142e9486d11ec3a83b0100000000000000000001000000";
+
     // Normative test: 3-dimensional state (matches original MA-ISA)
     #[test]
     fn test_three_dimension_state_creation() {
@@ -463,6 +849,198 @@ mod tests {
         assert_eq!(state1.version(), state2.version());
     }
 
+    #[test]
+    #[cfg(feature = "serde")]
+    fn test_serialization_roundtrip_any_dimension_count() {
+        let master_seed = [1u8; 32];
+        let mut state5: IntegrityState<5> = IntegrityState::from_master_seed(master_seed);
+        state5.dimension_mut(4).unwrap().accumulate(b"event", b"entropy", 10);
+        let restored5 = IntegrityState::<5>::from_bytes(&state5.to_bytes().unwrap()).unwrap();
+        assert_eq!(state5.state_vector(), restored5.state_vector());
+        assert_eq!(restored5.dimension(4).unwrap().counter(), 1);
+
+        let state7: IntegrityState<7> = IntegrityState::from_master_seed(master_seed);
+        let restored7 = IntegrityState::<7>::from_bytes(&state7.to_bytes().unwrap()).unwrap();
+        assert_eq!(state7.state_vector(), restored7.state_vector());
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn test_deserialization_dimension_mismatch() {
+        let state5: IntegrityState<5> = IntegrityState::from_master_seed([1u8; 32]);
+        let bytes = state5.to_bytes().unwrap();
+
+        match IntegrityState::<3>::from_bytes(&bytes) {
+            Err(IntegrityStateError::DimensionMismatch { found, expected }) => {
+                assert_eq!(found, 5);
+                assert_eq!(expected, 3);
+            }
+            other => panic!("expected DimensionMismatch, got {:?}", other.map(|_| ())),
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn test_deserialization_legacy_payload() {
+        let legacy = hex::decode(LEGACY_FIXTURE).unwrap();
+        assert_eq!(legacy.len(), LEGACY_PAYLOAD_LEN);
+
+        let restored = IntegrityState::<3>::from_bytes(&legacy).unwrap();
+        assert_eq!(restored.version(), Version::new(0, 1, 0));
+        assert_eq!(restored.entropy_mode(), EntropyMode::Random);
+        let counters: Vec<u64> = restored.dimensions().iter().map(|dim| dim.counter()).collect();
+        assert_eq!(counters, vec![2, 1, 1]);
+        assert_eq!(
+            restored.dimension(0).unwrap().state().to_vec(),
+            hex::decode("fbb2ae61a5ad574ac8456f4333e47de45deb00399b44909580e48dc7f33e4b9b").unwrap()
+        );
+        assert_eq!(
+            restored.dimension(2).unwrap().state().to_vec(),
+            hex::decode("531dfe3b027e9ec3691b0f0df058615be701e24567b663142e9486d11ec3a83b").unwrap()
+        );
+
+        assert!(matches!(
+            IntegrityState::<5>::from_bytes(&legacy),
+            Err(IntegrityStateError::DimensionMismatch { found: 3, expected: 5 })
+        ));
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn test_legacy_length_alone_is_not_legacy() {
+        // Same length, but the trailing version does not repeat the leading one
+        let mut bytes = hex::decode(LEGACY_FIXTURE).unwrap();
+        bytes[LEGACY_PAYLOAD_LEN - 4] = 9;
+        assert!(!is_legacy_payload(&bytes));
+        assert!(IntegrityState::<3>::from_bytes(&bytes).is_err());
+        assert!(encoded_hash_suite(&bytes).is_err());
+
+        assert!(IntegrityState::<3>::from_bytes(&[0xA5u8; LEGACY_PAYLOAD_LEN]).is_err());
+    }
+
+    #[test]
+    fn test_canonical_roundtrip() {
+        let mut state: IntegrityState<5> = IntegrityState::from_master_seed([1u8; 32]);
+        state.dimension_mut(3).unwrap().accumulate(b"event", b"entropy", 10);
+
+        let bytes = state.to_canonical_bytes();
+        assert_eq!(bytes.len(), canonical::encoded_len(5));
+
+        let restored = IntegrityState::<5>::from_canonical_bytes(&bytes).unwrap();
+        assert_eq!(state.state_vector(), restored.state_vector());
+        assert_eq!(restored.dimension(3).unwrap().counter(), 1);
+
+        assert!(matches!(
+            IntegrityState::<3>::from_canonical_bytes(&bytes),
+            Err(IntegrityStateError::DimensionMismatch { found: 5, expected: 3 })
+        ));
+    }
+
+    #[test]
+    fn test_entropy_mode_roundtrip() {
+        let state: IntegrityState<3> = IntegrityState::from_master_seed([1u8; 32])
+            .with_entropy_mode(EntropyMode::Deterministic);
+
+        let restored = IntegrityState::<3>::from_canonical_bytes(&state.to_canonical_bytes()).unwrap();
+        assert_eq!(restored.entropy_mode(), EntropyMode::Deterministic);
+
+        #[cfg(feature = "serde")]
+        {
+            let restored = IntegrityState::<3>::from_bytes(&state.to_bytes().unwrap()).unwrap();
+            assert_eq!(restored.entropy_mode(), EntropyMode::Deterministic);
+
+            let mut value = serde_json::to_value(&state).unwrap();
+            let restored: IntegrityState<3> = serde_json::from_value(value.clone()).unwrap();
+            assert_eq!(restored.entropy_mode(), EntropyMode::Deterministic);
+
+            // v0.1.0 representation: no entropy mode after the version
+            value.as_array_mut().unwrap().pop();
+            let restored: IntegrityState<3> = serde_json::from_value(value).unwrap();
+            assert_eq!(restored.entropy_mode(), EntropyMode::Random);
+            assert_eq!(restored.state_vector(), state.state_vector());
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn test_from_bytes_accepts_both_encodings() {
+        let state: IntegrityState<3> = IntegrityState::from_master_seed([1u8; 32]);
+
+        let from_canonical = IntegrityState::<3>::from_bytes(&state.to_canonical_bytes()).unwrap();
+        let from_bincode = IntegrityState::<3>::from_bytes(&state.to_bytes().unwrap()).unwrap();
+        assert_eq!(from_canonical.state_vector(), state.state_vector());
+        assert_eq!(from_bincode.state_vector(), state.state_vector());
+    }
+
+    #[test]
+    fn test_hash_suites_produce_distinct_states() {
+        use crate::suite::{Sha256HkdfSuite, Shake256Suite};
+
+        let master_seed = [1u8; 32];
+        let blake3: IntegrityState<3> = IntegrityState::from_master_seed(master_seed);
+        let sha256 = IntegrityState::<3, Sha256HkdfSuite>::from_master_seed_with_suite(master_seed);
+        let shake = IntegrityState::<3, Shake256Suite>::from_master_seed_with_suite(master_seed);
+
+        assert_eq!(blake3.hash_suite(), 1);
+        assert_eq!(sha256.hash_suite(), 2);
+        assert_eq!(shake.hash_suite(), 3);
+        assert_ne!(blake3.state_vector(), sha256.state_vector());
+        assert_ne!(blake3.state_vector(), shake.state_vector());
+        assert_ne!(sha256.state_vector(), shake.state_vector());
+    }
+
+    #[test]
+    fn test_canonical_rejects_other_suite() {
+        use crate::suite::Sha256HkdfSuite;
+
+        let state = IntegrityState::<3, Sha256HkdfSuite>::from_master_seed_with_suite([1u8; 32]);
+        let bytes = state.to_canonical_bytes();
+        assert_eq!(bytes[6], Sha256HkdfSuite::ID);
+
+        assert!(IntegrityState::<3, Sha256HkdfSuite>::from_canonical_bytes(&bytes).is_ok());
+        assert!(matches!(
+            IntegrityState::<3>::from_canonical_bytes(&bytes),
+            Err(IntegrityStateError::HashSuiteMismatch { found: 2, expected: 1 })
+        ));
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn test_versioned_payload_rejects_other_suite() {
+        use crate::suite::Shake256Suite;
+
+        let state = IntegrityState::<4, Shake256Suite>::from_master_seed_with_suite([1u8; 32]);
+        let bytes = state.to_bytes().unwrap();
+
+        let restored = IntegrityState::<4, Shake256Suite>::from_bytes(&bytes).unwrap();
+        assert_eq!(restored.state_vector(), state.state_vector());
+        assert!(matches!(
+            IntegrityState::<4>::from_bytes(&bytes),
+            Err(IntegrityStateError::HashSuiteMismatch { found: 3, expected: 1 })
+        ));
+
+        // v0.1.0 payloads are always BLAKE3
+        let legacy = hex::decode(LEGACY_FIXTURE).unwrap();
+        assert!(matches!(
+            IntegrityState::<3, Shake256Suite>::from_bytes(&legacy),
+            Err(IntegrityStateError::HashSuiteMismatch { found: 1, expected: 3 })
+        ));
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn test_deserialization_incompatible_version() {
+        let state: IntegrityState<4> = IntegrityState::from_master_seed([1u8; 32]);
+        let mut bytes = state.to_bytes().unwrap();
+        // Major version lives in the first two bytes
+        bytes[0] = bytes[0].wrapping_add(1);
+
+        assert!(matches!(
+            IntegrityState::<4>::from_bytes(&bytes),
+            Err(IntegrityStateError::IncompatibleVersion { .. })
+        ));
+    }
+
     // Test with different dimension counts
     #[test]
     fn test_variable_dimension_count() {
@@ -477,4 +1055,105 @@ mod tests {
         assert!(state5.dimension(4).is_some());
         assert!(state5.dimension(5).is_none());
     }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn test_encoded_hash_suite() {
+        use crate::suite::Shake256Suite;
+
+        let state = IntegrityState::<4, Shake256Suite>::from_master_seed_with_suite([1u8; 32]);
+        assert_eq!(encoded_hash_suite(&state.to_canonical_bytes()).unwrap(), Shake256Suite::ID);
+        assert_eq!(encoded_hash_suite(&state.to_bytes().unwrap()).unwrap(), Shake256Suite::ID);
+
+        let mut bytes = state.to_canonical_bytes();
+        bytes[6] = 0x7f;
+        assert!(matches!(
+            encoded_hash_suite(&bytes),
+            Err(IntegrityStateError::UnknownHashSuite(0x7f))
+        ));
+    }
+
+    #[test]
+    fn test_additive_mode_divergence_is_missed_increments() {
+        use crate::divergence::CircularDistance;
+        use crate::kdf::phi_with_suite;
+
+        let mut honest: IntegrityState<2> = IntegrityState::from_master_seed([1u8; 32])
+            .with_accumulation_mode(AccumulationMode::Additive);
+        let lagging = honest.clone();
+
+        honest.dimension_mut(1).unwrap().accumulate(b"event", b"entropy", 10);
+
+        let divergence = honest.divergence(&lagging);
+        assert_eq!(divergence.values[0], [0u8; 32]);
+        assert_eq!(
+            CircularDistance::compute(&honest.dimension(1).unwrap().state(), &lagging.dimension(1).unwrap().state()),
+            phi_with_suite::<Blake3Suite>(b"event", b"entropy", 10)
+        );
+    }
+
+    #[test]
+    fn test_divergence_report_tells_lag_from_tampering() {
+        use crate::report::DimensionStatus;
+
+        let mut reference: IntegrityState<3> = IntegrityState::from_master_seed([1u8; 32]);
+        let mut device = reference.clone();
+        for i in 0..10u64 {
+            reference.dimension_mut(0).unwrap().accumulate(&i.to_le_bytes(), b"entropy", 1);
+        }
+
+        let report = device.divergence_report(&reference);
+        assert!(report.is_lagging());
+        assert_eq!(report.get(0).unwrap().status(), DimensionStatus::Lagging);
+        assert_eq!(report.get(0).unwrap().counter_delta(), -10);
+        assert_eq!(report.get(1).unwrap().status(), DimensionStatus::Identical);
+        assert_eq!(reference.divergence_report(&device).get(0).unwrap().status(), DimensionStatus::Ahead);
+
+        device.dimension_mut(1).unwrap().accumulate(b"tampered", b"entropy", 1);
+        reference.dimension_mut(1).unwrap().accumulate(b"honest", b"entropy", 1);
+        let report = device.divergence_report(&reference);
+        assert_eq!(report.get(1).unwrap().status(), DimensionStatus::Conflicting);
+        assert!(report.has_conflict() && !report.is_lagging());
+    }
+
+    #[test]
+    fn test_convergence_restores_trusted_state() {
+        use crate::divergence::CircularDistance;
+
+        let mut trusted: IntegrityState<5> = IntegrityState::from_master_seed([1u8; 32]);
+        trusted.dimension_mut(4).unwrap().set_mode(AccumulationMode::Multiset);
+        let mut device = trusted.clone();
+
+        for i in 0..5 {
+            trusted.dimension_mut(i).unwrap().accumulate(b"honest", b"entropy", 1);
+        }
+        device.dimension_mut(2).unwrap().accumulate(b"tampered", b"entropy", 1);
+
+        let k = device.convergence_constant(&trusted.state_vector());
+        assert_eq!(k.values[2], CircularDistance::compute(
+            &trusted.dimension(2).unwrap().state(),
+            &device.dimension(2).unwrap().state(),
+        ));
+
+        device.apply_convergence(&k);
+        assert_eq!(device.state_vector(), trusted.state_vector());
+        assert_eq!(device.dimension(0).unwrap().counter(), 0);
+        assert_eq!(device.dimension(2).unwrap().counter(), 1);
+        assert_eq!(device.dimension(4).unwrap().mode(), AccumulationMode::Multiset);
+    }
+
+    #[test]
+    fn test_accumulation_mode_roundtrip() {
+        let state: IntegrityState<3> = IntegrityState::from_master_seed([1u8; 32])
+            .with_accumulation_mode(AccumulationMode::Additive);
+
+        let restored = IntegrityState::<3>::from_canonical_bytes(&state.to_canonical_bytes()).unwrap();
+        assert!(restored.dimensions().iter().all(|dim| dim.mode() == AccumulationMode::Additive));
+
+        #[cfg(feature = "serde")]
+        {
+            let restored = IntegrityState::<3>::from_bytes(&state.to_bytes().unwrap()).unwrap();
+            assert!(restored.dimensions().iter().all(|dim| dim.mode() == AccumulationMode::Additive));
+        }
+    }
 }
@@ -1,6 +1,7 @@
 //! Deterministic key derivation functions.
 //!
-//! BLAKE3-based KDF for deriving keys and mixing state.
+//! Suite-parameterised KDF for deriving keys and mixing state. BLAKE3 is the
+//! default suite; see [`crate::suite`] for the alternatives.
 //!
 //! ## Invariants
 //!
@@ -8,33 +9,37 @@
 //! - Context strings provide domain separation
 //! - No randomness or external entropy
 
-use blake3::Hasher;
+use crate::suite::{Blake3Suite, HashSuite};
 
-pub struct Kdf {
-    hasher: Hasher,
+pub struct Kdf<H: HashSuite = Blake3Suite> {
+    hasher: H::Hasher,
 }
 
 impl Kdf {
     pub fn new(context: &[u8]) -> Self {
-        let mut hasher = Hasher::new();
-        hasher.update(b"MA-ISA-KDF-v1");
-        hasher.update(context);
-        Self { hasher }
+        Self::new_with_suite(context)
+    }
+
+    pub fn derive_key(context: &[u8], inputs: &[&[u8]]) -> [u8; 32] {
+        Self::derive_key_with_suite(context, inputs)
+    }
+}
+
+impl<H: HashSuite> Kdf<H> {
+    pub fn new_with_suite(context: &[u8]) -> Self {
+        Self { hasher: H::init(context) }
     }
 
     pub fn update(&mut self, data: &[u8]) {
-        self.hasher.update(data);
+        H::update(&mut self.hasher, data);
     }
 
     pub fn finalize(self) -> [u8; 32] {
-        let hash = self.hasher.finalize();
-        let mut output = [0u8; 32];
-        output.copy_from_slice(hash.as_bytes());
-        output
+        H::finalize(self.hasher)
     }
 
-    pub fn derive_key(context: &[u8], inputs: &[&[u8]]) -> [u8; 32] {
-        let mut kdf = Self::new(context);
+    pub fn derive_key_with_suite(context: &[u8], inputs: &[&[u8]]) -> [u8; 32] {
+        let mut kdf = Self::new_with_suite(context);
         for input in inputs {
             kdf.update(input);
         }
@@ -42,14 +47,35 @@ impl Kdf {
     }
 }
 
-impl Drop for Kdf {
-    fn drop(&mut self) {
-    }
+pub fn mix_state(state: &[u8; 32], event: &[u8], entropy: &[u8], delta_t: u64) -> [u8; 32] {
+    mix_state_with_suite::<Blake3Suite>(state, event, entropy, delta_t)
 }
 
-pub fn mix_state(state: &[u8; 32], event: &[u8], entropy: &[u8], delta_t: u64) -> [u8; 32] {
+pub fn mix_state_with_suite<H: HashSuite>(
+    state: &[u8; 32],
+    event: &[u8],
+    entropy: &[u8],
+    delta_t: u64,
+) -> [u8; 32] {
     let delta_bytes = delta_t.to_le_bytes();
-    Kdf::derive_key(b"axis-accumulate", &[state, event, entropy, &delta_bytes])
+    Kdf::<H>::derive_key_with_suite(b"axis-accumulate", &[state, event, entropy, &delta_bytes])
+}
+
+/// Per-event increment Φ(event, entropy, Δt) for additive accumulation.
+///
+/// Unlike `mix_state`, the previous state is not an input: the caller adds
+/// the result to the state modulo 2^256.
+pub fn phi_with_suite<H: HashSuite>(event: &[u8], entropy: &[u8], delta_t: u64) -> [u8; 32] {
+    let delta_bytes = delta_t.to_le_bytes();
+    Kdf::<H>::derive_key_with_suite(b"axis-phi", &[event, entropy, &delta_bytes])
+}
+
+/// Per-event digest for multiset accumulation.
+///
+/// Depends on the event bytes only, so replicas that see the same events
+/// derive the same digests regardless of local entropy or timing.
+pub fn multiset_digest_with_suite<H: HashSuite>(event: &[u8]) -> [u8; 32] {
+    Kdf::<H>::derive_key_with_suite(b"axis-multiset", &[event])
 }
 
 #[cfg(test)]
@@ -102,4 +128,13 @@ mod tests {
         
         assert!(diff_count > 100, "Avalanche effect insufficient: {} bits changed", diff_count);
     }
+
+    #[test]
+    fn test_mix_state_default_suite_is_blake3() {
+        let state = [7u8; 32];
+        assert_eq!(
+            mix_state(&state, b"event", b"entropy", 5),
+            mix_state_with_suite::<Blake3Suite>(&state, b"event", b"entropy", 5)
+        );
+    }
 }
@@ -35,23 +35,37 @@ extern crate alloc;
 
 pub mod axis;
 pub mod divergence;
+pub mod entropy;
+pub mod fork;
 pub mod kdf;
+pub mod suite;
 pub mod version;
 pub mod dimension;
 pub mod integrity_state;
+pub mod canonical;
+pub mod commitment;
 pub mod compat;
 pub mod dynamic;
+pub mod migration;
+pub mod receipt;
+pub mod report;
 
 #[cfg(kani)]
 pub mod verify;
 
 // Domain-agnostic core API
-pub use axis::AxisAccumulator;
+pub use axis::{AccumulationMode, AxisAccumulator};
 pub use dimension::DimensionAccumulator;
 pub use integrity_state::{IntegrityState, DimensionVector, DivergenceVector, IntegrityStateError, DimensionId};
-pub use divergence::CircularDistance;
+pub use divergence::{CircularDistance, Divergence256};
+pub use entropy::EntropyMode;
+pub use fork::{find_fork, ForkEvidence, ForkFailure};
 pub use version::Version;
 pub use dynamic::DynamicIntegrityState;
+pub use suite::{HashSuite, Blake3Suite, Sha256HkdfSuite, Shake256Suite};
+pub use receipt::{verify_inclusion, verify_receipt_chain, AccumulationReceipt};
+pub use report::{DimensionDivergence, DimensionStatus, DivergenceReport};
+pub use migration::{migrate, migrate_dynamic, MigrationFailure, MigrationRecord};
 
 // Backward-compatible domain-specific API
 pub use compat::{MultiAxisState, StateVector, DivergenceMetric, StateError, MultiAxisStateExt};
@@ -0,0 +1,471 @@
+//! Hash-suite migration with a verifiable continuity record.
+//!
+//! ## Conformance Classification
+//!
+//! **NORMATIVE** for implementations that support more than one hash suite.
+//!
+//! A state produced under one hash suite cannot keep accumulating under another.
+//! Migration derives a fresh state under the target suite `T` from the old state
+//! under suite `F`, keeping every dimension's event counter, and emits a
+//! [`MigrationRecord`] that binds the two together.
+//!
+//! ## Construction
+//!
+//! For dimension `i` with old state `s_i` and counter `c_i`:
+//!
+//! ```text
+//! s'_i = KDF_T("isa.migrate", F.id || T.id || i || s_i || c_i)      c'_i = c_i
+//! ```
+//!
+//! Each dimension keeps its accumulation mode `m_i`.
+//!
+//! The record then holds:
+//!
+//! ```text
+//! old_commitment = KDF_F("isa.migrate.digest", version || n || (s_i || c_i || m_i)...)
+//! new_commitment = KDF_T("isa.migrate.digest", version || n || (s'_i || c'_i || m_i)...)
+//! binding        = KDF_T("isa.migrate.binding", F.id || T.id || n || old_commitment || new_commitment)
+//! seal           = KDF_T("isa.migrate.seal", KDF_T("isa.migrate.seal-key", master_seed) || binding)
+//! ```
+//!
+//! All integers are little-endian (`i` and `n` as u32, counters as u64, modes as u8).
+//!
+//! Anyone holding the old state can check that the new state is its legitimate
+//! continuation ([`MigrationRecord::verify_continuity`]); only holders of the
+//! master seed can check that the device signed off on the migration
+//! ([`MigrationRecord::verify_seal`]).
+
+use crate::dimension::DimensionAccumulator;
+use crate::dynamic::DynamicIntegrityState;
+use crate::integrity_state::{IntegrityState, IntegrityStateError};
+use crate::kdf::Kdf;
+use crate::suite::HashSuite;
+use crate::version::Version;
+use crate::STATE_SIZE;
+
+#[cfg(not(feature = "std"))]
+use alloc::vec::Vec;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// Record binding a pre-migration state to its post-migration successor.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct MigrationRecord {
+    /// Version of the migrated state.
+    pub version: Version,
+    /// Suite id of the old state.
+    pub from_suite: u8,
+    /// Suite id of the new state.
+    pub to_suite: u8,
+    /// Number of migrated dimensions.
+    pub dimension_count: u32,
+    /// Digest of the old state under the old suite.
+    pub old_commitment: [u8; STATE_SIZE],
+    /// Digest of the new state under the new suite.
+    pub new_commitment: [u8; STATE_SIZE],
+    /// Binding of both commitments and suite ids.
+    pub binding: [u8; STATE_SIZE],
+    /// Seal over the binding, keyed from the master seed.
+    pub seal: [u8; STATE_SIZE],
+}
+
+/// Reason a migration record failed verification.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MigrationFailure {
+    /// The record names different suites than the states being checked.
+    SuiteMismatch,
+    /// The record names a different dimension count than the states.
+    DimensionMismatch,
+    /// The old state does not match `old_commitment`.
+    OldStateMismatch,
+    /// The new state is not the derivation of the old state.
+    NewStateMismatch,
+    /// The binding does not cover the recorded commitments.
+    BindingMismatch,
+    /// The seal was not produced with the given master seed.
+    SealMismatch,
+}
+
+impl core::fmt::Display for MigrationFailure {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let reason = match self {
+            MigrationFailure::SuiteMismatch => "hash suites do not match the record",
+            MigrationFailure::DimensionMismatch => "dimension count does not match the record",
+            MigrationFailure::OldStateMismatch => "old state does not match the record",
+            MigrationFailure::NewStateMismatch => "new state is not derived from the old state",
+            MigrationFailure::BindingMismatch => "binding does not match the commitments",
+            MigrationFailure::SealMismatch => "seal does not match the master seed",
+        };
+        f.write_str(reason)
+    }
+}
+
+impl MigrationRecord {
+    /// Length of the encoding produced by [`MigrationRecord::to_bytes`].
+    pub const ENCODED_LEN: usize = 6 + 1 + 1 + 4 + 4 * STATE_SIZE;
+
+    /// Encode the record as a fixed-size little-endian byte string.
+    ///
+    /// Layout: version (6), from suite (1), to suite (1), dimension count (4),
+    /// old commitment, new commitment, binding, seal (32 each).
+    pub fn to_bytes(&self) -> [u8; Self::ENCODED_LEN] {
+        let mut bytes = [0u8; Self::ENCODED_LEN];
+        bytes[0..6].copy_from_slice(&self.version.to_bytes());
+        bytes[6] = self.from_suite;
+        bytes[7] = self.to_suite;
+        bytes[8..12].copy_from_slice(&self.dimension_count.to_le_bytes());
+        for (i, field) in [&self.old_commitment, &self.new_commitment, &self.binding, &self.seal]
+            .into_iter()
+            .enumerate()
+        {
+            let offset = 12 + i * STATE_SIZE;
+            bytes[offset..offset + STATE_SIZE].copy_from_slice(field);
+        }
+        bytes
+    }
+
+    /// Decode a record produced by [`MigrationRecord::to_bytes`].
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, IntegrityStateError> {
+        if bytes.len() != Self::ENCODED_LEN {
+            return Err(IntegrityStateError::Truncated);
+        }
+
+        let field = |i: usize| {
+            let mut out = [0u8; STATE_SIZE];
+            let offset = 12 + i * STATE_SIZE;
+            out.copy_from_slice(&bytes[offset..offset + STATE_SIZE]);
+            out
+        };
+        let mut version = [0u8; 6];
+        version.copy_from_slice(&bytes[0..6]);
+
+        Ok(Self {
+            version: Version::from_bytes(&version),
+            from_suite: bytes[6],
+            to_suite: bytes[7],
+            dimension_count: u32::from_le_bytes([bytes[8], bytes[9], bytes[10], bytes[11]]),
+            old_commitment: field(0),
+            new_commitment: field(1),
+            binding: field(2),
+            seal: field(3),
+        })
+    }
+
+    /// Check that `new` is the legitimate continuation of `old` under this record.
+    pub fn verify_continuity<const N: usize, F: HashSuite, T: HashSuite>(
+        &self,
+        old: &IntegrityState<N, F>,
+        new: &IntegrityState<N, T>,
+    ) -> Result<(), IntegrityStateError> {
+        self.verify_dimensions::<F, T>(old.version(), old.dimensions(), new.dimensions())
+    }
+
+    /// Check continuity between two dynamic states.
+    pub fn verify_continuity_dynamic<F: HashSuite, T: HashSuite>(
+        &self,
+        old: &DynamicIntegrityState<F>,
+        new: &DynamicIntegrityState<T>,
+    ) -> Result<(), IntegrityStateError> {
+        self.verify_dimensions::<F, T>(old.version(), old.dimensions(), new.dimensions())
+    }
+
+    /// Check that the seal was produced by a device holding `master_seed`.
+    ///
+    /// `T` must be the suite the state was migrated to.
+    pub fn verify_seal<T: HashSuite>(
+        &self,
+        master_seed: &[u8; STATE_SIZE],
+    ) -> Result<(), IntegrityStateError> {
+        if self.to_suite != T::ID {
+            return Err(IntegrityStateError::InvalidMigration(MigrationFailure::SuiteMismatch));
+        }
+        if !ct_eq(&seal::<T>(master_seed, &self.binding), &self.seal) {
+            return Err(IntegrityStateError::InvalidMigration(MigrationFailure::SealMismatch));
+        }
+        Ok(())
+    }
+
+    fn verify_dimensions<F: HashSuite, T: HashSuite>(
+        &self,
+        old_version: Version,
+        old: &[DimensionAccumulator<F>],
+        new: &[DimensionAccumulator<T>],
+    ) -> Result<(), IntegrityStateError> {
+        let fail = |failure| Err(IntegrityStateError::InvalidMigration(failure));
+
+        if self.from_suite != F::ID || self.to_suite != T::ID {
+            return fail(MigrationFailure::SuiteMismatch);
+        }
+        if old.len() != new.len() || self.dimension_count as usize != old.len() {
+            return fail(MigrationFailure::DimensionMismatch);
+        }
+        if !ct_eq(&digest(old_version, old), &self.old_commitment) {
+            return fail(MigrationFailure::OldStateMismatch);
+        }
+
+        let derived_matches = old.iter().enumerate().zip(new).all(|((i, old), new)| {
+            let expected = derive_dimension::<F, T>(i, old);
+            ct_eq(&expected.state(), &new.state())
+                && expected.counter() == new.counter()
+                && expected.mode() == new.mode()
+        });
+        if !derived_matches || !ct_eq(&digest(self.version, new), &self.new_commitment) {
+            return fail(MigrationFailure::NewStateMismatch);
+        }
+
+        let expected_binding = binding::<T>(
+            F::ID,
+            self.dimension_count,
+            &self.old_commitment,
+            &self.new_commitment,
+        );
+        if !ct_eq(&expected_binding, &self.binding) {
+            return fail(MigrationFailure::BindingMismatch);
+        }
+        Ok(())
+    }
+}
+
+/// Migrate a fixed-dimension state from suite `F` to suite `T`.
+///
+/// Returns the new state, positioned at the same event counters and in the
+/// same entropy mode, and the record binding it to `old`.
+pub fn migrate<const N: usize, F: HashSuite, T: HashSuite>(
+    old: &IntegrityState<N, F>,
+    master_seed: &[u8; STATE_SIZE],
+) -> (IntegrityState<N, T>, MigrationRecord) {
+    let dimensions = core::array::from_fn(|i| derive_dimension::<F, T>(i, &old.dimensions()[i]));
+    let new = IntegrityState::from_dimensions(dimensions).with_entropy_mode(old.entropy_mode());
+    let record = build_record::<F, T>(
+        old.version(),
+        old.dimensions(),
+        new.version(),
+        new.dimensions(),
+        master_seed,
+    );
+    (new, record)
+}
+
+/// Migrate a dynamic state from suite `F` to suite `T`.
+pub fn migrate_dynamic<F: HashSuite, T: HashSuite>(
+    old: &DynamicIntegrityState<F>,
+    master_seed: &[u8; STATE_SIZE],
+) -> (DynamicIntegrityState<T>, MigrationRecord) {
+    let dimensions: Vec<DimensionAccumulator<T>> = old
+        .dimensions()
+        .iter()
+        .enumerate()
+        .map(|(i, dim)| derive_dimension::<F, T>(i, dim))
+        .collect();
+    let new = DynamicIntegrityState::from_dimensions(dimensions).with_entropy_mode(old.entropy_mode());
+    let record = build_record::<F, T>(
+        old.version(),
+        old.dimensions(),
+        new.version(),
+        new.dimensions(),
+        master_seed,
+    );
+    (new, record)
+}
+
+fn build_record<F: HashSuite, T: HashSuite>(
+    old_version: Version,
+    old: &[DimensionAccumulator<F>],
+    new_version: Version,
+    new: &[DimensionAccumulator<T>],
+    master_seed: &[u8; STATE_SIZE],
+) -> MigrationRecord {
+    let dimension_count = u32::try_from(old.len()).expect("dimension count exceeds u32");
+    let old_commitment = digest(old_version, old);
+    let new_commitment = digest(new_version, new);
+    let binding = binding::<T>(F::ID, dimension_count, &old_commitment, &new_commitment);
+
+    MigrationRecord {
+        version: new_version,
+        from_suite: F::ID,
+        to_suite: T::ID,
+        dimension_count,
+        old_commitment,
+        new_commitment,
+        binding,
+        seal: seal::<T>(master_seed, &binding),
+    }
+}
+
+fn derive_dimension<F: HashSuite, T: HashSuite>(
+    index: usize,
+    old: &DimensionAccumulator<F>,
+) -> DimensionAccumulator<T> {
+    let state = Kdf::<T>::derive_key_with_suite(
+        b"isa.migrate",
+        &[
+            &[F::ID, T::ID],
+            &(index as u32).to_le_bytes(),
+            &old.state(),
+            &old.counter().to_le_bytes(),
+        ],
+    );
+    DimensionAccumulator::from_state_with_suite(state, old.counter()).with_mode(old.mode())
+}
+
+fn digest<H: HashSuite>(version: Version, dimensions: &[DimensionAccumulator<H>]) -> [u8; STATE_SIZE] {
+    let mut kdf = Kdf::<H>::new_with_suite(b"isa.migrate.digest");
+    kdf.update(&version.to_bytes());
+    kdf.update(&(dimensions.len() as u32).to_le_bytes());
+    for dimension in dimensions {
+        kdf.update(&dimension.state());
+        kdf.update(&dimension.counter().to_le_bytes());
+        kdf.update(&[dimension.mode().id()]);
+    }
+    kdf.finalize()
+}
+
+fn binding<T: HashSuite>(
+    from_suite: u8,
+    dimension_count: u32,
+    old_commitment: &[u8; STATE_SIZE],
+    new_commitment: &[u8; STATE_SIZE],
+) -> [u8; STATE_SIZE] {
+    Kdf::<T>::derive_key_with_suite(
+        b"isa.migrate.binding",
+        &[&[from_suite, T::ID], &dimension_count.to_le_bytes(), old_commitment, new_commitment],
+    )
+}
+
+fn seal<T: HashSuite>(master_seed: &[u8; STATE_SIZE], binding: &[u8; STATE_SIZE]) -> [u8; STATE_SIZE] {
+    let key = Kdf::<T>::derive_key_with_suite(b"isa.migrate.seal-key", &[master_seed]);
+    Kdf::<T>::derive_key_with_suite(b"isa.migrate.seal", &[&key, binding])
+}
+
+fn ct_eq(a: &[u8; STATE_SIZE], b: &[u8; STATE_SIZE]) -> bool {
+    use subtle::ConstantTimeEq;
+    a.ct_eq(b).into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::suite::{Blake3Suite, Sha256HkdfSuite, Shake256Suite};
+
+    const MASTER_SEED: [u8; 32] = [7u8; 32];
+
+    fn old_state() -> IntegrityState<3> {
+        let mut state: IntegrityState<3> = IntegrityState::from_master_seed(MASTER_SEED);
+        state.dimension_mut(0).unwrap().accumulate(b"sale", b"entropy", 10);
+        state.dimension_mut(0).unwrap().accumulate(b"sale", b"entropy", 20);
+        state.dimension_mut(2).unwrap().accumulate(b"hw", b"entropy", 5);
+        state
+    }
+
+    #[test]
+    fn test_migration_preserves_counters() {
+        let old = old_state();
+        let (new, record) = migrate::<3, Blake3Suite, Sha256HkdfSuite>(&old, &MASTER_SEED);
+
+        assert_eq!(new.hash_suite(), Sha256HkdfSuite::ID);
+        assert_eq!(record.from_suite, Blake3Suite::ID);
+        assert_eq!(record.to_suite, Sha256HkdfSuite::ID);
+        for i in 0..3 {
+            assert_eq!(new.dimension(i).unwrap().counter(), old.dimension(i).unwrap().counter());
+            assert_ne!(new.dimension(i).unwrap().state(), old.dimension(i).unwrap().state());
+        }
+    }
+
+    #[test]
+    fn test_migration_is_deterministic() {
+        let old = old_state();
+        let (new1, record1) = migrate::<3, Blake3Suite, Shake256Suite>(&old, &MASTER_SEED);
+        let (new2, record2) = migrate::<3, Blake3Suite, Shake256Suite>(&old, &MASTER_SEED);
+
+        assert_eq!(new1.state_vector(), new2.state_vector());
+        assert_eq!(record1, record2);
+    }
+
+    #[test]
+    fn test_verify_continuity_and_seal() {
+        let old = old_state();
+        let (new, record) = migrate::<3, Blake3Suite, Sha256HkdfSuite>(&old, &MASTER_SEED);
+
+        record.verify_continuity(&old, &new).unwrap();
+        record.verify_seal::<Sha256HkdfSuite>(&MASTER_SEED).unwrap();
+
+        assert!(matches!(
+            record.verify_seal::<Sha256HkdfSuite>(&[8u8; 32]),
+            Err(IntegrityStateError::InvalidMigration(MigrationFailure::SealMismatch))
+        ));
+        assert!(matches!(
+            record.verify_seal::<Shake256Suite>(&MASTER_SEED),
+            Err(IntegrityStateError::InvalidMigration(MigrationFailure::SuiteMismatch))
+        ));
+    }
+
+    #[test]
+    fn test_verify_rejects_forged_new_state() {
+        let old = old_state();
+        let (_, record) = migrate::<3, Blake3Suite, Sha256HkdfSuite>(&old, &MASTER_SEED);
+        let forged = IntegrityState::<3, Sha256HkdfSuite>::from_master_seed_with_suite(MASTER_SEED);
+
+        assert!(matches!(
+            record.verify_continuity(&old, &forged),
+            Err(IntegrityStateError::InvalidMigration(MigrationFailure::NewStateMismatch))
+        ));
+    }
+
+    #[test]
+    fn test_verify_rejects_other_old_state() {
+        let old = old_state();
+        let (new, record) = migrate::<3, Blake3Suite, Sha256HkdfSuite>(&old, &MASTER_SEED);
+
+        let mut other = old.clone();
+        other.dimension_mut(1).unwrap().accumulate(b"extra", b"entropy", 1);
+
+        assert!(matches!(
+            record.verify_continuity(&other, &new),
+            Err(IntegrityStateError::InvalidMigration(MigrationFailure::OldStateMismatch))
+        ));
+    }
+
+    #[test]
+    fn test_verify_rejects_tampered_binding() {
+        let old = old_state();
+        let (new, mut record) = migrate::<3, Blake3Suite, Sha256HkdfSuite>(&old, &MASTER_SEED);
+        record.binding[0] ^= 1;
+
+        assert!(matches!(
+            record.verify_continuity(&old, &new),
+            Err(IntegrityStateError::InvalidMigration(MigrationFailure::BindingMismatch))
+        ));
+    }
+
+    #[test]
+    fn test_dynamic_migration() {
+        let mut old = DynamicIntegrityState::new(5, MASTER_SEED);
+        old.dimension_mut(4).unwrap().accumulate(b"event", b"entropy", 1);
+
+        let (new, record) = migrate_dynamic::<Blake3Suite, Shake256Suite>(&old, &MASTER_SEED);
+        assert_eq!(new.dimension_count(), 5);
+        assert_eq!(new.dimension(4).unwrap().counter(), 1);
+        record.verify_continuity_dynamic(&old, &new).unwrap();
+    }
+
+    #[test]
+    fn test_record_roundtrip() {
+        let old = old_state();
+        let (_, record) = migrate::<3, Blake3Suite, Sha256HkdfSuite>(&old, &MASTER_SEED);
+
+        let bytes = record.to_bytes();
+        assert_eq!(MigrationRecord::from_bytes(&bytes).unwrap(), record);
+        assert!(MigrationRecord::from_bytes(&bytes[1..]).is_err());
+    }
+
+    #[test]
+    fn test_migrated_state_keeps_accumulating() {
+        let old = old_state();
+        let (mut new, _) = migrate::<3, Blake3Suite, Sha256HkdfSuite>(&old, &MASTER_SEED);
+
+        new.dimension_mut(0).unwrap().accumulate(b"sale", b"entropy", 30);
+        assert_eq!(new.dimension(0).unwrap().counter(), 3);
+    }
+}
@@ -0,0 +1,323 @@
+//! Per-event accumulation receipts.
+//!
+//! ## Conformance Classification
+//!
+//! **NORMATIVE** - Receipt construction and chain verification SHALL be
+//! implemented exactly as specified here.
+//!
+//! Every call to `AxisAccumulator::accumulate` returns an [`AccumulationReceipt`]
+//! tying the event to the counter value it was accumulated at. Receipts carry
+//! commitments to the states before and after the event, never the states
+//! themselves, so a device can hand them to an auditor without revealing
+//! anything that would let the auditor continue the chain.
+//!
+//! ## Construction
+//!
+//! ```text
+//! commitment(S, c) = KDF("isa.receipt.state", S || c)
+//! digest(event)    = KDF("isa.receipt.event", event)
+//! removal(event)   = KDF("isa.receipt.removal", event)
+//! ```
+//!
+//! with `c` encoded as a little-endian u64 and `KDF` the accumulator's hash
+//! suite. A sequence of receipts is a valid chain from commitment `A` to
+//! commitment `B` when the first receipt starts at `A`, each receipt starts
+//! where the previous one ended with a counter one above the previous
+//! receipt's (one below for a removal), and the last receipt ends at `B`.
+//!
+//! `AxisAccumulator::remove` returns a receipt at the decremented counter
+//! whose digest is `removal(event)`, so removals are part of the chain but
+//! never prove inclusion. Fork evidence (see [`crate::fork`]) is built from
+//! accumulation receipts.
+
+use crate::integrity_state::IntegrityStateError;
+use crate::kdf::Kdf;
+use crate::suite::HashSuite;
+use crate::STATE_SIZE;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// Proof that an event was accumulated at a given counter value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct AccumulationReceipt {
+    /// Counter value after the event was accumulated.
+    pub counter: u64,
+    /// Commitment to the state before the event.
+    pub previous_commitment: [u8; STATE_SIZE],
+    /// Digest of the event bytes.
+    pub event_digest: [u8; STATE_SIZE],
+    /// Commitment to the state after the event.
+    pub new_commitment: [u8; STATE_SIZE],
+}
+
+impl AccumulationReceipt {
+    /// Length of the encoding produced by [`AccumulationReceipt::to_bytes`].
+    pub const ENCODED_LEN: usize = 8 + 3 * STATE_SIZE;
+
+    /// Check that this receipt covers `event`, using hash suite `H`.
+    pub fn covers<H: HashSuite>(&self, event: &[u8]) -> bool {
+        event_digest::<H>(event) == self.event_digest
+    }
+
+    /// Check that this receipt records the removal of `event`, using hash suite `H`.
+    pub fn covers_removal<H: HashSuite>(&self, event: &[u8]) -> bool {
+        removal_digest::<H>(event) == self.event_digest
+    }
+
+    /// Encode the receipt as a fixed-size little-endian byte string.
+    ///
+    /// Layout: counter (8), previous commitment, event digest, new commitment
+    /// (32 each).
+    pub fn to_bytes(&self) -> [u8; Self::ENCODED_LEN] {
+        let mut bytes = [0u8; Self::ENCODED_LEN];
+        bytes[0..8].copy_from_slice(&self.counter.to_le_bytes());
+        for (i, field) in [&self.previous_commitment, &self.event_digest, &self.new_commitment]
+            .into_iter()
+            .enumerate()
+        {
+            let offset = 8 + i * STATE_SIZE;
+            bytes[offset..offset + STATE_SIZE].copy_from_slice(field);
+        }
+        bytes
+    }
+
+    /// Decode a receipt produced by [`AccumulationReceipt::to_bytes`].
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, IntegrityStateError> {
+        if bytes.len() != Self::ENCODED_LEN {
+            return Err(IntegrityStateError::Truncated);
+        }
+
+        let field = |i: usize| {
+            let mut out = [0u8; STATE_SIZE];
+            let offset = 8 + i * STATE_SIZE;
+            out.copy_from_slice(&bytes[offset..offset + STATE_SIZE]);
+            out
+        };
+        let mut counter = [0u8; 8];
+        counter.copy_from_slice(&bytes[0..8]);
+
+        Ok(Self {
+            counter: u64::from_le_bytes(counter),
+            previous_commitment: field(0),
+            event_digest: field(1),
+            new_commitment: field(2),
+        })
+    }
+}
+
+/// Commit to an accumulator state and counter.
+pub fn state_commitment<H: HashSuite>(state: &[u8; STATE_SIZE], counter: u64) -> [u8; STATE_SIZE] {
+    Kdf::<H>::derive_key_with_suite(b"isa.receipt.state", &[state, &counter.to_le_bytes()])
+}
+
+/// Digest of an event as recorded in receipts.
+pub fn event_digest<H: HashSuite>(event: &[u8]) -> [u8; STATE_SIZE] {
+    Kdf::<H>::derive_key_with_suite(b"isa.receipt.event", &[event])
+}
+
+/// Digest of a removed event as recorded in removal receipts.
+pub fn removal_digest<H: HashSuite>(event: &[u8]) -> [u8; STATE_SIZE] {
+    Kdf::<H>::derive_key_with_suite(b"isa.receipt.removal", &[event])
+}
+
+/// Verify that `receipts` link commitment `from` to commitment `to`.
+///
+/// An empty sequence is valid only if `from == to`. On failure, returns
+/// `BrokenReceiptChain` with the index of the first receipt that does not link
+/// (`receipts.len()` if the chain does not end at `to`).
+pub fn verify_receipt_chain(
+    from: &[u8; STATE_SIZE],
+    to: &[u8; STATE_SIZE],
+    receipts: &[AccumulationReceipt],
+) -> Result<(), IntegrityStateError> {
+    let mut commitment = *from;
+    let mut previous_counter: Option<u64> = None;
+
+    for (index, receipt) in receipts.iter().enumerate() {
+        let counter_skips = matches!(
+            previous_counter,
+            Some(counter) if receipt.counter != counter.wrapping_add(1)
+                && receipt.counter != counter.wrapping_sub(1)
+        );
+        if receipt.previous_commitment != commitment || counter_skips {
+            return Err(IntegrityStateError::BrokenReceiptChain { index });
+        }
+        commitment = receipt.new_commitment;
+        previous_counter = Some(receipt.counter);
+    }
+
+    if commitment != *to {
+        return Err(IntegrityStateError::BrokenReceiptChain { index: receipts.len() });
+    }
+    Ok(())
+}
+
+/// Verify a receipt chain and that the receipt at counter `counter` covers `event`.
+///
+/// This is the offline inclusion check: given the commitments published by a
+/// device before and after a period, an auditor confirms that a specific
+/// transaction was accumulated at `counter` within that period.
+pub fn verify_inclusion<H: HashSuite>(
+    from: &[u8; STATE_SIZE],
+    to: &[u8; STATE_SIZE],
+    receipts: &[AccumulationReceipt],
+    counter: u64,
+    event: &[u8],
+) -> Result<(), IntegrityStateError> {
+    verify_receipt_chain(from, to, receipts)?;
+    // Removals revisit counter values, so several receipts may share `counter`
+    if receipts.iter().any(|receipt| receipt.counter == counter && receipt.covers::<H>(event)) {
+        Ok(())
+    } else {
+        Err(IntegrityStateError::EventNotIncluded { counter })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::axis::{AccumulationMode, AxisAccumulator};
+    use crate::suite::Blake3Suite;
+
+    #[cfg(not(feature = "std"))]
+    use alloc::vec::Vec;
+
+    fn chain() -> (AxisAccumulator, [u8; 32], Vec<AccumulationReceipt>) {
+        let mut acc = AxisAccumulator::new([5u8; 32]);
+        let start = acc.commitment();
+        let receipts = [b"tx:1".as_slice(), b"tx:2", b"tx:3"]
+            .iter()
+            .map(|event| acc.accumulate(event, b"entropy", 1))
+            .collect();
+        (acc, start, receipts)
+    }
+
+    #[test]
+    fn test_receipts_link() {
+        let (acc, start, receipts) = chain();
+
+        assert_eq!(receipts[0].previous_commitment, start);
+        assert_eq!(receipts[2].new_commitment, acc.commitment());
+        assert_eq!(receipts.iter().map(|r| r.counter).collect::<Vec<_>>(), [1, 2, 3]);
+        verify_receipt_chain(&start, &acc.commitment(), &receipts).unwrap();
+    }
+
+    #[test]
+    fn test_receipt_commitment_hides_state() {
+        let (acc, _, receipts) = chain();
+        assert_ne!(receipts[2].new_commitment, acc.state());
+        assert_eq!(
+            receipts[2].new_commitment,
+            state_commitment::<Blake3Suite>(&acc.state(), acc.counter())
+        );
+    }
+
+    #[test]
+    fn test_empty_chain() {
+        let (acc, start, _) = chain();
+        verify_receipt_chain(&start, &start, &[]).unwrap();
+        assert!(matches!(
+            verify_receipt_chain(&start, &acc.commitment(), &[]),
+            Err(IntegrityStateError::BrokenReceiptChain { index: 0 })
+        ));
+    }
+
+    #[test]
+    fn test_chain_rejects_gap_and_reorder() {
+        let (acc, start, receipts) = chain();
+        let end = acc.commitment();
+
+        let gapped = [receipts[0], receipts[2]];
+        assert!(matches!(
+            verify_receipt_chain(&start, &end, &gapped),
+            Err(IntegrityStateError::BrokenReceiptChain { index: 1 })
+        ));
+
+        let reordered = [receipts[1], receipts[0], receipts[2]];
+        assert!(matches!(
+            verify_receipt_chain(&start, &end, &reordered),
+            Err(IntegrityStateError::BrokenReceiptChain { index: 0 })
+        ));
+
+        let mut tampered = receipts.clone();
+        tampered[1].counter = 7;
+        assert!(matches!(
+            verify_receipt_chain(&start, &end, &tampered),
+            Err(IntegrityStateError::BrokenReceiptChain { index: 1 })
+        ));
+    }
+
+    #[test]
+    fn test_chain_rejects_wrong_end() {
+        let (_, start, receipts) = chain();
+        assert!(matches!(
+            verify_receipt_chain(&start, &[0u8; 32], &receipts),
+            Err(IntegrityStateError::BrokenReceiptChain { index: 3 })
+        ));
+    }
+
+    #[test]
+    fn test_inclusion() {
+        let (acc, start, receipts) = chain();
+        let end = acc.commitment();
+
+        verify_inclusion::<Blake3Suite>(&start, &end, &receipts, 2, b"tx:2").unwrap();
+        assert!(matches!(
+            verify_inclusion::<Blake3Suite>(&start, &end, &receipts, 2, b"tx:3"),
+            Err(IntegrityStateError::EventNotIncluded { counter: 2 })
+        ));
+        assert!(matches!(
+            verify_inclusion::<Blake3Suite>(&start, &end, &receipts, 9, b"tx:2"),
+            Err(IntegrityStateError::EventNotIncluded { counter: 9 })
+        ));
+    }
+
+    #[test]
+    fn test_receipt_bytes_roundtrip() {
+        let (_, _, receipts) = chain();
+        let bytes = receipts[1].to_bytes();
+        assert_eq!(AccumulationReceipt::from_bytes(&bytes).unwrap(), receipts[1]);
+        assert!(matches!(
+            AccumulationReceipt::from_bytes(&bytes[1..]),
+            Err(IntegrityStateError::Truncated)
+        ));
+    }
+
+    #[test]
+    fn test_receipts_in_every_mode() {
+        for mode in [AccumulationMode::Chained, AccumulationMode::Additive, AccumulationMode::Multiset] {
+            let mut acc = AxisAccumulator::new([5u8; 32]).with_mode(mode);
+            let start = acc.commitment();
+            let receipt = acc.accumulate(b"tx:1", b"entropy", 1);
+            verify_receipt_chain(&start, &acc.commitment(), &[receipt]).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_removal_receipts_chain() {
+        let mut acc = AxisAccumulator::new([5u8; 32]).with_mode(AccumulationMode::Multiset);
+        let start = acc.commitment();
+        let receipts = [
+            acc.accumulate(b"tx:1", b"", 0),
+            acc.accumulate(b"tx:2", b"", 0),
+            acc.remove(b"tx:1").unwrap(),
+            acc.accumulate(b"tx:3", b"", 0),
+        ];
+        let end = acc.commitment();
+
+        assert_eq!(receipts.iter().map(|r| r.counter).collect::<Vec<_>>(), [1, 2, 1, 2]);
+        verify_receipt_chain(&start, &end, &receipts).unwrap();
+        assert!(receipts[2].covers_removal::<Blake3Suite>(b"tx:1"));
+        assert!(!receipts[2].covers::<Blake3Suite>(b"tx:1"));
+
+        // A removal never proves inclusion; the later event at the same counter does
+        verify_inclusion::<Blake3Suite>(&start, &end, &receipts, 1, b"tx:1").unwrap();
+        verify_inclusion::<Blake3Suite>(&start, &end, &receipts, 2, b"tx:3").unwrap();
+        assert!(matches!(
+            verify_inclusion::<Blake3Suite>(&start, &end, &receipts[2..], 1, b"tx:1"),
+            Err(IntegrityStateError::BrokenReceiptChain { index: 0 })
+        ));
+    }
+}
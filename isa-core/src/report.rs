@@ -0,0 +1,179 @@
+//! Event-lag divergence reports.
+//!
+//! ## Conformance Classification
+//!
+//! **NORMATIVE** - Dimension classification SHALL follow the rules below.
+//!
+//! Circular distance alone cannot tell a device that is behind from one that
+//! was tampered with. A report pairs the distance of each dimension with the
+//! counter delta and classifies the dimension, from the point of view of the
+//! local state compared against a reference:
+//!
+//! | Counters           | States    | Status        |
+//! |--------------------|-----------|---------------|
+//! | equal              | equal     | `Identical`   |
+//! | equal              | different | `Conflicting` |
+//! | local < reference  | any       | `Lagging`     |
+//! | local > reference  | any       | `Ahead`       |
+//!
+//! A lagging or ahead dimension is consistent with missing events but does not
+//! prove it; replaying the missing events (or comparing receipts) does.
+
+use crate::divergence::Divergence256;
+use crate::STATE_SIZE;
+
+#[cfg(not(feature = "std"))]
+use alloc::vec::Vec;
+
+#[cfg(feature = "std")]
+use std::vec::Vec;
+
+/// Classification of one dimension against a reference.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DimensionStatus {
+    /// Same counter and same state.
+    Identical,
+    /// Fewer events than the reference.
+    Lagging,
+    /// More events than the reference.
+    Ahead,
+    /// Same counter but a different state.
+    Conflicting,
+}
+
+impl DimensionStatus {
+    /// Name used in reports.
+    pub fn name(self) -> &'static str {
+        match self {
+            DimensionStatus::Identical => "identical",
+            DimensionStatus::Lagging => "lagging",
+            DimensionStatus::Ahead => "ahead",
+            DimensionStatus::Conflicting => "conflicting",
+        }
+    }
+}
+
+/// Divergence of one dimension against a reference.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DimensionDivergence {
+    /// Minimum circular distance between the two states.
+    pub distance: Divergence256,
+    /// Local event counter.
+    pub counter: u64,
+    /// Reference event counter.
+    pub reference_counter: u64,
+}
+
+impl DimensionDivergence {
+    /// Compare a local state and counter against a reference.
+    pub fn new(
+        state: &[u8; STATE_SIZE],
+        counter: u64,
+        reference_state: &[u8; STATE_SIZE],
+        reference_counter: u64,
+    ) -> Self {
+        Self {
+            distance: Divergence256::between(state, reference_state),
+            counter,
+            reference_counter,
+        }
+    }
+
+    /// Local counter minus reference counter: positive when ahead, negative when behind.
+    pub fn counter_delta(&self) -> i128 {
+        self.counter as i128 - self.reference_counter as i128
+    }
+
+    pub fn status(&self) -> DimensionStatus {
+        match self.counter.cmp(&self.reference_counter) {
+            core::cmp::Ordering::Less => DimensionStatus::Lagging,
+            core::cmp::Ordering::Greater => DimensionStatus::Ahead,
+            core::cmp::Ordering::Equal if self.distance.is_zero() => DimensionStatus::Identical,
+            core::cmp::Ordering::Equal => DimensionStatus::Conflicting,
+        }
+    }
+}
+
+/// Per-dimension divergence report, in dimension index order.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DivergenceReport {
+    dimensions: Vec<DimensionDivergence>,
+}
+
+impl DivergenceReport {
+    pub fn new(dimensions: Vec<DimensionDivergence>) -> Self {
+        Self { dimensions }
+    }
+
+    pub fn dimensions(&self) -> &[DimensionDivergence] {
+        &self.dimensions
+    }
+
+    pub fn get(&self, index: usize) -> Option<&DimensionDivergence> {
+        self.dimensions.get(index)
+    }
+
+    /// Every dimension is identical.
+    pub fn is_identical(&self) -> bool {
+        self.statuses().all(|status| status == DimensionStatus::Identical)
+    }
+
+    /// No dimension is ahead or conflicting, and at least one is lagging.
+    ///
+    /// This is the signature of a device that simply missed events.
+    pub fn is_lagging(&self) -> bool {
+        self.statuses()
+            .all(|status| matches!(status, DimensionStatus::Identical | DimensionStatus::Lagging))
+            && self.statuses().any(|status| status == DimensionStatus::Lagging)
+    }
+
+    /// At least one dimension has the reference counter but a different state.
+    pub fn has_conflict(&self) -> bool {
+        self.statuses().any(|status| status == DimensionStatus::Conflicting)
+    }
+
+    fn statuses(&self) -> impl Iterator<Item = DimensionStatus> + '_ {
+        self.dimensions.iter().map(DimensionDivergence::status)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_status_classification() {
+        let a = [1u8; 32];
+        let b = [2u8; 32];
+
+        assert_eq!(DimensionDivergence::new(&a, 5, &a, 5).status(), DimensionStatus::Identical);
+        assert_eq!(DimensionDivergence::new(&a, 5, &b, 5).status(), DimensionStatus::Conflicting);
+        assert_eq!(DimensionDivergence::new(&a, 3, &b, 5).status(), DimensionStatus::Lagging);
+        assert_eq!(DimensionDivergence::new(&a, 7, &b, 5).status(), DimensionStatus::Ahead);
+    }
+
+    #[test]
+    fn test_counter_delta() {
+        let a = [1u8; 32];
+        assert_eq!(DimensionDivergence::new(&a, 3, &a, 13).counter_delta(), -10);
+        assert_eq!(DimensionDivergence::new(&a, u64::MAX, &a, 0).counter_delta(), u64::MAX as i128);
+    }
+
+    #[test]
+    fn test_report_summary() {
+        let a = [1u8; 32];
+        let b = [2u8; 32];
+        let identical = DimensionDivergence::new(&a, 1, &a, 1);
+        let lagging = DimensionDivergence::new(&a, 1, &b, 2);
+        let conflicting = DimensionDivergence::new(&a, 1, &b, 1);
+
+        let report = DivergenceReport::new(vec![identical, identical]);
+        assert!(report.is_identical() && !report.is_lagging() && !report.has_conflict());
+
+        let report = DivergenceReport::new(vec![identical, lagging]);
+        assert!(!report.is_identical() && report.is_lagging() && !report.has_conflict());
+
+        let report = DivergenceReport::new(vec![lagging, conflicting]);
+        assert!(!report.is_lagging() && report.has_conflict());
+    }
+}
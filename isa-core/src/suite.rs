@@ -0,0 +1,212 @@
+//! Pluggable hash suites for state mixing and key derivation.
+//!
+//! ## Conformance Classification
+//!
+//! **NORMATIVE** - This module defines the hash suites a conforming
+//! implementation SHALL support and their wire identifiers.
+//!
+//! A hash suite fixes the primitive behind `Kdf` and therefore behind every
+//! state transition. States produced by different suites are not comparable;
+//! the suite is part of the state type and its id is recorded in serialized
+//! state, so mixing suites is rejected at compile time or at load time.
+//!
+//! ## Suites
+//!
+//! | Id | Suite             | Construction                                            |
+//! |----|-------------------|---------------------------------------------------------|
+//! | 1  | `Blake3Suite`     | BLAKE3(`"MA-ISA-KDF-v1"` ‖ context ‖ inputs)            |
+//! | 2  | `Sha256HkdfSuite` | HKDF-SHA256, salt = context, IKM = inputs, info = `"MA-ISA-KDF-v1"`, L = 32 |
+//! | 3  | `Shake256Suite`   | SHAKE256(`"MA-ISA-KDF-v1"` ‖ context ‖ inputs), 32 bytes |
+//!
+//! Inputs are concatenated without separators in all suites.
+
+use core::fmt::Debug;
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+use sha3::digest::{ExtendableOutput, Update, XofReader};
+use sha3::Shake256;
+
+/// Domain label prefixed to every derivation.
+const KDF_LABEL: &[u8] = b"MA-ISA-KDF-v1";
+
+/// A hash suite usable for state mixing and key derivation.
+///
+/// Suites are zero-sized marker types; the incremental hashing state lives in
+/// the associated `Hasher` type.
+pub trait HashSuite: Copy + Default + Debug + Eq + Send + Sync + 'static {
+    /// Identifier recorded in serialized state.
+    const ID: u8;
+
+    /// Human-readable suite name.
+    const NAME: &'static str;
+
+    /// Incremental hashing state.
+    type Hasher: Clone;
+
+    /// Start a derivation for the given context.
+    fn init(context: &[u8]) -> Self::Hasher;
+
+    /// Absorb input data.
+    fn update(hasher: &mut Self::Hasher, data: &[u8]);
+
+    /// Produce the 32-byte output.
+    fn finalize(hasher: Self::Hasher) -> [u8; 32];
+}
+
+/// BLAKE3 suite (the default).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Blake3Suite;
+
+impl HashSuite for Blake3Suite {
+    const ID: u8 = 1;
+    const NAME: &'static str = "blake3";
+    type Hasher = blake3::Hasher;
+
+    fn init(context: &[u8]) -> Self::Hasher {
+        let mut hasher = blake3::Hasher::new();
+        hasher.update(KDF_LABEL);
+        hasher.update(context);
+        hasher
+    }
+
+    fn update(hasher: &mut Self::Hasher, data: &[u8]) {
+        hasher.update(data);
+    }
+
+    fn finalize(hasher: Self::Hasher) -> [u8; 32] {
+        *hasher.finalize().as_bytes()
+    }
+}
+
+/// HKDF-SHA256 suite (RFC 5869) for deployments that require FIPS-approved primitives.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Sha256HkdfSuite;
+
+impl HashSuite for Sha256HkdfSuite {
+    const ID: u8 = 2;
+    const NAME: &'static str = "sha256-hkdf";
+    type Hasher = Hmac<Sha256>;
+
+    /// HKDF-Extract with the context as salt; inputs form the IKM.
+    fn init(context: &[u8]) -> Self::Hasher {
+        <Hmac<Sha256> as Mac>::new_from_slice(context)
+            .expect("HMAC accepts keys of any length")
+    }
+
+    fn update(hasher: &mut Self::Hasher, data: &[u8]) {
+        Mac::update(hasher, data);
+    }
+
+    /// HKDF-Expand of the extracted key to a single 32-byte block.
+    fn finalize(hasher: Self::Hasher) -> [u8; 32] {
+        let prk = hasher.finalize().into_bytes();
+        let mut expand = <Hmac<Sha256> as Mac>::new_from_slice(&prk)
+            .expect("HMAC accepts keys of any length");
+        Mac::update(&mut expand, KDF_LABEL);
+        Mac::update(&mut expand, &[0x01]);
+        expand.finalize().into_bytes().into()
+    }
+}
+
+/// SHAKE256 suite (FIPS 202).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Shake256Suite;
+
+impl HashSuite for Shake256Suite {
+    const ID: u8 = 3;
+    const NAME: &'static str = "shake256";
+    type Hasher = Shake256;
+
+    fn init(context: &[u8]) -> Self::Hasher {
+        let mut hasher = Shake256::default();
+        Update::update(&mut hasher, KDF_LABEL);
+        Update::update(&mut hasher, context);
+        hasher
+    }
+
+    fn update(hasher: &mut Self::Hasher, data: &[u8]) {
+        Update::update(hasher, data);
+    }
+
+    fn finalize(hasher: Self::Hasher) -> [u8; 32] {
+        let mut output = [0u8; 32];
+        hasher.finalize_xof().read(&mut output);
+        output
+    }
+}
+
+/// Look up the name of a suite by its serialized id.
+pub fn suite_name(id: u8) -> Option<&'static str> {
+    match id {
+        Blake3Suite::ID => Some(Blake3Suite::NAME),
+        Sha256HkdfSuite::ID => Some(Sha256HkdfSuite::NAME),
+        Shake256Suite::ID => Some(Shake256Suite::NAME),
+        _ => None,
+    }
+}
+
+/// Look up the serialized id of a suite by its name.
+pub fn suite_id(name: &str) -> Option<u8> {
+    match name {
+        Blake3Suite::NAME => Some(Blake3Suite::ID),
+        Sha256HkdfSuite::NAME => Some(Sha256HkdfSuite::ID),
+        Shake256Suite::NAME => Some(Shake256Suite::ID),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::kdf::Kdf;
+
+    #[test]
+    fn test_suite_ids_are_distinct() {
+        assert_ne!(Blake3Suite::ID, Sha256HkdfSuite::ID);
+        assert_ne!(Blake3Suite::ID, Shake256Suite::ID);
+        assert_ne!(Sha256HkdfSuite::ID, Shake256Suite::ID);
+        assert_eq!(suite_name(Sha256HkdfSuite::ID), Some("sha256-hkdf"));
+        assert_eq!(suite_name(0), None);
+        assert_eq!(suite_id("shake256"), Some(Shake256Suite::ID));
+        assert_eq!(suite_id("md5"), None);
+    }
+
+    #[test]
+    fn test_suites_produce_different_outputs() {
+        let blake3 = Kdf::<Blake3Suite>::derive_key_with_suite(b"ctx", &[b"input"]);
+        let sha256 = Kdf::<Sha256HkdfSuite>::derive_key_with_suite(b"ctx", &[b"input"]);
+        let shake = Kdf::<Shake256Suite>::derive_key_with_suite(b"ctx", &[b"input"]);
+
+        assert_ne!(blake3, sha256);
+        assert_ne!(blake3, shake);
+        assert_ne!(sha256, shake);
+    }
+
+    #[test]
+    fn test_sha256_hkdf_matches_rfc5869_construction() {
+        // HKDF-Expand(HKDF-Extract(salt = context, IKM = inputs), info = label, L = 32)
+        let prk = {
+            let mut mac = <Hmac<Sha256> as Mac>::new_from_slice(b"ctx").unwrap();
+            Mac::update(&mut mac, b"input");
+            mac.finalize().into_bytes()
+        };
+        let okm: [u8; 32] = {
+            let mut mac = <Hmac<Sha256> as Mac>::new_from_slice(&prk).unwrap();
+            Mac::update(&mut mac, b"MA-ISA-KDF-v1\x01");
+            mac.finalize().into_bytes().into()
+        };
+
+        assert_eq!(Kdf::<Sha256HkdfSuite>::derive_key_with_suite(b"ctx", &[b"in", b"put"]), okm);
+    }
+
+    #[test]
+    fn test_shake256_known_answer() {
+        // SHAKE256("MA-ISA-KDF-v1"), first 32 bytes
+        let mut hasher = Shake256::default();
+        Update::update(&mut hasher, b"MA-ISA-KDF-v1");
+        let mut expected = [0u8; 32];
+        hasher.finalize_xof().read(&mut expected);
+
+        assert_eq!(Kdf::<Shake256Suite>::derive_key_with_suite(b"", &[]), expected);
+    }
+}
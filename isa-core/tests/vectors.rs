@@ -10,7 +10,7 @@
 //! - Expected values are frozen and treated as canonical
 //! - Never regenerate unless the math intentionally changes
 
-use isa_core::{AxisAccumulator, MultiAxisState, CircularDistance};
+use isa_core::{AccumulationMode, AxisAccumulator, DynamicIntegrityState, EntropyMode, IntegrityState, MultiAxisState, CircularDistance, Sha256HkdfSuite, Shake256Suite};
 
 /// Test vector 001: Basic single-axis accumulation
 #[test]
@@ -196,3 +196,97 @@ fn vector_010_large_delta_t() {
     
     assert_eq!(state, expected_array, "Vector 010 failed: large delta_t");
 }
+
+/// Test vector 011: Basic accumulation with the SHA-256 HKDF suite
+#[test]
+fn vector_011_sha256_hkdf_accumulation() {
+    let mut axis = AxisAccumulator::<Sha256HkdfSuite>::new_with_suite([0u8; 32]);
+    axis.accumulate(b"sale:1000", b"device:pos_dakar_01", 1);
+    
+    // HKDF-SHA256(salt = "axis-accumulate", IKM = state || event || entropy || delta_t, info = "MA-ISA-KDF-v1")
+    let expected = hex::decode("40e575556772c22a29c7a3953db688c44067186dc93945b3b15339a8553c3e5c").unwrap();
+    let expected_array: [u8; 32] = expected.try_into().unwrap();
+    
+    assert_eq!(axis.state(), expected_array, "Vector 011 failed: SHA-256 HKDF accumulation");
+}
+
+/// Test vector 012: Basic accumulation with the SHAKE256 suite
+#[test]
+fn vector_012_shake256_accumulation() {
+    let mut axis = AxisAccumulator::<Shake256Suite>::new_with_suite([0u8; 32]);
+    axis.accumulate(b"sale:1000", b"device:pos_dakar_01", 1);
+    
+    // SHAKE256("MA-ISA-KDF-v1" || "axis-accumulate" || state || event || entropy || delta_t)
+    let expected = hex::decode("04abe0e727c7ad4f1cacbfe43439eb2cefd484dbd5479386db3292afbc656f9f").unwrap();
+    let expected_array: [u8; 32] = expected.try_into().unwrap();
+    
+    assert_eq!(axis.state(), expected_array, "Vector 012 failed: SHAKE256 accumulation");
+}
+
+/// Test vector 013: Additive accumulation with the SHA-256 HKDF suite
+#[test]
+fn vector_013_additive_accumulation() {
+    let mut axis = AxisAccumulator::<Sha256HkdfSuite>::new_with_suite([0xFFu8; 32])
+        .with_mode(AccumulationMode::Additive);
+    
+    // S_n = (S_{n-1} + HKDF-SHA256(salt = "axis-phi", IKM = event || entropy || delta_t, info = "MA-ISA-KDF-v1")) mod 2^256
+    axis.accumulate(b"event1", b"entropy1", 100);
+    let expected1 = hex::decode("7a733d500a5d8a1042b18453c608a55eeae7d7abeeaa7f89594fb5294766c552").unwrap();
+    let expected1_array: [u8; 32] = expected1.try_into().unwrap();
+    assert_eq!(axis.state(), expected1_array, "Vector 013 failed: first additive accumulation");
+    
+    axis.accumulate(b"event2", b"entropy2", 200);
+    let expected2 = hex::decode("56f4577003fb944c2ca7651aee367f267ba015df0814d4058b0636c1cd5bc5d7").unwrap();
+    let expected2_array: [u8; 32] = expected2.try_into().unwrap();
+    assert_eq!(axis.state(), expected2_array, "Vector 013 failed: second additive accumulation");
+    
+    assert_eq!(axis.counter(), 2);
+}
+
+/// Test vector 014: Whole-state commitment with the SHA-256 HKDF suite
+#[test]
+fn vector_014_state_commitment() {
+    let mut state = IntegrityState::<2, Sha256HkdfSuite>::new_with_suite([[0x11u8; 32], [0x22u8; 32]]);
+    
+    // HKDF-SHA256(salt = "isa.state.commitment", IKM = version || n || S_0 || c_0 || S_1 || c_1, info = "MA-ISA-KDF-v1")
+    let expected0 = hex::decode("52c4ddc968513bf3fb5e3edec8653b7f4ff9d66f7e0c2315f9ee28d4bf41a887").unwrap();
+    let expected0_array: [u8; 32] = expected0.try_into().unwrap();
+    assert_eq!(state.commitment(), expected0_array, "Vector 014 failed: initial commitment");
+    
+    state.dimension_mut(1).unwrap().set_mode(AccumulationMode::Additive);
+    state.dimension_mut(1).unwrap().accumulate(b"event", b"entropy", 5);
+    let expected1 = hex::decode("a7425184153cfe1ccf9125e500b9a023235777606eabed100cb7b56914bd061c").unwrap();
+    let expected1_array: [u8; 32] = expected1.try_into().unwrap();
+    assert_eq!(state.commitment(), expected1_array, "Vector 014 failed: commitment after accumulation");
+    
+    // The dynamic state shares the encoding
+    let dynamic = DynamicIntegrityState::<Sha256HkdfSuite>::from_canonical_bytes(&state.to_canonical_bytes()).unwrap();
+    assert_eq!(dynamic.commitment(), expected1_array, "Vector 014 failed: dynamic commitment");
+}
+
+/// Test vector 015: Canonical format version 1 payload
+#[test]
+fn vector_015_canonical_format_v1() {
+    // IntegrityState::<2>::new([[0x11; 32], [0x22; 32]]) after accumulating
+    // ("event", "entropy", 5) into dimension 1, encoded before format version 2
+    let v1 = hex::decode(concat!(
+        "4d495341", "0100", "01", "00", "000001000000", "02000000",
+        "1111111111111111111111111111111111111111111111111111111111111111", "0000000000000000",
+        "01998b8c12ee73ceda1f9ce3eb30c56e7c58de55bf54040ed1ead487522677f8", "0100000000000000",
+        "d9b1243f",
+    )).unwrap();
+    
+    let state = IntegrityState::<2>::from_canonical_bytes(&v1).expect("Vector 015 failed: v1 payload rejected");
+    assert_eq!(state.entropy_mode(), EntropyMode::Random, "Vector 015 failed: entropy mode");
+    assert_eq!(state.dimension(0).unwrap().state(), [0x11u8; 32], "Vector 015 failed: dimension 0 state");
+    assert_eq!(state.dimension(0).unwrap().counter(), 0, "Vector 015 failed: dimension 0 counter");
+    assert_eq!(state.dimension(1).unwrap().state().to_vec(), v1[58..90].to_vec(), "Vector 015 failed: dimension 1 state");
+    assert_eq!(state.dimension(1).unwrap().counter(), 1, "Vector 015 failed: dimension 1 counter");
+    assert!(
+        (0..2).all(|i| state.dimension(i).unwrap().mode() == AccumulationMode::Chained),
+        "Vector 015 failed: accumulation mode"
+    );
+    
+    // Re-encoding writes the current format version
+    assert_eq!(&state.to_canonical_bytes()[4..6], &[2, 0], "Vector 015 failed: re-encoded format version");
+}
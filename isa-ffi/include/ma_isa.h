@@ -17,6 +17,11 @@ typedef enum {
     ISA_PERSISTENCE_FAILED = 5,
     ISA_TIME_FAILED = 6,
     ISA_BUFFER_TOO_SMALL = 7,
+    ISA_HASH_SUITE_MISMATCH = 8,
+    ISA_STATE_TAMPERED = 9,
+    ISA_STATE_ROLLED_BACK = 10,
+    ISA_STATE_IN_USE = 11,
+    ISA_DIMENSION_QUARANTINED = 12,
     ISA_UNKNOWN = 255
 } isa_error_t;
 
@@ -11,6 +11,10 @@
 //! - Opaque handles are used for complex types
 //! - No panics across FFI boundaries
 //!
+//! Functions that take raw pointers stay safe `extern "C"` functions, so the
+//! exported signatures do not change; each documents the pointer contract
+//! its callers must meet in a `# Safety` section.
+//!
 //! **ABI STABLE — DO NOT CHANGE WITHOUT MAJOR VERSION**
 
 use crate::error::FfiError;
@@ -35,7 +39,13 @@ pub struct StateVectorC {
 /// Returns an opaque handle (0 on failure).
 ///
 /// **ABI STABLE — DO NOT CHANGE WITHOUT MAJOR VERSION**
+///
+/// # Safety
+///
+/// `master_seed_ptr` must point to 32 readable bytes.
+/// `persistence_path` must be a NUL-terminated string.
 #[no_mangle]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
 pub extern "C" fn isa_runtime_new(
     master_seed_ptr: *const u8,
     persistence_path: *const c_char,
@@ -63,7 +73,12 @@ pub extern "C" fn isa_runtime_new(
     register_runtime(runtime)
 }
 
+/// # Safety
+///
+/// `master_seed_ptr` must point to 32 readable bytes.
+/// `persistence_path` must be a NUL-terminated string.
 #[no_mangle]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
 pub extern "C" fn isa_runtime_load_or_create(
     master_seed_ptr: *const u8,
     persistence_path: *const c_char,
@@ -104,7 +119,13 @@ pub extern "C" fn isa_runtime_free(handle: usize) -> FfiError {
 /// Record a sale event and update state.
 ///
 /// **ABI STABLE — DO NOT CHANGE WITHOUT MAJOR VERSION**
+///
+/// # Safety
+///
+/// `sale_ptr` must point to `sale_len` readable bytes.
+/// `out_vector` must be valid for writes.
 #[no_mangle]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
 pub extern "C" fn isa_record_sale(
     handle: usize,
     sale_ptr: *const u8,
@@ -140,7 +161,12 @@ pub extern "C" fn isa_record_sale(
     }
 }
 
+/// # Safety
+///
+/// `event_ptr` must point to `event_len` readable bytes.
+/// `out_vector` must be valid for writes.
 #[no_mangle]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
 pub extern "C" fn isa_record_event(
     handle: usize,
     axis: u8,
@@ -202,7 +228,11 @@ pub extern "C" fn isa_save(handle: usize) -> FfiError {
     }
 }
 
+/// # Safety
+///
+/// `out_vector` must be valid for writes.
 #[no_mangle]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
 pub extern "C" fn isa_get_state_vector(
     handle: usize,
     out_vector: *mut StateVectorC,
@@ -231,7 +261,11 @@ pub extern "C" fn isa_get_state_vector(
     FfiError::Success
 }
 
+/// # Safety
+///
+/// `seed_ptr` must point to 32 readable bytes.
 #[no_mangle]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
 pub extern "C" fn isa_axis_new(seed_ptr: *const u8) -> *mut u8 {
     if seed_ptr.is_null() {
         return std::ptr::null_mut();
@@ -247,7 +281,11 @@ pub extern "C" fn isa_axis_new(seed_ptr: *const u8) -> *mut u8 {
     Box::into_raw(Box::new(accumulator)) as *mut u8
 }
 
+/// # Safety
+///
+/// `ptr` must be null or a pointer returned by `isa_axis_new` that has not been freed.
 #[no_mangle]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
 pub extern "C" fn isa_axis_free(ptr: *mut u8) {
     if !ptr.is_null() {
         unsafe {
@@ -259,7 +297,13 @@ pub extern "C" fn isa_axis_free(ptr: *mut u8) {
 /// Accumulate an event into a single axis.
 ///
 /// **ABI STABLE — DO NOT CHANGE WITHOUT MAJOR VERSION**
+///
+/// # Safety
+///
+/// `axis_ptr` must come from `isa_axis_new` and not be used concurrently.
+/// `event_ptr` and `entropy_ptr` must point to `event_len` and `entropy_len` readable bytes.
 #[no_mangle]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
 pub extern "C" fn isa_axis_accumulate(
     axis_ptr: *mut u8,
     event_ptr: *const u8,
@@ -280,7 +324,12 @@ pub extern "C" fn isa_axis_accumulate(
     FfiError::Success
 }
 
+/// # Safety
+///
+/// `axis_ptr` must come from `isa_axis_new`.
+/// `out_state` must be valid for 32 bytes of writes.
 #[no_mangle]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
 pub extern "C" fn isa_axis_get_state(
     axis_ptr: *const u8,
     out_state: *mut u8,
@@ -299,7 +348,11 @@ pub extern "C" fn isa_axis_get_state(
     FfiError::Success
 }
 
+/// # Safety
+///
+/// `master_seed_ptr` must point to 32 readable bytes.
 #[no_mangle]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
 pub extern "C" fn isa_state_new(master_seed_ptr: *const u8) -> *mut u8 {
     if master_seed_ptr.is_null() {
         return std::ptr::null_mut();
@@ -315,7 +368,11 @@ pub extern "C" fn isa_state_new(master_seed_ptr: *const u8) -> *mut u8 {
     Box::into_raw(Box::new(state)) as *mut u8
 }
 
+/// # Safety
+///
+/// `ptr` must be null or a pointer returned by `isa_state_new` that has not been freed.
 #[no_mangle]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
 pub extern "C" fn isa_state_free(ptr: *mut u8) {
     if !ptr.is_null() {
         unsafe {
@@ -324,7 +381,11 @@ pub extern "C" fn isa_state_free(ptr: *mut u8) {
     }
 }
 
+/// # Safety
+///
+/// `major`, `minor` and `patch` must be valid for writes.
 #[no_mangle]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
 pub extern "C" fn isa_get_version(
     major: *mut u16,
     minor: *mut u16,
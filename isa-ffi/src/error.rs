@@ -17,6 +17,11 @@ pub enum FfiError {
     PersistenceFailed = 5,
     TimeFailed = 6,
     BufferTooSmall = 7,
+    HashSuiteMismatch = 8,
+    StateTampered = 9,
+    StateRolledBack = 10,
+    StateInUse = 11,
+    DimensionQuarantined = 12,
     Unknown = 255,
 }
 
@@ -31,6 +36,11 @@ impl fmt::Display for FfiError {
             FfiError::PersistenceFailed => write!(f, "Persistence operation failed"),
             FfiError::TimeFailed => write!(f, "Time source failed"),
             FfiError::BufferTooSmall => write!(f, "Buffer too small"),
+            FfiError::HashSuiteMismatch => write!(f, "Hash suite mismatch"),
+            FfiError::StateTampered => write!(f, "Persisted state failed authentication"),
+            FfiError::StateRolledBack => write!(f, "Persisted state was rolled back"),
+            FfiError::StateInUse => write!(f, "State in use by another process"),
+            FfiError::DimensionQuarantined => write!(f, "Dimension is quarantined"),
             FfiError::Unknown => write!(f, "Unknown error"),
         }
     }
@@ -43,6 +53,13 @@ impl From<isa_runtime::RuntimeError> for FfiError {
             isa_runtime::RuntimeError::PersistenceFailed(_) => FfiError::PersistenceFailed,
             isa_runtime::RuntimeError::TimeSourceFailed => FfiError::TimeFailed,
             isa_runtime::RuntimeError::InvalidState => FfiError::InvalidState,
+            isa_runtime::RuntimeError::HashSuiteMismatch { .. } => FfiError::HashSuiteMismatch,
+            isa_runtime::RuntimeError::UnknownHashSuite(_) => FfiError::HashSuiteMismatch,
+            isa_runtime::RuntimeError::InvalidConfig(_) => FfiError::InvalidState,
+            isa_runtime::RuntimeError::StateTampered => FfiError::StateTampered,
+            isa_runtime::RuntimeError::StateRolledBack { .. } => FfiError::StateRolledBack,
+            isa_runtime::RuntimeError::StateInUse(_) => FfiError::StateInUse,
+            isa_runtime::RuntimeError::DimensionQuarantined(_) => FfiError::DimensionQuarantined,
         }
     }
 }
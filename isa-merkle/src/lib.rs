@@ -194,9 +194,8 @@ impl MerkleTree {
         let mut current_index = index;
 
         for level in (0..self.height).rev() {
-            let level_start = (1 << level) - 1;
             let parent_index = current_index / 2;
-            let sibling_index = if current_index % 2 == 0 {
+            let sibling_index = if current_index & 1 == 0 {
                 current_index + 1
             } else {
                 current_index - 1
@@ -249,7 +248,7 @@ impl MerkleProof {
         let mut current_index = self.index;
 
         for sibling in &self.siblings {
-            current_hash = if current_index % 2 == 0 {
+            current_hash = if current_index & 1 == 0 {
                 MerkleTree::hash_pair(&current_hash, sibling)
             } else {
                 MerkleTree::hash_pair(sibling, &current_hash)
@@ -5,7 +5,7 @@
 //! 4. Dimension hierarchies
 //! 5. Adaptive profiles
 
-use isa_core::DynamicIntegrityState;
+use isa_core::{Divergence256, DynamicIntegrityState};
 use isa_runtime::{
     DimensionPolicy, PolicySet, RecoveryStrategy,
     DimensionConstraint, ConstraintSet, ConstraintType,
@@ -70,9 +70,9 @@ fn main() {
     
     // Simulate divergence evaluation
     let divergences = vec![
-        [150u8, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
-        [200u8, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
-        [250u8, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+        Divergence256::from(150u64),
+        Divergence256::from(200u64),
+        Divergence256::from(506u64),
     ];
     
     let violations = policy_set.evaluate(&divergences);
@@ -112,7 +112,7 @@ fn main() {
     
     let constraint_violations = constraint_set.evaluate(&divergences);
     println!("   Constraint violations: {}", constraint_violations.len());
-    for (idx, constraint) in constraint_violations {
+    for (_idx, constraint) in constraint_violations {
         println!("     - {}: severity={}", constraint.name, constraint.severity);
     }
     println!();
@@ -175,7 +175,7 @@ fn main() {
         adaptive_profile.record_observation(DimensionObservation {
             timestamp: 1000 + i * 100,
             dimension_index: 0,
-            divergence: [200u8, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+            divergence: Divergence256::from(200u64),
             event_count: 50,
             recovery_triggered: i % 2 == 0,
         });
@@ -184,7 +184,7 @@ fn main() {
         adaptive_profile.record_observation(DimensionObservation {
             timestamp: 1000 + i * 100,
             dimension_index: 1,
-            divergence: [100u8, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+            divergence: Divergence256::from(100u64),
             event_count: 30,
             recovery_triggered: i % 4 == 0,
         });
@@ -193,7 +193,7 @@ fn main() {
         adaptive_profile.record_observation(DimensionObservation {
             timestamp: 1000 + i * 100,
             dimension_index: 2,
-            divergence: [20u8, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+            divergence: Divergence256::from(20u64),
             event_count: 10,
             recovery_triggered: i == 9,
         });
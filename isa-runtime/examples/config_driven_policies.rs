@@ -3,10 +3,7 @@
 //! This shows how to load dimension policies, constraints, and hierarchies
 //! from configuration files in production environments.
 
-use isa_runtime::{
-    DimensionPolicy, PolicySet, RecoveryStrategy,
-    DimensionConstraint, ConstraintSet, ConstraintType,
-};
+use isa_runtime::{DimensionPolicy, PolicySet, RecoveryStrategy};
 use std::env;
 
 fn main() {
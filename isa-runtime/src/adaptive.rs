@@ -21,7 +21,7 @@
 //! This module provides a framework for dynamically adjusting dimension weights
 //! and policies based on observed patterns and ML models.
 
-use isa_core::STATE_SIZE;
+use isa_core::Divergence256;
 
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
@@ -37,7 +37,7 @@ pub struct DimensionObservation {
     pub dimension_index: usize,
     
     /// Divergence value at this time.
-    pub divergence: [u8; STATE_SIZE],
+    pub divergence: Divergence256,
     
     /// Event count since last observation.
     pub event_count: u64,
@@ -53,14 +53,14 @@ pub struct DimensionStats {
     /// Dimension index.
     pub dimension_index: usize,
     
-    /// Mean divergence value (first 8 bytes as u64).
+    /// Mean divergence value (each observation saturated to u64).
     pub mean_divergence: u64,
     
     /// Standard deviation of divergence.
     pub std_deviation: u64,
     
     /// Maximum observed divergence.
-    pub max_divergence: u64,
+    pub max_divergence: Divergence256,
     
     /// Number of recovery events.
     pub recovery_count: u64,
@@ -79,7 +79,7 @@ impl DimensionStats {
             dimension_index,
             mean_divergence: 0,
             std_deviation: 0,
-            max_divergence: 0,
+            max_divergence: Divergence256::ZERO,
             recovery_count: 0,
             observation_count: 0,
             importance: 0.5, // Start with neutral importance
@@ -88,15 +88,10 @@ impl DimensionStats {
     
     /// Update statistics with a new observation.
     pub fn update(&mut self, observation: &DimensionObservation) {
-        let div_value = u64::from_le_bytes([
-            observation.divergence[0], observation.divergence[1],
-            observation.divergence[2], observation.divergence[3],
-            observation.divergence[4], observation.divergence[5],
-            observation.divergence[6], observation.divergence[7],
-        ]);
+        let div_value = observation.divergence.saturating_to_u64();
         
         // Update max
-        self.max_divergence = self.max_divergence.max(div_value);
+        self.max_divergence = self.max_divergence.max(observation.divergence);
         
         // Update mean (running average)
         let n = self.observation_count as f64;
@@ -162,7 +157,7 @@ impl AdaptiveProfile {
     /// Create a new adaptive profile.
     pub fn new(name: impl Into<String>, dimension_count: usize) -> Self {
         let dimension_stats = (0..dimension_count)
-            .map(|i| DimensionStats::new(i))
+            .map(DimensionStats::new)
             .collect();
         
         Self {
@@ -314,7 +309,7 @@ mod tests {
         let obs = DimensionObservation {
             timestamp: 1000,
             dimension_index: 0,
-            divergence: [100u8, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+            divergence: Divergence256::from(100u64),
             event_count: 10,
             recovery_triggered: true,
         };
@@ -337,7 +332,7 @@ mod tests {
             profile.record_observation(DimensionObservation {
                 timestamp: 1000 + i * 100,
                 dimension_index: 0,
-                divergence: [200u8, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+                divergence: Divergence256::from(200u64),
                 event_count: 10,
                 recovery_triggered: true,
             });
@@ -348,7 +343,7 @@ mod tests {
             profile.record_observation(DimensionObservation {
                 timestamp: 1000 + i * 100,
                 dimension_index: 1,
-                divergence: [10u8, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+                divergence: Divergence256::from(10u64),
                 event_count: 10,
                 recovery_triggered: false,
             });
@@ -0,0 +1,367 @@
+//! Hash-chained log of recovery audits.
+//!
+//! ## Conformance Classification
+//!
+//! **INFORMATIVE** - The log format is provided for compliance and forensic
+//! review of recovery operations. Conforming implementations MAY keep audit
+//! trails differently.
+//!
+//! Every convergence applied by `DeviceRuntime` produces a [`RecoveryAudit`].
+//! An [`AuditLog`] stores each audit as an [`AuditEntry`] carrying a sequence
+//! number, the commitment to the healed state (see `isa_core::commitment`) and
+//! the hash of the previous entry. Removing, reordering or editing any entry
+//! breaks the chain, which [`verify_chain`] detects.
+//!
+//! ## Entry Format
+//!
+//! Entries are stored back to back, all integers little-endian:
+//!
+//! | Field                | Size          |
+//! |----------------------|---------------|
+//! | sequence             | 8             |
+//! | timestamp            | 8             |
+//! | pre-healing state    | 96            |
+//! | convergence constant | 96            |
+//! | post-healing state   | 96            |
+//! | state commitment     | 32            |
+//! | previous hash        | 32            |
+//! | reason length        | 4             |
+//! | reason (UTF-8)       | reason length |
+//! | entry hash           | 32            |
+//!
+//! State vectors are the finance, time and hardware states in that order. The
+//! entry hash is `KDF("isa.recovery.audit", body)` over every preceding field
+//! of the entry, using the default hash suite. The first entry has sequence 0
+//! and an all-zero previous hash.
+//!
+//! The chain proves the order and integrity of the entries, not their origin:
+//! anyone able to write the file can rebuild a consistent chain. Publish or
+//! sign the latest entry hash to anchor it.
+
+use crate::device::RecoveryAudit;
+use crate::{Result, RuntimeError};
+use isa_core::divergence::modular_add;
+use isa_core::kdf::Kdf;
+use isa_core::StateVector;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+/// Domain separation context for audit entry hashes.
+pub const AUDIT_HASH_CONTEXT: &[u8] = b"isa.recovery.audit";
+
+/// One recovery audit, linked into the chain.
+#[derive(Debug, Clone)]
+pub struct AuditEntry {
+    /// Position of the entry in the log, starting at 0.
+    pub sequence: u64,
+    pub audit: RecoveryAudit,
+    /// Commitment to the full state after healing.
+    pub state_commitment: [u8; 32],
+    /// Hash of the previous entry, or zero for the first entry.
+    pub previous_hash: [u8; 32],
+    /// Hash of this entry.
+    pub hash: [u8; 32],
+}
+
+impl AuditEntry {
+    /// Build the entry that follows `previous` (or starts the chain).
+    pub fn new(previous: Option<&AuditEntry>, audit: RecoveryAudit, state_commitment: [u8; 32]) -> Self {
+        let mut entry = Self {
+            sequence: previous.map_or(0, |entry| entry.sequence.wrapping_add(1)),
+            audit,
+            state_commitment,
+            previous_hash: previous.map_or([0u8; 32], |entry| entry.hash),
+            hash: [0u8; 32],
+        };
+        entry.hash = entry.compute_hash();
+        entry
+    }
+
+    /// Hash of the entry's fields, excluding `hash` itself.
+    pub fn compute_hash(&self) -> [u8; 32] {
+        Kdf::derive_key(AUDIT_HASH_CONTEXT, &[&self.body()])
+    }
+
+    fn body(&self) -> Vec<u8> {
+        let reason = self.audit.reason.as_bytes();
+        let mut bytes = Vec::with_capacity(384 + reason.len());
+        bytes.extend_from_slice(&self.sequence.to_le_bytes());
+        bytes.extend_from_slice(&self.audit.timestamp.to_le_bytes());
+        for vector in [
+            &self.audit.pre_healing_state,
+            &self.audit.convergence_constant,
+            &self.audit.post_healing_state,
+        ] {
+            bytes.extend_from_slice(&vector.finance);
+            bytes.extend_from_slice(&vector.time);
+            bytes.extend_from_slice(&vector.hardware);
+        }
+        bytes.extend_from_slice(&self.state_commitment);
+        bytes.extend_from_slice(&self.previous_hash);
+        bytes.extend_from_slice(&(reason.len() as u32).to_le_bytes());
+        bytes.extend_from_slice(reason);
+        bytes
+    }
+
+    /// Encode this entry in the log format.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = self.body();
+        bytes.extend_from_slice(&self.hash);
+        bytes
+    }
+
+    /// Decode one entry from the front of `bytes`.
+    ///
+    /// Returns the entry and the number of bytes consumed. The hash is read,
+    /// not checked; use [`verify_chain`].
+    pub fn from_bytes(bytes: &[u8]) -> Result<(Self, usize)> {
+        let mut reader = Reader { bytes, offset: 0 };
+
+        let sequence = u64::from_le_bytes(reader.array()?);
+        let timestamp = u64::from_le_bytes(reader.array()?);
+        let pre_healing_state = reader.state_vector()?;
+        let convergence_constant = reader.state_vector()?;
+        let post_healing_state = reader.state_vector()?;
+        let state_commitment = reader.array()?;
+        let previous_hash = reader.array()?;
+        let reason_len = u32::from_le_bytes(reader.array()?) as usize;
+        let reason = String::from_utf8(reader.take(reason_len)?.to_vec())
+            .map_err(|_| RuntimeError::InvalidState)?;
+        let hash = reader.array()?;
+
+        let audit = RecoveryAudit {
+            timestamp,
+            pre_healing_state,
+            convergence_constant,
+            post_healing_state,
+            reason,
+        };
+        Ok((Self { sequence, audit, state_commitment, previous_hash, hash }, reader.offset))
+    }
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+    offset: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8]> {
+        let end = self.offset.checked_add(len).ok_or(RuntimeError::InvalidState)?;
+        let slice = self.bytes.get(self.offset..end).ok_or(RuntimeError::InvalidState)?;
+        self.offset = end;
+        Ok(slice)
+    }
+
+    fn array<const L: usize>(&mut self) -> Result<[u8; L]> {
+        let mut array = [0u8; L];
+        array.copy_from_slice(self.take(L)?);
+        Ok(array)
+    }
+
+    fn state_vector(&mut self) -> Result<StateVector> {
+        Ok(StateVector {
+            finance: self.array()?,
+            time: self.array()?,
+            hardware: self.array()?,
+        })
+    }
+}
+
+/// Why an audit chain failed verification.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChainBreakKind {
+    /// The entry's sequence number does not follow the previous entry.
+    SequenceMismatch { expected: u64 },
+    /// The entry does not link to the previous entry's hash.
+    PreviousHashMismatch,
+    /// The stored hash does not match the entry's contents.
+    HashMismatch,
+    /// The post-healing state is not the pre-healing state plus the convergence constant.
+    ConvergenceMismatch,
+}
+
+/// First entry at which an audit chain is broken.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ChainBreak {
+    pub index: usize,
+    pub kind: ChainBreakKind,
+}
+
+/// Check the links, hashes and convergence arithmetic of `entries`, oldest first.
+///
+/// Returns the first break, or `None` if the chain is intact.
+pub fn verify_chain(entries: &[AuditEntry]) -> Option<ChainBreak> {
+    let mut previous: Option<&AuditEntry> = None;
+    for (index, entry) in entries.iter().enumerate() {
+        let broken = |kind| Some(ChainBreak { index, kind });
+        let expected = previous.map_or(0, |p| p.sequence.wrapping_add(1));
+        if entry.sequence != expected {
+            return broken(ChainBreakKind::SequenceMismatch { expected });
+        }
+        if entry.previous_hash != previous.map_or([0u8; 32], |p| p.hash) {
+            return broken(ChainBreakKind::PreviousHashMismatch);
+        }
+        if entry.hash != entry.compute_hash() {
+            return broken(ChainBreakKind::HashMismatch);
+        }
+
+        let audit = &entry.audit;
+        let (pre, k, post) = (&audit.pre_healing_state, &audit.convergence_constant, &audit.post_healing_state);
+        if modular_add(&pre.finance, &k.finance) != post.finance
+            || modular_add(&pre.time, &k.time) != post.time
+            || modular_add(&pre.hardware, &k.hardware) != post.hardware
+        {
+            return broken(ChainBreakKind::ConvergenceMismatch);
+        }
+        previous = Some(entry);
+    }
+    None
+}
+
+/// Append-only file of [`AuditEntry`]s.
+pub struct AuditLog {
+    path: PathBuf,
+}
+
+impl AuditLog {
+    pub fn new<P: AsRef<Path>>(path: P) -> Self {
+        Self {
+            path: path.as_ref().to_path_buf(),
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Chain `audit` to the last entry, append it and flush it to disk.
+    pub fn append(&self, audit: RecoveryAudit, state_commitment: [u8; 32]) -> Result<AuditEntry> {
+        let entries = self.entries()?;
+        let entry = AuditEntry::new(entries.last(), audit, state_commitment);
+
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent)
+                .map_err(|e| RuntimeError::PersistenceFailed(e.to_string()))?;
+        }
+        let mut file = fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .map_err(|e| RuntimeError::PersistenceFailed(e.to_string()))?;
+        file.write_all(&entry.to_bytes())
+            .and_then(|_| file.sync_all())
+            .map_err(|e| RuntimeError::PersistenceFailed(e.to_string()))?;
+        Ok(entry)
+    }
+
+    /// Read all entries, oldest first.
+    ///
+    /// A missing log is empty. A truncated or malformed log fails with `InvalidState`.
+    pub fn entries(&self) -> Result<Vec<AuditEntry>> {
+        let bytes = match fs::read(&self.path) {
+            Ok(bytes) => bytes,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(RuntimeError::PersistenceFailed(e.to_string())),
+        };
+
+        let mut entries = Vec::new();
+        let mut offset = 0;
+        while offset < bytes.len() {
+            let (entry, consumed) = AuditEntry::from_bytes(&bytes[offset..])?;
+            entries.push(entry);
+            offset += consumed;
+        }
+        Ok(entries)
+    }
+
+    /// Read the log and check its chain (see [`verify_chain`]).
+    pub fn verify(&self) -> Result<Option<ChainBreak>> {
+        Ok(verify_chain(&self.entries()?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn audit(seed: u8, reason: &str) -> RecoveryAudit {
+        let pre = StateVector { finance: [seed; 32], time: [1; 32], hardware: [2; 32] };
+        let k = StateVector { finance: [3; 32], time: [0; 32], hardware: [1; 32] };
+        let post = StateVector {
+            finance: modular_add(&pre.finance, &k.finance),
+            time: modular_add(&pre.time, &k.time),
+            hardware: modular_add(&pre.hardware, &k.hardware),
+        };
+        RecoveryAudit {
+            timestamp: seed as u64,
+            pre_healing_state: pre,
+            convergence_constant: k,
+            post_healing_state: post,
+            reason: reason.to_string(),
+        }
+    }
+
+    fn chain() -> Vec<AuditEntry> {
+        let first = AuditEntry::new(None, audit(1, "first"), [7; 32]);
+        let second = AuditEntry::new(Some(&first), audit(2, "second"), [8; 32]);
+        let third = AuditEntry::new(Some(&second), audit(3, "third"), [9; 32]);
+        vec![first, second, third]
+    }
+
+    #[test]
+    fn test_log_roundtrip() {
+        let temp_dir = TempDir::new().unwrap();
+        let log = AuditLog::new(temp_dir.path().join("audit.log"));
+        assert!(log.entries().unwrap().is_empty());
+
+        log.append(audit(1, "first"), [7; 32]).unwrap();
+        let second = log.append(audit(2, "sécond"), [8; 32]).unwrap();
+        let entries = log.entries().unwrap();
+
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[1].sequence, 1);
+        assert_eq!(entries[1].previous_hash, entries[0].hash);
+        assert_eq!(entries[1].hash, second.hash);
+        assert_eq!(entries[1].audit.reason, "sécond");
+        assert_eq!(log.verify().unwrap(), None);
+    }
+
+    #[test]
+    fn test_chain_detects_edits() {
+        let entries = chain();
+        assert_eq!(verify_chain(&entries), None);
+        let broken_at = |entries: &[AuditEntry]| verify_chain(entries).map(|b| (b.index, b.kind));
+
+        let mut removed = entries.clone();
+        removed.remove(1);
+        assert_eq!(broken_at(&removed), Some((1, ChainBreakKind::SequenceMismatch { expected: 1 })));
+
+        let mut edited = entries.clone();
+        edited[1].audit.reason = "edited".to_string();
+        assert_eq!(broken_at(&edited), Some((1, ChainBreakKind::HashMismatch)));
+
+        // Rehashing an edited entry breaks the link to the next one
+        edited[1].hash = edited[1].compute_hash();
+        assert_eq!(broken_at(&edited), Some((2, ChainBreakKind::PreviousHashMismatch)));
+
+        let mut forged = entries;
+        forged[0].audit.post_healing_state.finance[0] ^= 1;
+        forged[0].hash = forged[0].compute_hash();
+        assert_eq!(broken_at(&forged[..1]), Some((0, ChainBreakKind::ConvergenceMismatch)));
+    }
+
+    #[test]
+    fn test_truncated_log_rejected() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("audit.log");
+        let log = AuditLog::new(&path);
+        log.append(audit(1, "first"), [7; 32]).unwrap();
+
+        let bytes = fs::read(&path).unwrap();
+        fs::write(&path, &bytes[..bytes.len() - 1]).unwrap();
+        assert!(matches!(log.entries(), Err(RuntimeError::InvalidState)));
+    }
+}
@@ -6,9 +6,14 @@
 //! - Command-line arguments
 //! - Remote configuration services
 
-use crate::policy::{DimensionPolicy, PolicySet, RecoveryStrategy};
+use crate::policy::{DimensionPolicy, EscalationLadder, EscalationStep, PolicySet, RecoveryStrategy};
 use crate::constraints::{DimensionConstraint, ConstraintSet, ConstraintType};
 use crate::adaptive::AdaptiveProfile;
+use crate::expression::Expression;
+use crate::history::CorrelationMethod;
+use crate::registry::CustomRegistry;
+use crate::{Result, RuntimeError};
+use isa_core::{AccumulationMode, Blake3Suite, DynamicIntegrityState, HashSuite, IntegrityState};
 
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
@@ -47,10 +52,28 @@ pub struct GlobalConfig {
     /// Master seed for dimension initialization (hex string)
     #[cfg_attr(feature = "serde", serde(default))]
     pub master_seed: Option<String>,
+    
+    /// Hash suite for new and loaded states ("blake3", "sha256-hkdf", "shake256")
+    #[cfg_attr(feature = "serde", serde(default = "default_hash_suite"))]
+    pub hash_suite: String,
+    
+    /// Migrate states persisted under another hash suite when loading them
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub migrate_on_load: bool,
 }
 
 fn default_learning_rate() -> f32 { 0.1 }
 fn default_min_observations() -> u64 { 10 }
+fn default_hash_suite() -> String { Blake3Suite::NAME.to_string() }
+
+impl GlobalConfig {
+    /// Resolve the configured hash suite to its serialized id.
+    ///
+    /// Returns None if the name does not match a known suite.
+    pub fn hash_suite_id(&self) -> Option<u8> {
+        isa_core::suite::suite_id(&self.hash_suite)
+    }
+}
 
 /// Configuration for a single dimension
 #[derive(Debug, Clone)]
@@ -79,10 +102,46 @@ pub struct DimensionConfig {
     /// Whether this dimension is enabled
     #[cfg_attr(feature = "serde", serde(default = "default_enabled"))]
     pub enabled: bool,
+    
+    /// Accumulation mode ("chained", "additive" or "multiset")
+    #[cfg_attr(feature = "serde", serde(default = "default_accumulation_mode"))]
+    pub accumulation_mode: String,
+    
+    /// Escalation of repeated violations, replacing `strategy` once its
+    /// first step is reached
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub escalation: Option<EscalationConfig>,
+}
+
+/// Escalation ladder of a dimension
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct EscalationConfig {
+    /// Seconds over which violations are counted
+    pub window_secs: u64,
+    
+    /// Seconds without a violation after which the ladder drops back
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub cooldown_secs: u64,
+    
+    /// Steps in increasing order of violation count
+    pub steps: Vec<EscalationStepConfig>,
+}
+
+/// One step of an escalation ladder
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct EscalationStepConfig {
+    /// Violations within the window that reach this step
+    pub after: u32,
+    
+    /// Recovery strategy from this step on
+    pub strategy: String,
 }
 
 fn default_weight() -> f32 { 1.0 }
 fn default_enabled() -> bool { true }
+fn default_accumulation_mode() -> String { AccumulationMode::Chained.name().to_string() }
 
 /// Configuration for a constraint
 #[derive(Debug, Clone)]
@@ -91,13 +150,19 @@ pub struct ConstraintConfig {
     /// Constraint name
     pub name: String,
     
-    /// Dimensions involved
+    /// Dimensions involved (taken from the expression for Expression constraints)
+    #[cfg_attr(feature = "serde", serde(default))]
     pub dimensions: Vec<usize>,
     
     /// Constraint type
     #[cfg_attr(feature = "serde", serde(rename = "type"))]
     pub constraint_type: String,
     
+    /// Rule in the constraint expression language (for Expression
+    /// constraints); dimensions may be named by their configured names
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub expression: Option<String>,
+    
     /// Ratio (for MaxRatio constraints)
     #[cfg_attr(feature = "serde", serde(default))]
     pub ratio: Option<u32>,
@@ -106,6 +171,24 @@ pub struct ConstraintConfig {
     #[cfg_attr(feature = "serde", serde(default))]
     pub threshold: Option<u64>,
     
+    /// Threshold of the first dimension (for ConditionalCheck constraints;
+    /// `threshold` if unset)
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub trigger_threshold: Option<u64>,
+    
+    /// Threshold of the second dimension once the first exceeds its own
+    /// (for ConditionalCheck constraints; `threshold` if unset)
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub dependent_threshold: Option<u64>,
+    
+    /// Minimum correlation in percent, -100 to 100 (for Correlation constraints)
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub min_correlation: Option<i32>,
+    
+    /// "pearson" (default) or "spearman" (for Correlation constraints)
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub method: Option<String>,
+    
     /// Severity level (0-10)
     #[cfg_attr(feature = "serde", serde(default = "default_severity"))]
     pub severity: u8,
@@ -142,17 +225,33 @@ pub struct HierarchyNodeConfig {
 
 impl IsaConfig {
     /// Convert this configuration into a PolicySet
-    pub fn to_policy_set(&self) -> PolicySet {
+    ///
+    /// Every `custom:<id>` strategy must have a recovery handler in
+    /// `registry`. Fails with `InvalidConfig` on an unregistered or malformed
+    /// custom id, or on an invalid escalation ladder.
+    pub fn to_policy_set(&self, registry: &CustomRegistry) -> Result<PolicySet> {
         let mut policies = PolicySet::new();
         
         for dim_config in &self.dimensions {
-            let strategy = parse_recovery_strategy(&dim_config.strategy);
+            let strategy = registered_recovery_strategy(&dim_config.strategy, registry, dim_config.index)?;
             
             let mut policy = DimensionPolicy::new(&dim_config.name)
                 .with_threshold(dim_config.threshold)
                 .with_recovery(strategy)
                 .with_weight(dim_config.weight);
             
+            if let Some(escalation) = &dim_config.escalation {
+                let steps = escalation.steps.iter().map(|step| {
+                    Ok(EscalationStep {
+                        after: step.after,
+                        strategy: registered_recovery_strategy(&step.strategy, registry, dim_config.index)?,
+                    })
+                }).collect::<Result<_>>()?;
+                let ladder = EscalationLadder::new(steps, escalation.window_secs, escalation.cooldown_secs)
+                    .map_err(in_context(format!("dimension {}", dim_config.index)))?;
+                policy = policy.with_escalation(ladder);
+            }
+            
             if dim_config.critical {
                 policy = policy.critical();
             }
@@ -164,30 +263,113 @@ impl IsaConfig {
             policies.add_policy(policy);
         }
         
-        policies
+        Ok(policies)
+    }
+    
+    /// Resolve the accumulation mode of every dimension of a state with
+    /// `dimension_count` dimensions.
+    ///
+    /// Dimensions without a configuration entry use the chained mode. Fails
+    /// with `InvalidConfig` on an unknown mode name or an out-of-range index.
+    pub fn accumulation_modes(&self, dimension_count: usize) -> Result<Vec<AccumulationMode>> {
+        let mut modes = vec![AccumulationMode::Chained; dimension_count];
+        
+        for dim_config in &self.dimensions {
+            let mode = AccumulationMode::from_name(&dim_config.accumulation_mode).ok_or_else(|| {
+                RuntimeError::InvalidConfig(format!(
+                    "unknown accumulation mode '{}' for dimension {}",
+                    dim_config.accumulation_mode, dim_config.index
+                ))
+            })?;
+            let slot = modes.get_mut(dim_config.index).ok_or_else(|| {
+                RuntimeError::InvalidConfig(format!(
+                    "dimension {} is out of range for {} dimensions",
+                    dim_config.index, dimension_count
+                ))
+            })?;
+            *slot = mode;
+        }
+        
+        Ok(modes)
+    }
+    
+    /// Apply the configured accumulation modes to a newly created state.
+    pub fn apply_accumulation_modes<const N: usize, H: HashSuite>(
+        &self,
+        state: &mut IntegrityState<N, H>,
+    ) -> Result<()> {
+        for (index, mode) in self.accumulation_modes(N)?.into_iter().enumerate() {
+            if let Some(dimension) = state.dimension_mut(index) {
+                dimension.set_mode(mode);
+            }
+        }
+        Ok(())
+    }
+    
+    /// Apply the configured accumulation modes to a newly created dynamic state.
+    pub fn apply_accumulation_modes_dynamic<H: HashSuite>(
+        &self,
+        state: &mut DynamicIntegrityState<H>,
+    ) -> Result<()> {
+        for (index, mode) in self.accumulation_modes(state.dimension_count())?.into_iter().enumerate() {
+            if let Some(dimension) = state.dimension_mut(index) {
+                dimension.set_mode(mode);
+            }
+        }
+        Ok(())
     }
     
     /// Convert this configuration into a ConstraintSet
-    pub fn to_constraint_set(&self) -> ConstraintSet {
+    ///
+    /// Expressions are parsed and type-checked here; a missing or invalid
+    /// expression fails with `InvalidConfig`, as does a `custom:<id>` type
+    /// without a constraint evaluator in `registry`.
+    pub fn to_constraint_set(&self, registry: &CustomRegistry) -> Result<ConstraintSet> {
         let mut constraints = ConstraintSet::new();
         
         for constraint_config in &self.constraints {
-            let constraint_type = parse_constraint_type(
-                &constraint_config.constraint_type,
-                constraint_config.ratio,
-                constraint_config.threshold,
-            );
-            
-            let constraint = DimensionConstraint::new(
-                &constraint_config.name,
-                constraint_config.dimensions.clone(),
-                constraint_type,
-            ).with_severity(constraint_config.severity);
+            let constraint = if constraint_config.constraint_type.eq_ignore_ascii_case("expression") {
+                DimensionConstraint::from_expression(
+                    &constraint_config.name,
+                    self.parse_expression(constraint_config)?,
+                )
+            } else {
+                if let Some(id) = custom_id(&constraint_config.constraint_type)? {
+                    if registry.constraint_evaluator(id).is_none() {
+                        return Err(RuntimeError::InvalidConfig(format!(
+                            "no constraint evaluator registered for custom:{} (constraint '{}')",
+                            id, constraint_config.name
+                        )));
+                    }
+                }
+                DimensionConstraint::new(
+                    &constraint_config.name,
+                    constraint_config.dimensions.clone(),
+                    parse_constraint_type(constraint_config)?,
+                )
+            }.with_severity(constraint_config.severity);
             
             constraints.add_constraint(constraint);
         }
         
-        constraints
+        Ok(constraints)
+    }
+    
+    /// Parse the expression of a constraint, resolving dimension names.
+    fn parse_expression(&self, constraint_config: &ConstraintConfig) -> Result<Expression> {
+        let source = constraint_config.expression.as_deref().ok_or_else(|| {
+            RuntimeError::InvalidConfig(format!(
+                "constraint '{}' has no expression",
+                constraint_config.name
+            ))
+        })?;
+        let labels: Vec<(&str, usize)> = self
+            .dimensions
+            .iter()
+            .map(|dim_config| (dim_config.name.as_str(), dim_config.index))
+            .collect();
+        Expression::parse_with_labels(source, &labels)
+            .map_err(in_context(format!("constraint '{}'", constraint_config.name)))
     }
     
     /// Create an AdaptiveProfile from this configuration
@@ -221,32 +403,82 @@ fn parse_recovery_strategy(s: &str) -> RecoveryStrategy {
     }
 }
 
-/// Parse constraint type from string
-fn parse_constraint_type(
-    type_str: &str,
-    ratio: Option<u32>,
-    threshold: Option<u64>,
-) -> ConstraintType {
-    match type_str.to_lowercase().as_str() {
+/// Parse the recovery strategy of dimension `index`, checking that a
+/// `custom:<id>` strategy has a handler in `registry`.
+fn registered_recovery_strategy(s: &str, registry: &CustomRegistry, index: usize) -> Result<RecoveryStrategy> {
+    match custom_id(s)? {
+        Some(id) if registry.recovery_handler(id).is_none() => Err(RuntimeError::InvalidConfig(format!(
+            "no recovery handler registered for custom:{} (dimension {})",
+            id, index
+        ))),
+        Some(id) => Ok(RecoveryStrategy::Custom(id)),
+        None => Ok(parse_recovery_strategy(s)),
+    }
+}
+
+/// Prefix the message of an `InvalidConfig` error with `context`.
+fn in_context(context: String) -> impl FnOnce(RuntimeError) -> RuntimeError {
+    move |err| match err {
+        RuntimeError::InvalidConfig(message) => {
+            RuntimeError::InvalidConfig(format!("{}: {}", context, message))
+        }
+        other => other,
+    }
+}
+
+/// The id of a `custom:<id>` strategy or constraint type, `None` for other names.
+fn custom_id(s: &str) -> Result<Option<u32>> {
+    match s.strip_prefix("custom:") {
+        Some(num_str) => num_str.parse::<u32>().map(Some).map_err(|_| {
+            RuntimeError::InvalidConfig(format!("invalid custom id in '{}'", s))
+        }),
+        None => Ok(None),
+    }
+}
+
+/// Parse constraint type and its parameters from a constraint entry
+///
+/// Fails with `InvalidConfig` on an unknown constraint type or correlation
+/// method, a minimum correlation outside -100..=100, or a ConditionalCheck
+/// without thresholds.
+fn parse_constraint_type(config: &ConstraintConfig) -> Result<ConstraintType> {
+    let type_str = config.constraint_type.as_str();
+    let invalid = |message: String| RuntimeError::InvalidConfig(format!("constraint '{}': {}", config.name, message));
+    let constraint_type = match type_str.to_lowercase().as_str() {
         "maxratio" => ConstraintType::MaxRatio {
-            ratio: ratio.unwrap_or(2),
+            ratio: config.ratio.unwrap_or(2),
         },
         "sumbelow" => ConstraintType::SumBelow {
-            threshold: threshold.unwrap_or(1000),
+            threshold: config.threshold.unwrap_or(1000),
         },
-        "conditionalcheck" => ConstraintType::ConditionalCheck,
-        "correlation" => ConstraintType::Correlation {
-            min_correlation: 50, // default
+        "conditionalcheck" => ConstraintType::ConditionalCheck {
+            trigger_threshold: config
+                .trigger_threshold
+                .or(config.threshold)
+                .ok_or_else(|| invalid("missing trigger_threshold".to_string()))?,
+            dependent_threshold: config
+                .dependent_threshold
+                .or(config.threshold)
+                .ok_or_else(|| invalid("missing dependent_threshold".to_string()))?,
         },
-        _ => {
-            if let Some(num_str) = type_str.strip_prefix("custom:") {
-                if let Ok(num) = num_str.parse::<u32>() {
-                    return ConstraintType::Custom(num);
-                }
+        "correlation" => {
+            let min_correlation = config.min_correlation.unwrap_or(50);
+            if !(-100..=100).contains(&min_correlation) {
+                return Err(invalid(format!("min_correlation {} is outside -100..=100", min_correlation)));
             }
-            ConstraintType::SumBelow { threshold: 1000 } // default
+            let method = match config.method.as_deref() {
+                Some(name) => CorrelationMethod::from_name(name)
+                    .ok_or_else(|| invalid(format!("unknown correlation method '{}'", name)))?,
+                None => CorrelationMethod::default(),
+            };
+            ConstraintType::Correlation { min_correlation, method }
         }
-    }
+        _ => match custom_id(type_str)? {
+            Some(id) => ConstraintType::Custom(id),
+            None => return Err(invalid(format!("unknown constraint type '{}'", type_str))),
+        },
+    };
+    Ok(constraint_type)
 }
 
 /// Load configuration from environment variables
@@ -265,6 +497,14 @@ pub fn load_from_env(dimension_count: usize) -> IsaConfig {
     
     let master_seed = env::var("ISA_MASTER_SEED").ok();
     
+    let hash_suite = env::var("ISA_HASH_SUITE")
+        .unwrap_or_else(|_| default_hash_suite());
+    
+    let migrate_on_load = env::var("ISA_MIGRATE_ON_LOAD")
+        .ok()
+        .and_then(|s| s.parse().ok())
+        .unwrap_or(false);
+    
     let mut dimensions = Vec::new();
     for i in 0..dimension_count {
         let name = env::var(format!("ISA_DIM{}_NAME", i))
@@ -288,6 +528,9 @@ pub fn load_from_env(dimension_count: usize) -> IsaConfig {
             .and_then(|s| s.parse().ok())
             .unwrap_or(1.0);
         
+        let accumulation_mode = env::var(format!("ISA_DIM{}_MODE", i))
+            .unwrap_or_else(|_| default_accumulation_mode());
+        
         dimensions.push(DimensionConfig {
             index: i,
             name,
@@ -296,6 +539,8 @@ pub fn load_from_env(dimension_count: usize) -> IsaConfig {
             critical,
             weight,
             enabled: true,
+            accumulation_mode,
+            escalation: None,
         });
     }
     
@@ -304,6 +549,8 @@ pub fn load_from_env(dimension_count: usize) -> IsaConfig {
             learning_rate,
             min_observations,
             master_seed,
+            hash_suite,
+            migrate_on_load,
         },
         dimensions,
         constraints: Vec::new(),
@@ -338,6 +585,8 @@ mod tests {
                 learning_rate: 0.1,
                 min_observations: 10,
                 master_seed: None,
+                hash_suite: "blake3".to_string(),
+                migrate_on_load: false,
             },
             dimensions: vec![
                 DimensionConfig {
@@ -348,18 +597,213 @@ mod tests {
                     critical: true,
                     weight: 1.0,
                     enabled: true,
+                    accumulation_mode: "chained".to_string(),
+                    escalation: None,
                 },
             ],
             constraints: Vec::new(),
             hierarchy: None,
         };
         
-        let policy_set = config.to_policy_set();
+        let policy_set = config.to_policy_set(&CustomRegistry::new()).unwrap();
         assert_eq!(policy_set.len(), 1);
         
         let policy = policy_set.get(0).unwrap();
         assert_eq!(policy.name, "Test Dimension");
-        assert_eq!(policy.max_divergence, 1000);
+        assert_eq!(policy.max_divergence, isa_core::Divergence256::from(1000u64));
         assert!(policy.is_critical);
     }
+    
+    #[test]
+    fn test_history_constraint_syntax() {
+        let entry = |constraint_type: &str| ConstraintConfig {
+            name: constraint_type.to_string(),
+            dimensions: vec![0, 1],
+            constraint_type: constraint_type.to_string(),
+            expression: None,
+            ratio: None,
+            threshold: None,
+            trigger_threshold: Some(5000),
+            dependent_threshold: Some(100),
+            min_correlation: Some(-20),
+            method: Some("Spearman".to_string()),
+            severity: 5,
+        };
+        let mut config = load_from_env(2);
+        config.constraints.push(entry("ConditionalCheck"));
+        config.constraints.push(entry("Correlation"));
+        
+        let constraints = config.to_constraint_set(&CustomRegistry::new()).unwrap();
+        assert_eq!(
+            constraints.get(0).unwrap().constraint_type,
+            ConstraintType::ConditionalCheck { trigger_threshold: 5000, dependent_threshold: 100 }
+        );
+        assert_eq!(
+            constraints.get(1).unwrap().constraint_type,
+            ConstraintType::Correlation { min_correlation: -20, method: CorrelationMethod::Spearman }
+        );
+        
+        let mut invalid = entry("Correlation");
+        invalid.method = Some("kendall".to_string());
+        let mut out_of_range = entry("Correlation");
+        out_of_range.min_correlation = Some(150);
+        let mut no_trigger = entry("ConditionalCheck");
+        no_trigger.trigger_threshold = None;
+        let unknown = entry("SumAbove");
+        for constraint in [invalid, out_of_range, no_trigger, unknown] {
+            config.constraints = vec![constraint];
+            assert!(matches!(config.to_constraint_set(&CustomRegistry::new()), Err(RuntimeError::InvalidConfig(_))));
+        }
+    }
+    
+    #[test]
+    fn test_expression_constraints_checked_at_load() {
+        let mut config = load_from_env(3);
+        config.dimensions[2].name = "Hardware Clock".to_string();
+        let mut entry = ConstraintConfig {
+            name: "spike".to_string(),
+            dimensions: Vec::new(),
+            constraint_type: "Expression".to_string(),
+            expression: Some("d0 > 2 * d1 && \"Hardware Clock\" < 5000 || lag(d1) > 0".to_string()),
+            ratio: None,
+            threshold: None,
+            trigger_threshold: None,
+            dependent_threshold: None,
+            min_correlation: None,
+            method: None,
+            severity: 8,
+        };
+        config.constraints.push(entry.clone());
+        
+        let constraints = config.to_constraint_set(&CustomRegistry::new()).unwrap();
+        let constraint = constraints.get(0).unwrap();
+        assert_eq!(constraint.dimensions, vec![0, 1, 2]);
+        assert_eq!(constraint.severity, 8);
+        assert!(constraint.evaluate(&[
+            isa_core::Divergence256::from(3u64),
+            isa_core::Divergence256::from(1u64),
+            isa_core::Divergence256::ZERO,
+        ]));
+        
+        for invalid in [None, Some("d0 + d1"), Some("Unknown > 1")] {
+            entry.expression = invalid.map(str::to_string);
+            config.constraints[0] = entry.clone();
+            assert!(matches!(config.to_constraint_set(&CustomRegistry::new()), Err(RuntimeError::InvalidConfig(_))));
+        }
+    }
+    
+    #[test]
+    fn test_escalation_config() {
+        let mut config = load_from_env(1);
+        let step = |after, strategy: &str| EscalationStepConfig { after, strategy: strategy.to_string() };
+        config.dimensions[0].escalation = Some(EscalationConfig {
+            window_secs: 3600,
+            cooldown_secs: 86_400,
+            steps: vec![step(1, "monitor"), step(3, "quarantine"), step(4, "full")],
+        });
+        
+        let policies = config.to_policy_set(&CustomRegistry::new()).unwrap();
+        let ladder = policies.get(0).unwrap().escalation.as_ref().unwrap();
+        assert_eq!(ladder.window_secs(), 3600);
+        assert_eq!(ladder.cooldown_secs(), 86_400);
+        assert_eq!(ladder.strategy(2), Some(RecoveryStrategy::FullRecovery));
+        
+        if let Some(escalation) = config.dimensions[0].escalation.as_mut() {
+            escalation.steps.push(step(2, "custom:4"));
+        }
+        assert!(matches!(config.to_policy_set(&CustomRegistry::new()), Err(RuntimeError::InvalidConfig(_))));
+    }
+    
+    #[test]
+    fn test_hash_suite_id() {
+        let mut global = load_from_env(0).global;
+        global.hash_suite = "sha256-hkdf".to_string();
+        assert_eq!(global.hash_suite_id(), Some(isa_core::Sha256HkdfSuite::ID));
+        
+        global.hash_suite = "md5".to_string();
+        assert_eq!(global.hash_suite_id(), None);
+    }
+    
+    #[test]
+    fn test_accumulation_modes_per_dimension() {
+        let mut config = load_from_env(3);
+        config.dimensions[1].accumulation_mode = "multiset".to_string();
+        config.dimensions[2].accumulation_mode = "additive".to_string();
+        
+        let mut state: IntegrityState<3> = IntegrityState::from_master_seed([1u8; 32]);
+        config.apply_accumulation_modes(&mut state).unwrap();
+        assert_eq!(state.dimension(0).unwrap().mode(), AccumulationMode::Chained);
+        assert_eq!(state.dimension(1).unwrap().mode(), AccumulationMode::Multiset);
+        assert_eq!(state.dimension(2).unwrap().mode(), AccumulationMode::Additive);
+        
+        let mut dynamic = DynamicIntegrityState::new(4, [1u8; 32]);
+        config.apply_accumulation_modes_dynamic(&mut dynamic).unwrap();
+        assert_eq!(dynamic.dimension(1).unwrap().mode(), AccumulationMode::Multiset);
+        assert_eq!(dynamic.dimension(3).unwrap().mode(), AccumulationMode::Chained);
+    }
+    
+    #[test]
+    fn test_custom_ids_checked_at_load() {
+        use crate::registry::{CustomRecovery, CustomViolation};
+        
+        let mut config = load_from_env(2);
+        config.dimensions[1].strategy = "custom:7".to_string();
+        config.constraints.push(ConstraintConfig {
+            name: "velocity".to_string(),
+            dimensions: vec![0, 1],
+            constraint_type: "custom:3".to_string(),
+            expression: None,
+            ratio: None,
+            threshold: None,
+            trigger_threshold: None,
+            dependent_threshold: None,
+            min_correlation: None,
+            method: None,
+            severity: 5,
+        });
+        
+        let mut registry = CustomRegistry::new();
+        assert!(matches!(config.to_policy_set(&registry), Err(RuntimeError::InvalidConfig(_))));
+        assert!(matches!(config.to_constraint_set(&registry), Err(RuntimeError::InvalidConfig(_))));
+        
+        registry
+            .register_recovery_handler(7, |_: &CustomViolation<'_>| {
+                Ok(CustomRecovery::Apply(RecoveryStrategy::MonitorOnly))
+            })
+            .unwrap();
+        assert!(matches!(
+            config.to_policy_set(&registry).unwrap().get(1).unwrap().recovery_strategy,
+            RecoveryStrategy::Custom(7)
+        ));
+        assert!(matches!(config.to_constraint_set(&registry), Err(RuntimeError::InvalidConfig(_))));
+        
+        registry
+            .register_constraint_evaluator(3, |_: &DimensionConstraint, _: &[isa_core::Divergence256]| false)
+            .unwrap();
+        assert_eq!(
+            config.to_constraint_set(&registry).unwrap().get(0).unwrap().constraint_type,
+            ConstraintType::Custom(3)
+        );
+        
+        config.dimensions[0].strategy = "custom:seven".to_string();
+        assert!(matches!(config.to_policy_set(&registry), Err(RuntimeError::InvalidConfig(_))));
+        
+        config.dimensions[0].strategy = "monitor".to_string();
+        config.dimensions[0].escalation = Some(EscalationConfig {
+            window_secs: 3600,
+            cooldown_secs: 3600,
+            steps: vec![EscalationStepConfig { after: 1, strategy: "custom:8".to_string() }],
+        });
+        assert!(matches!(config.to_policy_set(&registry), Err(RuntimeError::InvalidConfig(_))));
+    }
+    
+    #[test]
+    fn test_accumulation_modes_rejects_invalid_entries() {
+        let mut config = load_from_env(3);
+        config.dimensions[0].accumulation_mode = "sorted".to_string();
+        assert!(matches!(config.accumulation_modes(3), Err(RuntimeError::InvalidConfig(_))));
+        
+        let config = load_from_env(3);
+        assert!(matches!(config.accumulation_modes(2), Err(RuntimeError::InvalidConfig(_))));
+    }
 }
@@ -15,13 +15,16 @@
 //! - "If dimension A exceeds threshold, dimension B must be checked"
 //! - "Dimensions A and B must maintain a specific ratio"
 
-use isa_core::STATE_SIZE;
+use crate::expression::Expression;
+use crate::history::{CorrelationMethod, DivergenceHistory};
+use crate::registry::CustomRegistry;
+use isa_core::{Divergence256, DivergenceReport};
 
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 
 /// Type of relationship between dimensions.
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum ConstraintType {
     /// Dimension A divergence must be <= ratio * Dimension B divergence.
@@ -30,14 +33,19 @@ pub enum ConstraintType {
     /// Sum of divergences must be below threshold.
     SumBelow { threshold: u64 },
     
-    /// If dimension A exceeds threshold, dimension B must be checked.
-    ConditionalCheck,
+    /// If dimension A (the trigger) exceeds `trigger_threshold`, dimension B
+    /// (the dependent) must stay within `dependent_threshold`.
+    ConditionalCheck { trigger_threshold: u64, dependent_threshold: u64 },
     
-    /// Dimensions must maintain correlation (for ML/statistical analysis).
-    Correlation { min_correlation: i32 }, // -100 to 100
+    /// Dimensions A and B must maintain a correlation of at least
+    /// `min_correlation` percent (-100 to 100) over a divergence history.
+    Correlation { min_correlation: i32, method: CorrelationMethod },
     
     /// Custom constraint function (index into registry).
     Custom(u32),
+    
+    /// Rule in the constraint expression language, violated when true.
+    Expression(Expression),
 }
 
 /// A constraint between two or more dimensions.
@@ -61,6 +69,11 @@ pub struct DimensionConstraint {
 }
 
 impl DimensionConstraint {
+    /// Create a constraint from an expression over the dimensions it refers to.
+    pub fn from_expression(name: impl Into<String>, expression: Expression) -> Self {
+        Self::new(name, expression.dimensions(), ConstraintType::Expression(expression))
+    }
+    
     /// Create a new constraint.
     pub fn new(name: impl Into<String>, dimensions: Vec<usize>, constraint_type: ConstraintType) -> Self {
         Self {
@@ -80,13 +93,50 @@ impl DimensionConstraint {
     
     /// Evaluate this constraint against divergence values.
     ///
-    /// Returns true if the constraint is violated.
-    pub fn evaluate(&self, divergences: &[[u8; STATE_SIZE]]) -> bool {
+    /// Returns true if the constraint is violated. `Custom` constraints are
+    /// never violated; see [`DimensionConstraint::evaluate_with`].
+    /// `Correlation` constraints need a history; see
+    /// [`DimensionConstraint::evaluate_history`].
+    pub fn evaluate(&self, divergences: &[Divergence256]) -> bool {
+        self.evaluate_with(divergences, &CustomRegistry::new())
+    }
+    
+    /// Evaluate this constraint, dispatching `Custom(id)` to the evaluator
+    /// registered under `id`.
+    ///
+    /// A `Custom` constraint without a registered evaluator is not violated.
+    pub fn evaluate_with(&self, divergences: &[Divergence256], registry: &CustomRegistry) -> bool {
+        self.check(divergences, None, None, registry)
+    }
+    
+    /// Evaluate this constraint against a divergence report, which also
+    /// gives `Expression` constraints the event counters used by `lag`.
+    pub fn evaluate_report(&self, report: &DivergenceReport, registry: &CustomRegistry) -> bool {
+        let divergences: Vec<_> = report.dimensions().iter().map(|dim| dim.distance).collect();
+        self.check(&divergences, Some(report), None, registry)
+    }
+    
+    /// Evaluate this constraint against the latest sample of `history`;
+    /// `Correlation` constraints use the whole window.
+    ///
+    /// A correlation that cannot be computed yet (too few samples, or a
+    /// dimension whose divergence never changed) is not a violation.
+    pub fn evaluate_history(&self, history: &DivergenceHistory, registry: &CustomRegistry) -> bool {
+        self.check(history.latest().unwrap_or(&[]), None, Some(history), registry)
+    }
+    
+    fn check(
+        &self,
+        divergences: &[Divergence256],
+        report: Option<&DivergenceReport>,
+        history: Option<&DivergenceHistory>,
+        registry: &CustomRegistry,
+    ) -> bool {
         if !self.enabled || self.dimensions.is_empty() {
             return false;
         }
         
-        match self.constraint_type {
+        match &self.constraint_type {
             ConstraintType::MaxRatio { ratio } => {
                 if self.dimensions.len() < 2 {
                     return false;
@@ -95,45 +145,56 @@ impl DimensionConstraint {
                 let div_a = self.get_divergence_value(divergences, self.dimensions[0]);
                 let div_b = self.get_divergence_value(divergences, self.dimensions[1]);
                 
-                if div_b == 0 {
-                    return div_a > 0;
+                if div_b.is_zero() {
+                    return !div_a.is_zero();
                 }
                 
-                div_a > div_b.saturating_mul(ratio as u64)
+                div_a > div_b.saturating_mul(*ratio as u64)
             }
             
             ConstraintType::SumBelow { threshold } => {
-                let sum: u64 = self.dimensions.iter()
+                let sum = self.dimensions.iter()
                     .map(|&idx| self.get_divergence_value(divergences, idx))
-                    .sum();
+                    .fold(Divergence256::ZERO, Divergence256::saturating_add);
                 
-                sum > threshold
+                sum > Divergence256::from(*threshold)
             }
             
-            ConstraintType::ConditionalCheck => {
-                // This requires external logic to implement
-                false
+            ConstraintType::ConditionalCheck { trigger_threshold, dependent_threshold } => {
+                if self.dimensions.len() < 2 {
+                    return false;
+                }
+                
+                let trigger = self.get_divergence_value(divergences, self.dimensions[0]);
+                let dependent = self.get_divergence_value(divergences, self.dimensions[1]);
+                
+                trigger > Divergence256::from(*trigger_threshold)
+                    && dependent > Divergence256::from(*dependent_threshold)
             }
             
-            ConstraintType::Correlation { .. } => {
-                // Correlation analysis requires historical data
-                false
+            ConstraintType::Correlation { min_correlation, method } => {
+                if self.dimensions.len() < 2 {
+                    return false;
+                }
+                
+                history
+                    .and_then(|history| history.correlation(self.dimensions[0], self.dimensions[1], *method))
+                    .is_some_and(|correlation| correlation < *min_correlation)
             }
             
-            ConstraintType::Custom(_) => {
-                // Custom constraints require external implementation
-                false
-            }
+            ConstraintType::Custom(id) => registry
+                .constraint_evaluator(*id)
+                .is_some_and(|evaluator| evaluator.evaluate(self, divergences)),
+            
+            ConstraintType::Expression(expression) => match report {
+                Some(report) => expression.evaluate_report(report),
+                None => expression.evaluate(divergences),
+            },
         }
     }
     
-    fn get_divergence_value(&self, divergences: &[[u8; STATE_SIZE]], index: usize) -> u64 {
-        divergences.get(index).map(|div| {
-            u64::from_le_bytes([
-                div[0], div[1], div[2], div[3],
-                div[4], div[5], div[6], div[7],
-            ])
-        }).unwrap_or(0)
+    fn get_divergence_value(&self, divergences: &[Divergence256], index: usize) -> Divergence256 {
+        divergences.get(index).copied().unwrap_or(Divergence256::ZERO)
     }
 }
 
@@ -165,11 +226,56 @@ impl ConstraintSet {
     /// Evaluate all constraints against divergence values.
     ///
     /// Returns a vector of (constraint_index, constraint) pairs for violated constraints.
-    pub fn evaluate(&self, divergences: &[[u8; STATE_SIZE]]) -> Vec<(usize, &DimensionConstraint)> {
+    pub fn evaluate(&self, divergences: &[Divergence256]) -> Vec<(usize, &DimensionConstraint)> {
+        self.evaluate_with(divergences, &CustomRegistry::new())
+    }
+    
+    /// Evaluate all constraints, dispatching `Custom` constraints to `registry`.
+    pub fn evaluate_with(
+        &self,
+        divergences: &[Divergence256],
+        registry: &CustomRegistry,
+    ) -> Vec<(usize, &DimensionConstraint)> {
+        let mut violations = Vec::new();
+        
+        for (i, constraint) in self.constraints.iter().enumerate() {
+            if constraint.evaluate_with(divergences, registry) {
+                violations.push((i, constraint));
+            }
+        }
+        
+        violations
+    }
+    
+    /// Evaluate all constraints against a divergence report (see
+    /// [`DimensionConstraint::evaluate_report`]).
+    pub fn evaluate_report(
+        &self,
+        report: &DivergenceReport,
+        registry: &CustomRegistry,
+    ) -> Vec<(usize, &DimensionConstraint)> {
         let mut violations = Vec::new();
         
         for (i, constraint) in self.constraints.iter().enumerate() {
-            if constraint.evaluate(divergences) {
+            if constraint.evaluate_report(report, registry) {
+                violations.push((i, constraint));
+            }
+        }
+        
+        violations
+    }
+    
+    /// Evaluate all constraints against a divergence history (see
+    /// [`DimensionConstraint::evaluate_history`]).
+    pub fn evaluate_history(
+        &self,
+        history: &DivergenceHistory,
+        registry: &CustomRegistry,
+    ) -> Vec<(usize, &DimensionConstraint)> {
+        let mut violations = Vec::new();
+        
+        for (i, constraint) in self.constraints.iter().enumerate() {
+            if constraint.evaluate_history(history, registry) {
                 violations.push((i, constraint));
             }
         }
@@ -209,8 +315,8 @@ mod tests {
         // Dimension 0 = 100, Dimension 1 = 60
         // 100 > 60 * 2 = 120? No, so not violated
         let divergences = vec![
-            [100u8, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
-            [60u8, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+            Divergence256::from(100u64),
+            Divergence256::from(60u64),
         ];
         
         assert!(!constraint.evaluate(&divergences));
@@ -218,8 +324,8 @@ mod tests {
         // Dimension 0 = 100, Dimension 1 = 40
         // 100 > 40 * 2 = 80, so 100 > 80 = true
         let divergences2 = vec![
-            [100u8, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
-            [30u8, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+            Divergence256::from(100u64),
+            Divergence256::from(30u64),
         ];
         
         assert!(constraint.evaluate(&divergences2));
@@ -234,21 +340,93 @@ mod tests {
         );
         
         let divergences = vec![
-            [50u8, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
-            [60u8, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
-            [70u8, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+            Divergence256::from(50u64),
+            Divergence256::from(60u64),
+            Divergence256::from(70u64),
         ];
         
         // Sum = 180, threshold = 200, so not violated
         assert!(!constraint.evaluate(&divergences));
         
         let divergences2 = vec![
-            [80u8, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
-            [80u8, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
-            [80u8, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+            Divergence256::from(80u64),
+            Divergence256::from(80u64),
+            Divergence256::from(80u64),
         ];
         
         // Sum = 240, threshold = 200, so violated
         assert!(constraint.evaluate(&divergences2));
     }
+    
+    #[test]
+    fn test_conditional_check_constraint() {
+        let constraint = DimensionConstraint::new(
+            "finance_then_hardware",
+            vec![0, 2],
+            ConstraintType::ConditionalCheck { trigger_threshold: 100, dependent_threshold: 10 }
+        );
+        
+        let divergences = |finance: u64, hardware: u64| vec![
+            Divergence256::from(finance),
+            Divergence256::ZERO,
+            Divergence256::from(hardware),
+        ];
+        
+        // Trigger below its threshold: the dependent is not checked
+        assert!(!constraint.evaluate(&divergences(100, 500)));
+        // Trigger exceeded, dependent within its threshold
+        assert!(!constraint.evaluate(&divergences(101, 10)));
+        // Trigger exceeded, dependent exceeded
+        assert!(constraint.evaluate(&divergences(101, 11)));
+    }
+    
+    #[test]
+    fn test_expression_constraint() {
+        let expression = Expression::parse("d0 > 2 * d1 && d2 < 5000 || lag(d1) > 0").unwrap();
+        let constraint = DimensionConstraint::from_expression("expr", expression);
+        assert_eq!(constraint.dimensions, vec![0, 1, 2]);
+        
+        let divergences = vec![
+            Divergence256::from(21u64),
+            Divergence256::from(10u64),
+            Divergence256::from(4999u64),
+        ];
+        assert!(constraint.evaluate(&divergences));
+        
+        let report = DivergenceReport::new(vec![
+            isa_core::DimensionDivergence { distance: Divergence256::ZERO, counter: 4, reference_counter: 4 },
+            isa_core::DimensionDivergence { distance: Divergence256::ZERO, counter: 2, reference_counter: 4 },
+        ]);
+        let mut set = ConstraintSet::new();
+        set.add_constraint(constraint);
+        assert!(set.evaluate(&[Divergence256::ZERO; 3]).is_empty());
+        assert_eq!(set.evaluate_report(&report, &CustomRegistry::new()).len(), 1);
+    }
+    
+    #[test]
+    fn test_correlation_constraint() {
+        let constraint = DimensionConstraint::new(
+            "move_together",
+            vec![0, 1],
+            ConstraintType::Correlation { min_correlation: 50, method: CorrelationMethod::Spearman }
+        );
+        let registry = CustomRegistry::new();
+        let mut set = ConstraintSet::new();
+        set.add_constraint(constraint.clone());
+        
+        let mut history = DivergenceHistory::new(4).unwrap();
+        for (a, b) in [(1u64, 10u64), (2, 20), (3, 30)] {
+            history.record(&[Divergence256::from(a), Divergence256::from(b)]);
+        }
+        assert!(!constraint.evaluate_history(&history, &registry));
+        // Without a history the correlation is unknown
+        assert!(!constraint.evaluate(history.latest().unwrap()));
+        
+        // Dimension 1 falls while dimension 0 keeps rising
+        for (a, b) in [(4u64, 5u64), (5, 4), (6, 3)] {
+            history.record(&[Divergence256::from(a), Divergence256::from(b)]);
+        }
+        assert!(constraint.evaluate_history(&history, &registry));
+        assert_eq!(set.evaluate_history(&history, &registry).len(), 1);
+    }
 }
@@ -1,89 +1,419 @@
-use crate::{EntropySource, MonotonicClock, Persistence, Result};
-use isa_core::{MultiAxisState, StateVector, CircularDistance, MultiAxisStateExt};
-
-pub struct DeviceRuntime<P: Persistence> {
-    pub state: MultiAxisState,
+use crate::audit_log::AuditLog;
+use crate::config::GlobalConfig;
+use crate::enforcement::EnforcementState;
+use crate::event_log::{EventLog, EventRecord};
+use crate::policy::{EscalationLadder, EscalationState};
+use crate::rollback::RollbackGuard;
+use crate::{EntropySource, MonotonicClock, Persistence, Result, RuntimeError};
+use std::cell::Cell;
+use std::collections::BTreeMap;
+use zeroize::Zeroizing;
+use isa_core::integrity_state::encoded_hash_suite;
+use isa_core::{
+    migrate, AccumulationMode, Blake3Suite, CircularDistance, EntropyMode, HashSuite, IntegrityState,
+    MigrationRecord, MultiAxisStateExt, Sha256HkdfSuite, Shake256Suite, StateVector,
+};
+
+pub struct DeviceRuntime<P: Persistence, H: HashSuite = Blake3Suite> {
+    pub state: IntegrityState<3, H>,
     entropy: EntropySource,
     clock: MonotonicClock,
     persistence: P,
     last_timestamp: u64,
+    migration: Option<MigrationRecord>,
+    event_log: Option<EventLog>,
+    rollback_guard: Option<Box<dyn RollbackGuard>>,
+    audit_log: Option<AuditLog>,
+    enforcement: EnforcementState,
+    enforcement_stored: Cell<bool>,
 }
 
 impl<P: Persistence> DeviceRuntime<P> {
     pub fn new(master_seed: [u8; 32], persistence: P) -> Self {
-        Self {
-            state: MultiAxisState::from_master_seed(master_seed),
-            entropy: EntropySource::new(),
-            clock: MonotonicClock::new(),
-            persistence,
-            last_timestamp: 0,
-        }
+        Self::new_with_suite(master_seed, persistence)
     }
 
     pub fn load_or_create(master_seed: [u8; 32], persistence: P) -> Result<Self> {
-        let state = if persistence.exists() {
-            persistence.load()?
-        } else {
-            MultiAxisState::from_master_seed(master_seed)
+        Self::load_or_create_with_suite(master_seed, persistence, false)
+    }
+}
+
+impl<P: Persistence, H: HashSuite> DeviceRuntime<P, H> {
+    /// Create a runtime whose state uses hash suite `H`.
+    pub fn new_with_suite(master_seed: [u8; 32], persistence: P) -> Self {
+        Self::from_parts(IntegrityState::from_master_seed_with_suite(master_seed), &master_seed, persistence, None)
+    }
+
+    /// Load the persisted state, or create one, using hash suite `H`.
+    ///
+    /// If the persisted state was written under another suite, it is migrated
+    /// to `H` when `migrate_on_load` is set (see `isa_core::migration`); the
+    /// migration record is appended to the persistence's history before the
+    /// migrated state is saved. Otherwise loading fails with `HashSuiteMismatch`.
+    ///
+    /// The entropy source follows the entropy mode recorded in the loaded state.
+    ///
+    /// A stored state without an enforcement state, such as one saved by
+    /// v0.1.0 or directly through `Persistence::save`, is adopted: an empty
+    /// enforcement state is saved next to it (see `crate::enforcement`).
+    pub fn load_or_create_with_suite(
+        master_seed: [u8; 32],
+        persistence: P,
+        migrate_on_load: bool,
+    ) -> Result<Self> {
+        let enforcement = match persistence.read_enforcement_state()? {
+            Some(bytes) => Some(EnforcementState::from_bytes(&bytes)?),
+            None => None,
         };
+        if !persistence.exists() {
+            return Self::new_with_suite(master_seed, persistence).with_stored_enforcement(enforcement);
+        }
+        
+        let bytes = persistence.read_bytes()?;
+        let stored_suite = encoded_hash_suite(&bytes).map_err(|_| RuntimeError::InvalidState)?;
+        if stored_suite == H::ID {
+            let state = IntegrityState::from_bytes(&bytes).map_err(|_| RuntimeError::InvalidState)?;
+            return Self::from_parts(state, &master_seed, persistence, None).with_stored_enforcement(enforcement);
+        }
+        
+        if !migrate_on_load {
+            return Err(RuntimeError::HashSuiteMismatch { found: stored_suite, expected: H::ID });
+        }
+        
+        let (state, record) = match stored_suite {
+            Blake3Suite::ID => migrate_bytes::<Blake3Suite, H>(&bytes, &master_seed)?,
+            Sha256HkdfSuite::ID => migrate_bytes::<Sha256HkdfSuite, H>(&bytes, &master_seed)?,
+            Shake256Suite::ID => migrate_bytes::<Shake256Suite, H>(&bytes, &master_seed)?,
+            _ => return Err(RuntimeError::InvalidState),
+        };
+        persistence.append_migration_record(&record)?;
+        persistence.write_bytes(&state.to_canonical_bytes())?;
+        
+        Self::from_parts(state, &master_seed, persistence, Some(record)).with_stored_enforcement(enforcement)
+    }
 
-        Ok(Self {
+    /// Load or create a state as directed by the global configuration.
+    ///
+    /// `config.hash_suite` must name `H`; `config.migrate_on_load` controls
+    /// whether states written under another suite are migrated.
+    pub fn load_or_create_with_config(
+        master_seed: [u8; 32],
+        persistence: P,
+        config: &GlobalConfig,
+    ) -> Result<Self> {
+        match config.hash_suite_id() {
+            None => return Err(RuntimeError::UnknownHashSuite(config.hash_suite.clone())),
+            Some(id) if id != H::ID => {
+                return Err(RuntimeError::HashSuiteMismatch { found: id, expected: H::ID });
+            }
+            Some(_) => {}
+        }
+        Self::load_or_create_with_suite(master_seed, persistence, config.migrate_on_load)
+    }
+
+    fn from_parts(
+        state: IntegrityState<3, H>,
+        master_seed: &[u8; 32],
+        persistence: P,
+        migration: Option<MigrationRecord>,
+    ) -> Self {
+        Self {
+            entropy: EntropySource::with_mode(state.entropy_mode(), *master_seed),
             state,
-            entropy: EntropySource::new(),
             clock: MonotonicClock::new(),
             persistence,
             last_timestamp: 0,
-        })
+            migration,
+            event_log: None,
+            rollback_guard: None,
+            audit_log: None,
+            enforcement: EnforcementState::default(),
+            enforcement_stored: Cell::new(false),
+        }
+    }
+
+    /// Restore the quarantines saved through the persistence. A stored state
+    /// without them gets an empty enforcement state, saved now.
+    fn with_stored_enforcement(mut self, enforcement: Option<EnforcementState>) -> Result<Self> {
+        match enforcement {
+            Some(enforcement) => {
+                self.enforcement = enforcement;
+                self.enforcement_stored.set(true);
+            }
+            None if self.persistence.exists() => self.save_enforcement()?,
+            None => {}
+        }
+        Ok(self)
+    }
+
+    /// Use the given accumulation mode for every axis (see `isa_core::axis`).
+    ///
+    /// The mode is saved with the state, so it only needs to be chosen when
+    /// the state is first created.
+    pub fn with_accumulation_mode(mut self, mode: AccumulationMode) -> Self {
+        self.state = self.state.clone().with_accumulation_mode(mode);
+        self
+    }
+
+    /// Derive entropy from the master seed instead of the platform RNG (see `isa_core::entropy`).
+    ///
+    /// `master_seed` must be the seed the state was created from. The mode is
+    /// saved with the state, and `load_or_create` restores it, so it only needs
+    /// to be chosen when the state is first created. A trusted authority with
+    /// the same seed and event stream can then reproduce the device state.
+    pub fn with_deterministic_entropy(mut self, master_seed: [u8; 32]) -> Self {
+        self.entropy = EntropySource::deterministic(master_seed);
+        self.state = self.state.clone().with_entropy_mode(EntropyMode::Deterministic);
+        self
+    }
+
+    /// Entropy mode of this runtime, as recorded in its state.
+    pub fn entropy_mode(&self) -> EntropyMode {
+        self.entropy.mode()
+    }
+
+    /// Append the inputs of every accumulation to `event_log` (see `crate::event_log`).
+    ///
+    /// Each record is written before the state changes, so the log is never
+    /// behind the in-memory state.
+    pub fn with_event_log(mut self, event_log: EventLog) -> Self {
+        self.event_log = Some(event_log);
+        self
+    }
+
+    pub fn event_log(&self) -> Option<&EventLog> {
+        self.event_log.as_ref()
+    }
+
+    /// Append every recovery audit to `audit_log` (see `crate::audit_log`).
+    pub fn with_audit_log(mut self, audit_log: AuditLog) -> Self {
+        self.audit_log = Some(audit_log);
+        self
+    }
+
+    pub fn audit_log(&self) -> Option<&AuditLog> {
+        self.audit_log.as_ref()
+    }
+
+    /// Refuse further events on `dimension` until it is released, and save that fact.
+    ///
+    /// Returns `false` if the dimension was already quarantined.
+    pub fn quarantine(&mut self, dimension: usize) -> Result<bool> {
+        if dimension >= self.state.dimensions().len() {
+            return Err(RuntimeError::InvalidState);
+        }
+        if self.is_quarantined(dimension) {
+            return Ok(false);
+        }
+        let mut enforcement = self.enforcement.clone();
+        enforcement.quarantined.insert(dimension);
+        self.commit_enforcement(enforcement)?;
+        Ok(true)
+    }
+
+    /// Lift the quarantine of `dimension`. Returns `false` if it was not quarantined.
+    pub fn release(&mut self, dimension: usize) -> Result<bool> {
+        if !self.is_quarantined(dimension) {
+            return Ok(false);
+        }
+        let mut enforcement = self.enforcement.clone();
+        enforcement.quarantined.remove(&dimension);
+        self.commit_enforcement(enforcement)?;
+        Ok(true)
+    }
+
+    pub fn is_quarantined(&self, dimension: usize) -> bool {
+        self.enforcement.quarantined.contains(&dimension)
+    }
+
+    /// Quarantined dimensions, in index order.
+    pub fn quarantined(&self) -> Vec<usize> {
+        self.enforcement.quarantined.iter().copied().collect()
+    }
+
+    /// Escalation history of `dimension`, if it has violated an escalating policy.
+    pub fn escalation(&self, dimension: usize) -> Option<&EscalationState> {
+        self.enforcement.escalations.get(&dimension)
+    }
+
+    /// Record a violation of `dimension` at `now` (seconds since the Unix
+    /// epoch) on `ladder`, save the history, and return the index of the
+    /// step in force (see [`EscalationLadder::record`]).
+    pub fn escalate(&mut self, dimension: usize, ladder: &EscalationLadder, now: u64) -> Result<Option<usize>> {
+        if dimension >= self.state.dimensions().len() {
+            return Err(RuntimeError::InvalidState);
+        }
+        let mut enforcement = self.enforcement.clone();
+        let step = ladder.record(enforcement.escalations.entry(dimension).or_default(), now);
+        self.commit_enforcement(enforcement)?;
+        Ok(step)
+    }
+
+    /// Replace the escalation histories of the given dimensions and save them in one write.
+    pub(crate) fn store_escalations(&mut self, escalations: BTreeMap<usize, EscalationState>) -> Result<()> {
+        if escalations.is_empty() {
+            return Ok(());
+        }
+        let mut enforcement = self.enforcement.clone();
+        enforcement.escalations.extend(escalations);
+        self.commit_enforcement(enforcement)
+    }
+
+    /// Forget the escalation history of `dimension`. Returns `false` if it had none.
+    pub fn reset_escalation(&mut self, dimension: usize) -> Result<bool> {
+        if self.escalation(dimension).is_none() {
+            return Ok(false);
+        }
+        let mut enforcement = self.enforcement.clone();
+        enforcement.escalations.remove(&dimension);
+        self.commit_enforcement(enforcement)?;
+        Ok(true)
+    }
+
+    /// Save `enforcement`, then put it in force, so the runtime never acts
+    /// on an enforcement state that is not stored.
+    fn commit_enforcement(&mut self, enforcement: EnforcementState) -> Result<()> {
+        self.persistence.write_enforcement_state(&enforcement.to_bytes())?;
+        self.enforcement = enforcement;
+        self.enforcement_stored.set(true);
+        Ok(())
+    }
+
+    fn save_enforcement(&self) -> Result<()> {
+        self.persistence.write_enforcement_state(&self.enforcement.to_bytes())?;
+        self.enforcement_stored.set(true);
+        Ok(())
+    }
+
+    fn check_not_quarantined(&self, axis: EventAxis) -> Result<()> {
+        if self.is_quarantined(axis.index()) {
+            return Err(RuntimeError::DimensionQuarantined(axis.index()));
+        }
+        Ok(())
+    }
+
+    /// Refuse states older than the high-water mark kept by `guard`, and raise
+    /// the mark on every save (see `crate::rollback`).
+    ///
+    /// Attach the guard right after loading: this fails with `StateRolledBack`
+    /// if the loaded state's counters are below the mark, including when the
+    /// state file was deleted and a fresh state created.
+    pub fn with_rollback_guard<G: RollbackGuard + 'static>(mut self, guard: G) -> Result<Self> {
+        guard.check(&self.counters())?;
+        self.rollback_guard = Some(Box::new(guard));
+        Ok(self)
+    }
+
+    fn counters(&self) -> Vec<u64> {
+        self.state.dimensions().iter().map(|dim| dim.counter()).collect()
+    }
+
+    /// Record of the hash-suite migration performed when this runtime was loaded, if any.
+    pub fn migration_record(&self) -> Option<&MigrationRecord> {
+        self.migration.as_ref()
     }
 
     pub fn record_sale(&mut self, sale_bytes: &[u8]) -> Result<StateVector> {
+        for axis in [EventAxis::Finance, EventAxis::Time, EventAxis::Hardware] {
+            self.check_not_quarantined(axis)?;
+        }
+        
         let current_time = self.clock.now()?;
         let delta_t = current_time.saturating_sub(self.last_timestamp);
-        self.last_timestamp = current_time;
-
-        let entropy = self.entropy.gather(32)?;
 
         // Use dimension indices from standard profile
-        self.state.finance_mut().accumulate(sale_bytes, &entropy, delta_t);
-        self.state.time_mut().accumulate(&current_time.to_le_bytes(), &entropy, delta_t);
+        let entropy = self.event_entropy(EventAxis::Finance)?;
+        let finance = self.event_record(EventAxis::Finance, sale_bytes, &entropy, delta_t)?;
+        
+        let entropy = self.event_entropy(EventAxis::Time)?;
+        let time = self.event_record(EventAxis::Time, &current_time.to_le_bytes(), &entropy, delta_t)?;
+
+        // In deterministic mode the hardware sample is the derived entropy itself
+        let entropy = self.event_entropy(EventAxis::Hardware)?;
+        let hw_sample = match self.entropy.mode() {
+            EntropyMode::Random => self.entropy.gather_32()?.to_vec(),
+            EntropyMode::Deterministic => entropy.to_vec(),
+        };
+        let hardware = self.event_record(EventAxis::Hardware, &hw_sample, &entropy, delta_t)?;
 
-        let hw_entropy = self.entropy.gather_32()?;
-        self.state.hardware_mut().accumulate(&hw_entropy, &entropy, delta_t);
+        self.accumulate(&[finance, time, hardware])?;
+        self.last_timestamp = current_time;
 
         Ok(self.state.state_vector_compat())
     }
 
     pub fn record_event(&mut self, axis: EventAxis, event_data: &[u8]) -> Result<StateVector> {
+        self.check_not_quarantined(axis)?;
+        
         let current_time = self.clock.now()?;
         let delta_t = current_time.saturating_sub(self.last_timestamp);
-        self.last_timestamp = current_time;
 
-        let entropy = self.entropy.gather(32)?;
+        let entropy = self.event_entropy(axis)?;
+        let record = self.event_record(axis, event_data, &entropy, delta_t)?;
 
-        // Map EventAxis to dimension index
-        match axis {
-            EventAxis::Finance => {
-                self.state.finance_mut().accumulate(event_data, &entropy, delta_t);
-            }
-            EventAxis::Time => {
-                self.state.time_mut().accumulate(event_data, &entropy, delta_t);
-            }
-            EventAxis::Hardware => {
-                self.state.hardware_mut().accumulate(event_data, &entropy, delta_t);
-            }
-        }
+        self.accumulate(&[record])?;
+        self.last_timestamp = current_time;
 
         Ok(self.state.state_vector_compat())
     }
 
+    /// Entropy for the next event on `axis`.
+    fn event_entropy(&self, axis: EventAxis) -> Result<Zeroizing<Vec<u8>>> {
+        let counter = self.state.dimension(axis.index()).ok_or(RuntimeError::InvalidState)?.counter();
+        self.entropy.for_event::<H>(axis.index(), counter.wrapping_add(1))
+    }
+
+    /// Inputs of the next accumulation on `axis`.
+    fn event_record(&self, axis: EventAxis, event_data: &[u8], entropy: &[u8], delta_t: u64) -> Result<EventRecord> {
+        let counter = self.state.dimension(axis.index()).ok_or(RuntimeError::InvalidState)?.counter();
+        Ok(EventRecord {
+            axis,
+            data: event_data.to_vec(),
+            entropy: entropy.to_vec(),
+            delta_t,
+            counter: counter.wrapping_add(1),
+        })
+    }
+
+    /// Log the events if an event log is attached, then accumulate them.
+    ///
+    /// The records are appended in one write, so a failing log leaves the
+    /// state untouched.
+    fn accumulate(&mut self, records: &[EventRecord]) -> Result<()> {
+        if let Some(event_log) = &self.event_log {
+            event_log.append_all(records)?;
+        }
+        
+        for record in records {
+            let dimension = self.state.dimension_mut(record.axis.index()).ok_or(RuntimeError::InvalidState)?;
+            dimension.accumulate(&record.data, &record.entropy, record.delta_t);
+        }
+        Ok(())
+    }
+
+    /// Persist the state, then raise the rollback mark if a guard is attached.
+    ///
+    /// The enforcement state is saved first if this runtime has not stored it yet.
     pub fn save(&self) -> Result<()> {
-        self.persistence.save(&self.state)
+        if !self.enforcement_stored.get() {
+            self.save_enforcement()?;
+        }
+        self.persistence.write_bytes(&self.state.to_canonical_bytes())?;
+        match &self.rollback_guard {
+            Some(guard) => guard.advance(&self.counters()),
+            None => Ok(()),
+        }
     }
 
     pub fn state_vector(&self) -> StateVector {
         self.state.state_vector_compat()
     }
 
+    /// The full integrity state, including counters and modes.
+    pub fn state(&self) -> &IntegrityState<3, H> {
+        &self.state
+    }
+
     /// Calculate divergence between current state and a trusted authority state.
     ///
     /// Returns the divergence vector for each axis (finance, time, hardware).
@@ -103,15 +433,10 @@ impl<P: Persistence> DeviceRuntime<P> {
     /// for each axis. This implements Equation 8 from the paper.
     ///
     /// The convergence constant can be applied via `apply_convergence()` to restore
-    /// the device to the correct trajectory.
+    /// the device to the correct trajectory. With `AccumulationMode::Additive`,
+    /// K is the sum of the increments Φ the device missed or added.
     pub fn calculate_convergence_constant(&self, trusted_state: &StateVector) -> StateVector {
-        // K = (S_honest - S_drifted) mod 2^256
-        // This is equivalent to the divergence calculation
-        StateVector {
-            finance: CircularDistance::compute(&trusted_state.finance, &self.state.finance().state()),
-            time: CircularDistance::compute(&trusted_state.time, &self.state.time().state()),
-            hardware: CircularDistance::compute(&trusted_state.hardware, &self.state.hardware().state()),
-        }
+        self.state.convergence_constant(&(*trusted_state).into()).into()
     }
 
     /// Apply convergence constant to heal the device state.
@@ -129,8 +454,10 @@ impl<P: Persistence> DeviceRuntime<P> {
     /// * `Err(_)` - If the recovery fails or state cannot be persisted
     ///
     /// # Security
-    /// This operation is cryptographically logged and creates a permanent audit trail.
-    /// The healing event is recorded with timestamp and reason for forensic analysis.
+    /// With an audit log attached (`with_audit_log`), the audit is appended to the
+    /// hash-chained log, bound to the commitment of the healed state, before the
+    /// healed state is adopted and saved. Without one, keeping the returned
+    /// audit is up to the caller.
     pub fn apply_convergence(
         &mut self,
         convergence_constant: &StateVector,
@@ -138,37 +465,26 @@ impl<P: Persistence> DeviceRuntime<P> {
     ) -> Result<RecoveryAudit> {
         let current_time = self.clock.now()?;
         
-        // Record pre-healing state for audit
-        let pre_healing = self.state_vector();
-        
         // Apply K to each axis: S_restored = (S_drifted + K) mod 2^256
-        *self.state.finance_mut() = isa_core::DimensionAccumulator::from_state(
-            modular_add(&self.state.finance().state(), &convergence_constant.finance),
-            self.state.finance().counter(),
-        );
-        
-        *self.state.time_mut() = isa_core::DimensionAccumulator::from_state(
-            modular_add(&self.state.time().state(), &convergence_constant.time),
-            self.state.time().counter(),
-        );
-        
-        *self.state.hardware_mut() = isa_core::DimensionAccumulator::from_state(
-            modular_add(&self.state.hardware().state(), &convergence_constant.hardware),
-            self.state.hardware().counter(),
-        );
-        
-        let post_healing = self.state_vector();
+        let mut healed = self.state.clone();
+        healed.apply_convergence(&(*convergence_constant).into());
         
         // Create audit record
         let audit = RecoveryAudit {
             timestamp: current_time,
-            pre_healing_state: pre_healing,
-            convergence_constant: convergence_constant.clone(),
-            post_healing_state: post_healing,
+            pre_healing_state: self.state_vector(),
+            convergence_constant: *convergence_constant,
+            post_healing_state: healed.state_vector_compat(),
             reason: audit_reason.to_string(),
         };
         
+        // Log the audit before the healed state takes effect
+        if let Some(audit_log) = &self.audit_log {
+            audit_log.append(audit.clone(), healed.commitment())?;
+        }
+        
         // Persist healed state
+        self.state = healed;
         self.save()?;
         
         Ok(audit)
@@ -206,13 +522,34 @@ impl<P: Persistence> DeviceRuntime<P> {
     }
 }
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum EventAxis {
     Finance,
     Time,
     Hardware,
 }
 
+impl EventAxis {
+    /// Index of the dimension this axis maps to in the standard profile.
+    pub fn index(&self) -> usize {
+        match self {
+            EventAxis::Finance => 0,
+            EventAxis::Time => 1,
+            EventAxis::Hardware => 2,
+        }
+    }
+
+    /// Axis for a dimension index of the standard profile.
+    pub fn from_index(index: usize) -> Option<Self> {
+        match index {
+            0 => Some(EventAxis::Finance),
+            1 => Some(EventAxis::Time),
+            2 => Some(EventAxis::Hardware),
+            _ => None,
+        }
+    }
+}
+
 /// Audit record for state recovery/healing events.
 ///
 /// This structure provides a cryptographically verifiable trail of all
@@ -231,26 +568,20 @@ pub struct RecoveryAudit {
     pub reason: String,
 }
 
-/// Modular addition in Z_2^256.
-///
-/// Computes (a + b) mod 2^256 with proper carry propagation.
-fn modular_add(a: &[u8; 32], b: &[u8; 32]) -> [u8; 32] {
-    let mut result = [0u8; 32];
-    let mut carry = 0u16;
-    
-    for i in 0..32 {
-        let sum = a[i] as u16 + b[i] as u16 + carry;
-        result[i] = sum as u8;
-        carry = sum >> 8;
-    }
-    
-    result
+/// Decode a state written under suite `F` and migrate it to suite `T`.
+fn migrate_bytes<F: HashSuite, T: HashSuite>(
+    bytes: &[u8],
+    master_seed: &[u8; 32],
+) -> Result<(IntegrityState<3, T>, MigrationRecord)> {
+    let old = IntegrityState::<3, F>::from_bytes(bytes).map_err(|_| RuntimeError::InvalidState)?;
+    Ok(migrate(&old, master_seed))
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::FilePersistence;
+    use crate::rollback::RollbackGuard;
     use tempfile::TempDir;
 
     #[test]
@@ -320,4 +651,299 @@ mod tests {
         let after_hw = runtime.state_vector();
         assert_ne!(after_time.hardware, after_hw.hardware);
     }
+
+    #[test]
+    fn test_load_rejects_other_suite_without_migration() {
+        let temp_dir = TempDir::new().unwrap();
+        let state_path = temp_dir.path().join("state.bin");
+        let master_seed = [1u8; 32];
+        
+        DeviceRuntime::new(master_seed, FilePersistence::new(&state_path)).save().unwrap();
+        
+        let result = DeviceRuntime::<_, isa_core::Sha256HkdfSuite>::load_or_create_with_suite(
+            master_seed,
+            FilePersistence::new(&state_path),
+            false,
+        );
+        assert!(matches!(
+            result,
+            Err(RuntimeError::HashSuiteMismatch { found: 1, expected: 2 })
+        ));
+    }
+
+    #[test]
+    fn test_load_migrates_to_configured_suite() {
+        let temp_dir = TempDir::new().unwrap();
+        let state_path = temp_dir.path().join("state.bin");
+        let master_seed = [1u8; 32];
+        
+        let mut old_runtime = DeviceRuntime::new(master_seed, FilePersistence::new(&state_path));
+        old_runtime.record_sale(b"sale:100.00").unwrap();
+        old_runtime.save().unwrap();
+        let old_state = old_runtime.state.clone();
+        
+        let mut config = crate::config::load_from_env(3).global;
+        config.hash_suite = "sha256-hkdf".to_string();
+        config.migrate_on_load = true;
+        
+        let runtime = DeviceRuntime::<_, isa_core::Sha256HkdfSuite>::load_or_create_with_config(
+            master_seed,
+            FilePersistence::new(&state_path),
+            &config,
+        ).unwrap();
+        assert_eq!(runtime.state.dimension(0).unwrap().counter(), 1);
+        
+        let record = *runtime.migration_record().unwrap();
+        record.verify_continuity(&old_state, &runtime.state).unwrap();
+        record.verify_seal::<isa_core::Sha256HkdfSuite>(&master_seed).unwrap();
+        assert_eq!(FilePersistence::new(&state_path).migration_records().unwrap(), vec![record]);
+        
+        // The migrated state was persisted; loading again needs no migration
+        let reloaded = DeviceRuntime::<_, isa_core::Sha256HkdfSuite>::load_or_create_with_config(
+            master_seed,
+            FilePersistence::new(&state_path),
+            &config,
+        ).unwrap();
+        assert!(reloaded.migration_record().is_none());
+        assert_eq!(reloaded.state.state_vector(), runtime.state.state_vector());
+    }
+
+    #[test]
+    fn test_config_must_name_runtime_suite() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut config = crate::config::load_from_env(3).global;
+        config.hash_suite = "md5".to_string();
+        
+        let result = DeviceRuntime::<_, Blake3Suite>::load_or_create_with_config(
+            [1u8; 32],
+            FilePersistence::new(temp_dir.path().join("state.bin")),
+            &config,
+        );
+        assert!(matches!(result, Err(RuntimeError::UnknownHashSuite(_))));
+    }
+
+    #[test]
+    fn test_event_log_replays_to_saved_state() {
+        let temp_dir = TempDir::new().unwrap();
+        let state_path = temp_dir.path().join("state.bin");
+        let master_seed = [1u8; 32];
+        
+        let mut runtime = DeviceRuntime::new(master_seed, FilePersistence::new(&state_path))
+            .with_event_log(EventLog::new(temp_dir.path().join("events.log")));
+        runtime.record_sale(b"sale:100.00").unwrap();
+        runtime.record_event(EventAxis::Finance, b"refund:20.00").unwrap();
+        runtime.save().unwrap();
+        
+        let records = runtime.event_log().unwrap().records().unwrap();
+        assert_eq!(records.len(), 4);
+        assert_eq!(records[3].axis, EventAxis::Finance);
+        assert_eq!(records[3].counter, 2);
+        
+        let persisted = FilePersistence::new(&state_path).load::<IntegrityState<3>>().unwrap();
+        assert!(crate::event_log::replay(master_seed, &records, &persisted).is_consistent());
+    }
+
+    #[test]
+    fn test_failing_event_log_leaves_state_untouched() {
+        let temp_dir = TempDir::new().unwrap();
+        let log_path = temp_dir.path().join("events.log");
+        let master_seed = [1u8; 32];
+        
+        let mut runtime = DeviceRuntime::new(master_seed, FilePersistence::new(temp_dir.path().join("state.bin")))
+            .with_deterministic_entropy(master_seed)
+            .with_event_log(EventLog::new(&log_path));
+        runtime.record_sale(b"sale:100.00").unwrap();
+        let before = runtime.state_vector();
+        
+        // A directory in place of the log makes every append fail
+        std::fs::rename(&log_path, temp_dir.path().join("events.bak")).unwrap();
+        std::fs::create_dir(&log_path).unwrap();
+        assert!(matches!(runtime.record_sale(b"sale:5.00"), Err(RuntimeError::PersistenceFailed(_))));
+        assert!(runtime.record_event(EventAxis::Time, b"tick").is_err());
+        assert_eq!(runtime.state_vector(), before);
+        assert_eq!(runtime.counters(), vec![1, 1, 1]);
+        
+        std::fs::remove_dir(&log_path).unwrap();
+        std::fs::rename(temp_dir.path().join("events.bak"), &log_path).unwrap();
+        runtime.record_sale(b"sale:5.00").unwrap();
+        let records = runtime.event_log().unwrap().records().unwrap();
+        assert_eq!(records.len(), 6);
+        let replayed = crate::event_log::replay(master_seed, &records, runtime.state());
+        assert!(replayed.is_consistent());
+    }
+
+    #[test]
+    fn test_deterministic_entropy_lets_authority_shadow_device() {
+        let temp_dir = TempDir::new().unwrap();
+        let state_path = temp_dir.path().join("state.bin");
+        let master_seed = [1u8; 32];
+        
+        let mut runtime = DeviceRuntime::new(master_seed, FilePersistence::new(&state_path))
+            .with_deterministic_entropy(master_seed)
+            .with_event_log(EventLog::new(temp_dir.path().join("events.log")));
+        runtime.record_sale(b"sale:100.00").unwrap();
+        runtime.record_event(EventAxis::Finance, b"refund:20.00").unwrap();
+        runtime.save().unwrap();
+        
+        // The authority knows the seed and the event stream, but not the entropy
+        let mut shadow = isa_core::MultiAxisState::from_master_seed(master_seed);
+        for record in runtime.event_log().unwrap().records().unwrap() {
+            let dimension = shadow.dimension_mut(record.axis.index()).unwrap();
+            let entropy = isa_core::entropy::deterministic_entropy_with_suite::<Blake3Suite>(
+                &master_seed,
+                record.axis.index() as u32,
+                dimension.counter() + 1,
+            );
+            assert_eq!(record.entropy, entropy);
+            dimension.accumulate(&record.data, &entropy, record.delta_t);
+        }
+        assert_eq!(shadow.state_vector(), runtime.state.state_vector());
+        
+        let reloaded = DeviceRuntime::load_or_create(master_seed, FilePersistence::new(&state_path)).unwrap();
+        assert_eq!(reloaded.state.entropy_mode(), EntropyMode::Deterministic);
+        assert_eq!(reloaded.entropy_mode(), EntropyMode::Deterministic);
+    }
+
+    #[test]
+    fn test_random_entropy_is_default() {
+        let temp_dir = TempDir::new().unwrap();
+        let state_path = temp_dir.path().join("state.bin");
+        
+        DeviceRuntime::new([1u8; 32], FilePersistence::new(&state_path)).save().unwrap();
+        let reloaded = DeviceRuntime::load_or_create([1u8; 32], FilePersistence::new(&state_path)).unwrap();
+        assert_eq!(reloaded.entropy_mode(), EntropyMode::Random);
+    }
+
+    #[test]
+    fn test_additive_mode_persists_and_recovers() {
+        let temp_dir = TempDir::new().unwrap();
+        let state_path = temp_dir.path().join("state.bin");
+        let master_seed = [1u8; 32];
+        
+        let mut runtime = DeviceRuntime::new(master_seed, FilePersistence::new(&state_path))
+            .with_accumulation_mode(AccumulationMode::Additive);
+        runtime.record_sale(b"sale:100.00").unwrap();
+        let trusted = runtime.state_vector();
+        
+        runtime.record_event(EventAxis::Finance, b"rogue").unwrap();
+        runtime.recover_from_trusted_state(&trusted, "test").unwrap();
+        assert_eq!(runtime.state_vector(), trusted);
+        
+        let reloaded = DeviceRuntime::load_or_create(master_seed, FilePersistence::new(&state_path)).unwrap();
+        assert_eq!(reloaded.state.finance().mode(), AccumulationMode::Additive);
+        assert_eq!(reloaded.state_vector(), trusted);
+    }
+
+    #[test]
+    fn test_rollback_guard_rejects_restored_state() {
+        let temp_dir = TempDir::new().unwrap();
+        let state_path = temp_dir.path().join("device.state");
+        let master_seed = [9u8; 32];
+        let guard = crate::FileRollbackGuard::new(temp_dir.path().join("device.mark"));
+
+        let mut runtime = DeviceRuntime::load_or_create(master_seed, FilePersistence::new(&state_path))
+            .unwrap()
+            .with_rollback_guard(guard)
+            .unwrap();
+        runtime.record_sale(b"sale:1").unwrap();
+        runtime.save().unwrap();
+        let old_file = std::fs::read(&state_path).unwrap();
+        runtime.record_sale(b"sale:2").unwrap();
+        runtime.save().unwrap();
+        drop(runtime);
+
+        // The current state loads
+        let guard = crate::FileRollbackGuard::new(temp_dir.path().join("device.mark"));
+        DeviceRuntime::load_or_create(master_seed, FilePersistence::new(&state_path))
+            .unwrap()
+            .with_rollback_guard(guard)
+            .unwrap();
+
+        // The older copy does not
+        std::fs::write(&state_path, &old_file).unwrap();
+        let guard = crate::FileRollbackGuard::new(temp_dir.path().join("device.mark"));
+        let result = DeviceRuntime::load_or_create(master_seed, FilePersistence::new(&state_path))
+            .unwrap()
+            .with_rollback_guard(guard);
+        assert!(matches!(
+            result,
+            Err(RuntimeError::StateRolledBack { dimension: 0, found: 1, expected: 2 })
+        ));
+    }
+
+    #[test]
+    fn test_rollback_guard_rejects_deleted_state() {
+        let temp_dir = TempDir::new().unwrap();
+        let state_path = temp_dir.path().join("device.state");
+        let guard = crate::InMemoryRollbackGuard::new();
+
+        let mut runtime = DeviceRuntime::new([9u8; 32], FilePersistence::new(&state_path))
+            .with_rollback_guard(guard.clone())
+            .unwrap();
+        runtime.record_event(EventAxis::Time, b"tick").unwrap();
+        runtime.save().unwrap();
+        assert_eq!(guard.high_water_mark().unwrap(), Some(vec![0, 1, 0]));
+
+        std::fs::remove_file(&state_path).unwrap();
+        let result = DeviceRuntime::load_or_create([9u8; 32], FilePersistence::new(&state_path))
+            .unwrap()
+            .with_rollback_guard(guard);
+        assert!(matches!(result, Err(RuntimeError::StateRolledBack { dimension: 1, .. })));
+    }
+
+    #[test]
+    fn test_runtime_with_in_memory_persistence() {
+        let persistence = crate::InMemoryPersistence::new();
+        let mut runtime = DeviceRuntime::load_or_create([4u8; 32], persistence.clone()).unwrap();
+        runtime.record_sale(b"sale:1").unwrap();
+        runtime.save().unwrap();
+
+        let reloaded = DeviceRuntime::load_or_create([4u8; 32], persistence).unwrap();
+        assert_eq!(reloaded.state.commitment(), runtime.state.commitment());
+    }
+
+    #[test]
+    fn test_loads_state_saved_by_v0_1_0() {
+        // device.state saved by v0.1.0 after one sale and one finance event
+        const V0_1_0_STATE: &str = "000001000000fbb2ae61a5ad574ac8456f4333e47de45deb00399b44909580e48dc7f33e4b9b0200000000000000da429a10243a94b1e1eabe023ed8a15927c792b2b0a6936dd41669d8eee21a350100000000000000531dfe3b027e9ec3691b0f0df058615be701e24567b663142e9486d11ec3a83b0100000000000000000001000000";
+        let temp_dir = TempDir::new().unwrap();
+        let state_path = temp_dir.path().join("device.state");
+        std::fs::write(&state_path, hex::decode(V0_1_0_STATE).unwrap()).unwrap();
+
+        let mut runtime = DeviceRuntime::load_or_create([7u8; 32], FilePersistence::new(&state_path)).unwrap();
+        assert_eq!(runtime.counters(), vec![2, 1, 1]);
+        assert_eq!(
+            hex::encode(runtime.state_vector().finance),
+            "fbb2ae61a5ad574ac8456f4333e47de45deb00399b44909580e48dc7f33e4b9b"
+        );
+        assert!(runtime.quarantined().is_empty());
+        assert!(FilePersistence::new(&state_path).enforcement_path().exists());
+
+        runtime.record_sale(b"sale:5.00").unwrap();
+        runtime.save().unwrap();
+        drop(runtime);
+        let reloaded = DeviceRuntime::load_or_create([7u8; 32], FilePersistence::new(&state_path)).unwrap();
+        assert_eq!(reloaded.counters(), vec![3, 2, 2]);
+    }
+
+    #[test]
+    fn test_recovery_audits_are_chained_in_log() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut runtime = DeviceRuntime::new([6u8; 32], crate::InMemoryPersistence::new())
+            .with_audit_log(crate::AuditLog::new(temp_dir.path().join("audit.log")));
+        let trusted = runtime.state_vector();
+
+        runtime.record_sale(b"drift:1").unwrap();
+        runtime.recover_from_trusted_state(&trusted, "first recovery").unwrap();
+        runtime.record_sale(b"drift:2").unwrap();
+        let audit = runtime.recover_from_trusted_state(&trusted, "second recovery").unwrap();
+
+        let log = runtime.audit_log().unwrap();
+        let entries = log.entries().unwrap();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[1].audit.reason, audit.reason);
+        assert_eq!(entries[1].previous_hash, entries[0].hash);
+        assert_eq!(entries[1].state_commitment, runtime.state.commitment());
+        assert_eq!(log.verify().unwrap(), None);
+    }
 }
@@ -0,0 +1,730 @@
+//! Policy enforcement: carrying out recovery strategies.
+//!
+//! ## Conformance Classification
+//!
+//! **INFORMATIVE** - Reconciliation strategies are implementation-specific
+//! (see `crate::policy`). This module is the reference behaviour of
+//! `DeviceRuntime`.
+//!
+//! [`PolicySet::evaluate`] finds the dimensions whose divergence from a trusted
+//! state exceeds their threshold. A [`PolicyEnforcer`] acts on each violation
+//! according to its [`RecoveryStrategy`] and returns one [`EnforcementOutcome`]
+//! per violation:
+//!
+//! | Strategy        | Action                                                              |
+//! |-----------------|---------------------------------------------------------------------|
+//! | `MonitorOnly`   | none; the outcome carries the divergence report                      |
+//! | `Quarantine`    | the dimension refuses further events until released (persisted)      |
+//! | `ImmediateHeal` | convergence of that dimension to the trusted state, with an audit    |
+//! | `FullRecovery`  | convergence of every dimension, clearing quarantines, with an audit  |
+//! | `Custom(id)`    | decided by the handler registered under `id`, if any                 |
+//!
+//! A policy with an escalation ladder (see `crate::policy`) first records the
+//! violation in the dimension's escalation history, which is persisted with
+//! the quarantines, and applies the strategy of the step in force.
+//! A custom handler (see `crate::registry`) either picks one of the built-in
+//! strategies or handles the violation itself; without a handler the
+//! violation is reported as unhandled. A full recovery runs at most once per
+//! enforcement, before any other action; quarantines and heals of the same
+//! enforcement are then superseded.
+//!
+//! ## Enforcement State Format
+//!
+//! The set of quarantined dimensions is saved through
+//! `Persistence::write_enforcement_state`:
+//!
+//! ```text
+//! magic "MISP" (4) || version (1) || count u32 LE (4) || dimension u32 LE (4 each)
+//!     || escalation count u32 LE (4) || escalation entries || CRC-32 (4)
+//! escalation entry: dimension u32 LE (4) || level u32 LE (4) || last violation u64 LE (8)
+//!     || count u32 LE (4) || violation time u64 LE (8 each)
+//! ```
+//!
+//! Times are seconds since the Unix epoch. Version 1 ends after the
+//! quarantined dimensions.
+//!
+//! The enforcement state is kept next to the device state it belongs to:
+//! `DeviceRuntime::save` writes it before the state is first saved, and
+//! removing a device removes both. States saved before enforcement existed
+//! have none; `DeviceRuntime::load_or_create` adopts them by saving an empty
+//! enforcement state on first load. Deleting an enforcement state looks the
+//! same and lifts its quarantines, so it needs the same protection as the
+//! state file itself.
+
+use crate::device::{DeviceRuntime, RecoveryAudit};
+use crate::persistence::Persistence;
+use crate::policy::{DimensionPolicy, EscalationState, PolicySet, RecoveryStrategy};
+use crate::registry::{CustomRecovery, CustomRegistry, CustomViolation};
+use crate::time::unix_seconds;
+use crate::{Result, RuntimeError};
+use isa_core::{DimensionDivergence, HashSuite, IntegrityState, MultiAxisStateExt, StateVector};
+use std::collections::{BTreeMap, BTreeSet};
+
+/// Magic bytes at the start of an encoded enforcement state.
+pub const ENFORCEMENT_MAGIC: [u8; 4] = *b"MISP";
+
+/// Enforcement state format version.
+pub const ENFORCEMENT_FORMAT_VERSION: u8 = 2;
+
+/// Enforcement decisions that outlive a runtime.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct EnforcementState {
+    /// Dimensions that refuse further events.
+    pub quarantined: BTreeSet<usize>,
+    /// Violation history of dimensions with an escalating policy.
+    pub escalations: BTreeMap<usize, EscalationState>,
+}
+
+impl EnforcementState {
+    /// Encode the state (see the module docs).
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(17 + 4 * self.quarantined.len());
+        bytes.extend_from_slice(&ENFORCEMENT_MAGIC);
+        bytes.push(ENFORCEMENT_FORMAT_VERSION);
+        bytes.extend_from_slice(&(self.quarantined.len() as u32).to_le_bytes());
+        for &dimension in &self.quarantined {
+            bytes.extend_from_slice(&(dimension as u32).to_le_bytes());
+        }
+        bytes.extend_from_slice(&(self.escalations.len() as u32).to_le_bytes());
+        for (&dimension, escalation) in &self.escalations {
+            bytes.extend_from_slice(&(dimension as u32).to_le_bytes());
+            bytes.extend_from_slice(&escalation.level.to_le_bytes());
+            bytes.extend_from_slice(&escalation.last_violation.to_le_bytes());
+            bytes.extend_from_slice(&(escalation.recent.len() as u32).to_le_bytes());
+            for &time in &escalation.recent {
+                bytes.extend_from_slice(&time.to_le_bytes());
+            }
+        }
+        let checksum = isa_core::canonical::crc32(&bytes);
+        bytes.extend_from_slice(&checksum.to_le_bytes());
+        bytes
+    }
+
+    /// Decode a state produced by [`EnforcementState::to_bytes`]. Version 1
+    /// states, which predate escalation, load without escalation history.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self> {
+        if bytes.len() < 13
+            || bytes[..4] != ENFORCEMENT_MAGIC
+            || !(1..=ENFORCEMENT_FORMAT_VERSION).contains(&bytes[4])
+        {
+            return Err(RuntimeError::InvalidState);
+        }
+        let body_len = bytes.len() - 4;
+        let mut checksum = [0u8; 4];
+        checksum.copy_from_slice(&bytes[body_len..]);
+        if isa_core::canonical::crc32(&bytes[..body_len]) != u32::from_le_bytes(checksum) {
+            return Err(RuntimeError::InvalidState);
+        }
+
+        let mut reader = Reader { bytes: &bytes[5..body_len] };
+        let mut state = Self::default();
+        for _ in 0..reader.u32()? {
+            state.quarantined.insert(reader.u32()? as usize);
+        }
+        if bytes[4] >= 2 {
+            for _ in 0..reader.u32()? {
+                let dimension = reader.u32()? as usize;
+                let level = reader.u32()?;
+                let last_violation = reader.u64()?;
+                let count = reader.u32()? as usize;
+                if count > reader.bytes.len() / 8 {
+                    return Err(RuntimeError::InvalidState);
+                }
+                let recent = (0..count).map(|_| reader.u64()).collect::<Result<_>>()?;
+                state.escalations.insert(dimension, EscalationState { level, last_violation, recent });
+            }
+        }
+        if !reader.bytes.is_empty() {
+            return Err(RuntimeError::InvalidState);
+        }
+        Ok(state)
+    }
+}
+
+/// Cursor over the body of an encoded enforcement state.
+struct Reader<'a> {
+    bytes: &'a [u8],
+}
+
+impl Reader<'_> {
+    fn take<const LEN: usize>(&mut self) -> Result<[u8; LEN]> {
+        if self.bytes.len() < LEN {
+            return Err(RuntimeError::InvalidState);
+        }
+        let mut out = [0u8; LEN];
+        out.copy_from_slice(&self.bytes[..LEN]);
+        self.bytes = &self.bytes[LEN..];
+        Ok(out)
+    }
+
+    fn u32(&mut self) -> Result<u32> {
+        self.take().map(u32::from_le_bytes)
+    }
+
+    fn u64(&mut self) -> Result<u64> {
+        self.take().map(u64::from_le_bytes)
+    }
+}
+
+/// What was done about one violation.
+#[derive(Debug, Clone)]
+pub enum EnforcementAction {
+    /// Nothing was changed; see the outcome's divergence.
+    Monitored,
+    /// The dimension was quarantined. `already` is set if it was quarantined before.
+    Quarantined { already: bool },
+    /// The dimension was converged to the trusted state.
+    Healed(RecoveryAudit),
+    /// Every dimension was converged to the trusted state.
+    FullRecovery(RecoveryAudit),
+    /// A full recovery in the same enforcement already restored the dimension.
+    SupersededByFullRecovery,
+    /// A custom recovery handler dealt with the violation.
+    Custom { id: u32, note: String },
+    /// No handler is available for the strategy.
+    Unhandled,
+}
+
+/// Result of enforcing the policy of one dimension.
+#[derive(Debug, Clone)]
+pub struct EnforcementOutcome {
+    pub dimension: usize,
+    /// Name of the violated policy.
+    pub policy: String,
+    /// Strategy applied: the policy's, or the escalation step's in force.
+    /// For `Custom` strategies, the action shows what the handler chose.
+    pub strategy: RecoveryStrategy,
+    /// Index of the escalation step in force, for policies with a ladder.
+    pub escalation: Option<usize>,
+    /// Divergence from the trusted state before enforcement.
+    pub divergence: DimensionDivergence,
+    pub action: EnforcementAction,
+}
+
+/// Carries out the recovery strategies of a [`PolicySet`] on a runtime.
+#[derive(Debug, Clone)]
+pub struct PolicyEnforcer {
+    policies: PolicySet,
+    registry: CustomRegistry,
+}
+
+impl PolicyEnforcer {
+    pub fn new(policies: PolicySet) -> Self {
+        Self { policies, registry: CustomRegistry::new() }
+    }
+
+    /// Resolve `RecoveryStrategy::Custom` through `registry`.
+    pub fn with_registry(mut self, registry: CustomRegistry) -> Self {
+        self.registry = registry;
+        self
+    }
+
+    pub fn policies(&self) -> &PolicySet {
+        &self.policies
+    }
+
+    pub fn registry(&self) -> &CustomRegistry {
+        &self.registry
+    }
+
+    /// Compare `runtime` with `trusted`, then enforce every violated policy.
+    pub fn enforce<P: Persistence, H: HashSuite>(
+        &self,
+        runtime: &mut DeviceRuntime<P, H>,
+        trusted: &IntegrityState<3, H>,
+    ) -> Result<Vec<EnforcementOutcome>> {
+        self.enforce_at(runtime, trusted, unix_seconds()?)
+    }
+
+    /// [`PolicyEnforcer::enforce`] with violations escalated as happening at
+    /// `now` (seconds since the Unix epoch).
+    pub fn enforce_at<P: Persistence, H: HashSuite>(
+        &self,
+        runtime: &mut DeviceRuntime<P, H>,
+        trusted: &IntegrityState<3, H>,
+        now: u64,
+    ) -> Result<Vec<EnforcementOutcome>> {
+        let report = runtime.state().divergence_report(trusted);
+        let divergences: Vec<_> = report.dimensions().iter().map(|dim| dim.distance).collect();
+        let violations = self.policies.evaluate(&divergences);
+        enforce_violations(runtime, &violations, trusted, &self.registry, now)
+    }
+}
+
+/// How one violation is enforced once custom handlers have been consulted.
+enum Resolution {
+    Builtin(RecoveryStrategy),
+    Handled { id: u32, note: String },
+    Unhandled,
+}
+
+/// Enforce `violations` (as returned by [`PolicySet::evaluate`]) on `runtime`.
+///
+/// Violations of escalating policies are recorded as happening at `now`
+/// (seconds since the Unix epoch). Handlers of `Custom` strategies are
+/// looked up in `registry` and consulted before anything is changed; if one
+/// fails, the escalation histories are left as they were. Handlers run
+/// before the escalation histories are saved, so if that save fails they run
+/// again when the enforcement is retried: they are called at least once per
+/// violation and must be idempotent. Quarantines and escalations take effect
+/// only once saved. Heals and full recoveries converge to
+/// `trusted` and go through `DeviceRuntime::apply_convergence`, so they are
+/// saved and, with an audit log attached, logged.
+pub fn enforce_violations<P: Persistence, H: HashSuite>(
+    runtime: &mut DeviceRuntime<P, H>,
+    violations: &[(usize, &DimensionPolicy)],
+    trusted: &IntegrityState<3, H>,
+    registry: &CustomRegistry,
+    now: u64,
+) -> Result<Vec<EnforcementOutcome>> {
+    let report = runtime.state().divergence_report(trusted);
+    let trusted_vector = trusted.state_vector_compat();
+
+    // Escalations are saved once every handler has succeeded, so a failed
+    // enforcement does not use up a step of the ladder
+    let mut escalations = BTreeMap::new();
+    let mut strategies = Vec::with_capacity(violations.len());
+    let mut resolutions = Vec::with_capacity(violations.len());
+    for &(dimension, policy) in violations {
+        let divergence = report.get(dimension).ok_or(RuntimeError::InvalidState)?;
+        let (strategy, escalation) = match &policy.escalation {
+            Some(ladder) => {
+                let history = escalations
+                    .entry(dimension)
+                    .or_insert_with(|| runtime.escalation(dimension).cloned().unwrap_or_default());
+                let step = ladder.record(history, now);
+                let strategy = step.and_then(|step| ladder.strategy(step));
+                (strategy.unwrap_or(policy.recovery_strategy), step)
+            }
+            None => (policy.recovery_strategy, None),
+        };
+        strategies.push((strategy, escalation));
+        let resolution = match strategy {
+            RecoveryStrategy::Custom(id) => match registry.recovery_handler(id) {
+                Some(handler) => {
+                    let violation = CustomViolation { id, dimension, policy, divergence };
+                    match handler.recover(&violation)? {
+                        CustomRecovery::Apply(RecoveryStrategy::Custom(_)) => Resolution::Unhandled,
+                        CustomRecovery::Apply(strategy) => Resolution::Builtin(strategy),
+                        CustomRecovery::Handled(note) => Resolution::Handled { id, note },
+                    }
+                }
+                None => Resolution::Unhandled,
+            },
+            strategy => Resolution::Builtin(strategy),
+        };
+        resolutions.push(resolution);
+    }
+    runtime.store_escalations(escalations)?;
+
+    let full_recovery = match violations
+        .iter()
+        .zip(&resolutions)
+        .find(|(_, resolution)| matches!(resolution, Resolution::Builtin(RecoveryStrategy::FullRecovery)))
+    {
+        Some(((dimension, policy), _)) => {
+            let reason = format!("policy {}: full recovery (dimension {})", policy.name, dimension);
+            let k = runtime.calculate_convergence_constant(&trusted_vector);
+            let audit = runtime.apply_convergence(&k, &reason)?;
+            for dimension in runtime.quarantined() {
+                runtime.release(dimension)?;
+            }
+            Some(audit)
+        }
+        None => None,
+    };
+
+    let mut outcomes = Vec::with_capacity(violations.len());
+    for ((&(dimension, policy), resolution), (strategy, escalation)) in
+        violations.iter().zip(resolutions).zip(strategies)
+    {
+        let divergence = *report.get(dimension).ok_or(RuntimeError::InvalidState)?;
+        let action = match (resolution, &full_recovery) {
+            (Resolution::Builtin(RecoveryStrategy::MonitorOnly), _) => EnforcementAction::Monitored,
+            (Resolution::Handled { id, note }, _) => EnforcementAction::Custom { id, note },
+            (Resolution::Unhandled, _) => EnforcementAction::Unhandled,
+            (Resolution::Builtin(RecoveryStrategy::FullRecovery), Some(audit)) => {
+                EnforcementAction::FullRecovery(audit.clone())
+            }
+            (_, Some(_)) => EnforcementAction::SupersededByFullRecovery,
+            (Resolution::Builtin(RecoveryStrategy::Quarantine), None) => EnforcementAction::Quarantined {
+                already: !runtime.quarantine(dimension)?,
+            },
+            (Resolution::Builtin(RecoveryStrategy::ImmediateHeal), None) => {
+                let k = single_dimension(&runtime.calculate_convergence_constant(&trusted_vector), dimension)?;
+                let reason = format!("policy {}: immediate heal (dimension {})", policy.name, dimension);
+                EnforcementAction::Healed(runtime.apply_convergence(&k, &reason)?)
+            }
+            (Resolution::Builtin(RecoveryStrategy::Custom(_)), None)
+            | (Resolution::Builtin(RecoveryStrategy::FullRecovery), None) => EnforcementAction::Unhandled,
+        };
+        outcomes.push(EnforcementOutcome {
+            dimension,
+            policy: policy.name.clone(),
+            strategy,
+            escalation,
+            divergence,
+            action,
+        });
+    }
+    Ok(outcomes)
+}
+
+/// `k` with every axis but `dimension` set to zero.
+fn single_dimension(k: &StateVector, dimension: usize) -> Result<StateVector> {
+    let zero = [0u8; 32];
+    let pick = |index: usize, value: &[u8; 32]| if index == dimension { *value } else { zero };
+    if dimension > 2 {
+        return Err(RuntimeError::InvalidState);
+    }
+    Ok(StateVector {
+        finance: pick(0, &k.finance),
+        time: pick(1, &k.time),
+        hardware: pick(2, &k.hardware),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::device::EventAxis;
+    use crate::persistence::InMemoryPersistence;
+
+    fn policies(strategies: [RecoveryStrategy; 3]) -> PolicyEnforcer {
+        let mut set = PolicySet::new();
+        for (name, strategy) in ["finance", "time", "hardware"].into_iter().zip(strategies) {
+            set.add_policy(DimensionPolicy::new(name).with_threshold(0).with_recovery(strategy));
+        }
+        PolicyEnforcer::new(set)
+    }
+
+    /// A runtime that drifted from its trusted state on every axis.
+    fn drifted(persistence: InMemoryPersistence) -> (DeviceRuntime<InMemoryPersistence>, IntegrityState<3>) {
+        let mut runtime = DeviceRuntime::new([3u8; 32], persistence);
+        let trusted = runtime.state().clone();
+        runtime.record_sale(b"unsanctioned sale").unwrap();
+        (runtime, trusted)
+    }
+
+    #[test]
+    fn test_enforcement_state_roundtrip() {
+        let mut state = EnforcementState { quarantined: [0, 2].into_iter().collect(), ..Default::default() };
+        state.escalations.insert(1, EscalationState { level: 2, last_violation: 900, recent: vec![600, 900] });
+        let bytes = state.to_bytes();
+        assert_eq!(EnforcementState::from_bytes(&bytes).unwrap(), state);
+
+        // Version 1 states have no escalation section
+        let mut legacy = bytes[..17].to_vec();
+        legacy[4] = 1;
+        let checksum = isa_core::canonical::crc32(&legacy);
+        legacy.extend_from_slice(&checksum.to_le_bytes());
+        let loaded = EnforcementState::from_bytes(&legacy).unwrap();
+        assert_eq!(loaded.quarantined, state.quarantined);
+        assert!(loaded.escalations.is_empty());
+
+        let mut corrupted = bytes.clone();
+        corrupted[9] ^= 1;
+        assert!(EnforcementState::from_bytes(&corrupted).is_err());
+        assert!(EnforcementState::from_bytes(&bytes[..8]).is_err());
+    }
+
+    #[test]
+    fn test_quarantine_blocks_events_and_persists() {
+        let persistence = InMemoryPersistence::new();
+        let (mut runtime, trusted) = drifted(persistence.clone());
+        runtime.save().unwrap();
+        let enforcer = policies([
+            RecoveryStrategy::MonitorOnly,
+            RecoveryStrategy::Quarantine,
+            RecoveryStrategy::MonitorOnly,
+        ]);
+
+        let outcomes = enforcer.enforce(&mut runtime, &trusted).unwrap();
+        assert_eq!(outcomes.len(), 3);
+        assert!(matches!(outcomes[0].action, EnforcementAction::Monitored));
+        assert_eq!(outcomes[0].divergence.counter_delta(), 1);
+        assert!(matches!(outcomes[1].action, EnforcementAction::Quarantined { already: false }));
+
+        assert!(matches!(
+            runtime.record_event(EventAxis::Time, b"tick"),
+            Err(RuntimeError::DimensionQuarantined(1))
+        ));
+        assert!(matches!(runtime.record_sale(b"sale"), Err(RuntimeError::DimensionQuarantined(1))));
+        runtime.record_event(EventAxis::Finance, b"refund").unwrap();
+
+        // The quarantine survives a restart
+        let mut reloaded = DeviceRuntime::load_or_create([3u8; 32], persistence).unwrap();
+        assert_eq!(reloaded.quarantined(), vec![1]);
+        assert!(reloaded.record_event(EventAxis::Time, b"tick").is_err());
+
+        let outcomes = enforcer.enforce(&mut reloaded, &trusted).unwrap();
+        assert!(matches!(outcomes[1].action, EnforcementAction::Quarantined { already: true }));
+
+        assert!(reloaded.release(1).unwrap());
+        reloaded.record_event(EventAxis::Time, b"tick").unwrap();
+    }
+
+    #[test]
+    fn test_missing_enforcement_state_is_adopted() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let state_path = temp_dir.path().join("device.state");
+        let mut runtime = DeviceRuntime::new([3u8; 32], crate::FilePersistence::new(&state_path));
+        runtime.save().unwrap();
+        runtime.quarantine(1).unwrap();
+        drop(runtime);
+
+        // A state without an enforcement state loads with an empty one
+        let persistence = crate::FilePersistence::new(&state_path);
+        std::fs::remove_file(persistence.enforcement_path()).unwrap();
+        let mut runtime = DeviceRuntime::load_or_create([3u8; 32], crate::FilePersistence::new(&state_path)).unwrap();
+        assert!(runtime.quarantined().is_empty());
+        assert!(persistence.read_enforcement_state().unwrap().is_some());
+
+        // which is kept from then on
+        runtime.quarantine(1).unwrap();
+        drop(runtime);
+        let runtime = DeviceRuntime::load_or_create([3u8; 32], persistence).unwrap();
+        assert_eq!(runtime.quarantined(), vec![1]);
+    }
+
+    #[test]
+    fn test_immediate_heal_converges_one_dimension() {
+        let (mut runtime, trusted) = drifted(InMemoryPersistence::new());
+        let enforcer = policies([
+            RecoveryStrategy::ImmediateHeal,
+            RecoveryStrategy::MonitorOnly,
+            RecoveryStrategy::Custom(7),
+        ]);
+
+        let outcomes = enforcer.enforce(&mut runtime, &trusted).unwrap();
+        let audit = match &outcomes[0].action {
+            EnforcementAction::Healed(audit) => audit,
+            other => panic!("unexpected action {:?}", other),
+        };
+        assert_eq!(audit.post_healing_state.finance, trusted.state_vector_compat().finance);
+        assert!(matches!(outcomes[2].action, EnforcementAction::Unhandled));
+
+        let state = runtime.state_vector();
+        assert_eq!(state.finance, trusted.state_vector_compat().finance);
+        assert_ne!(state.time, trusted.state_vector_compat().time);
+
+        // Only the unhealed dimensions still violate their policies
+        let outcomes = enforcer.enforce(&mut runtime, &trusted).unwrap();
+        assert_eq!(outcomes.iter().map(|o| o.dimension).collect::<Vec<_>>(), vec![1, 2]);
+    }
+
+    #[test]
+    fn test_custom_handlers_choose_actions() {
+        let (mut runtime, trusted) = drifted(InMemoryPersistence::new());
+        let mut registry = CustomRegistry::new();
+        registry
+            .register_recovery_handler(1, |violation: &CustomViolation<'_>| {
+                assert_eq!(violation.divergence.counter_delta(), 1);
+                Ok(CustomRecovery::Apply(RecoveryStrategy::Quarantine))
+            })
+            .unwrap();
+        registry
+            .register_recovery_handler(2, |violation: &CustomViolation<'_>| {
+                Ok(CustomRecovery::Handled(format!("paged operator for {}", violation.policy.name)))
+            })
+            .unwrap();
+        let enforcer = policies([
+            RecoveryStrategy::Custom(1),
+            RecoveryStrategy::Custom(2),
+            RecoveryStrategy::Custom(3),
+        ])
+        .with_registry(registry);
+
+        let outcomes = enforcer.enforce(&mut runtime, &trusted).unwrap();
+        assert!(matches!(outcomes[0].action, EnforcementAction::Quarantined { already: false }));
+        assert!(matches!(
+            &outcomes[1].action,
+            EnforcementAction::Custom { id: 2, note } if note == "paged operator for time"
+        ));
+        assert!(matches!(outcomes[2].action, EnforcementAction::Unhandled));
+        assert_eq!(outcomes[0].strategy, RecoveryStrategy::Custom(1));
+        assert_eq!(runtime.quarantined(), vec![0]);
+    }
+
+    #[test]
+    fn test_custom_handler_can_request_full_recovery() {
+        let (mut runtime, trusted) = drifted(InMemoryPersistence::new());
+        let mut registry = CustomRegistry::new();
+        registry
+            .register_recovery_handler(5, |_: &CustomViolation<'_>| {
+                Ok(CustomRecovery::Apply(RecoveryStrategy::FullRecovery))
+            })
+            .unwrap();
+        let enforcer = policies([
+            RecoveryStrategy::Quarantine,
+            RecoveryStrategy::MonitorOnly,
+            RecoveryStrategy::Custom(5),
+        ])
+        .with_registry(registry);
+
+        let outcomes = enforcer.enforce(&mut runtime, &trusted).unwrap();
+        assert!(matches!(outcomes[0].action, EnforcementAction::SupersededByFullRecovery));
+        assert!(matches!(outcomes[2].action, EnforcementAction::FullRecovery(_)));
+        assert_eq!(runtime.state_vector(), trusted.state_vector_compat());
+        assert!(runtime.quarantined().is_empty());
+    }
+
+    #[test]
+    fn test_escalation_survives_restarts() {
+        use crate::policy::{EscalationLadder, EscalationStep};
+
+        let ladder = EscalationLadder::new(
+            vec![
+                EscalationStep { after: 1, strategy: RecoveryStrategy::MonitorOnly },
+                EscalationStep { after: 2, strategy: RecoveryStrategy::Quarantine },
+                EscalationStep { after: 3, strategy: RecoveryStrategy::FullRecovery },
+            ],
+            3600,
+            3600,
+        )
+        .unwrap();
+        let mut set = PolicySet::new();
+        set.add_policy(DimensionPolicy::new("finance").with_threshold(0).with_escalation(ladder));
+        let enforcer = PolicyEnforcer::new(set);
+
+        let persistence = InMemoryPersistence::new();
+        let (mut runtime, trusted) = drifted(persistence.clone());
+        runtime.save().unwrap();
+
+        let outcomes = enforcer.enforce_at(&mut runtime, &trusted, 1_000).unwrap();
+        assert!(matches!(outcomes[0].action, EnforcementAction::Monitored));
+        assert_eq!(outcomes[0].escalation, Some(0));
+        assert_eq!(outcomes[0].strategy, RecoveryStrategy::MonitorOnly);
+
+        let mut runtime = DeviceRuntime::load_or_create([3u8; 32], persistence.clone()).unwrap();
+        assert_eq!(runtime.escalation(0).unwrap().recent, vec![1_000]);
+        let outcomes = enforcer.enforce_at(&mut runtime, &trusted, 1_500).unwrap();
+        assert!(matches!(outcomes[0].action, EnforcementAction::Quarantined { already: false }));
+        assert_eq!(outcomes[0].escalation, Some(1));
+
+        let mut runtime = DeviceRuntime::load_or_create([3u8; 32], persistence.clone()).unwrap();
+        let outcomes = enforcer.enforce_at(&mut runtime, &trusted, 2_000).unwrap();
+        assert!(matches!(outcomes[0].action, EnforcementAction::FullRecovery(_)));
+        assert!(runtime.quarantined().is_empty());
+        assert_eq!(runtime.escalation(0).unwrap().level, 3);
+
+        assert!(runtime.reset_escalation(0).unwrap());
+        let runtime = DeviceRuntime::load_or_create([3u8; 32], persistence).unwrap();
+        assert!(runtime.escalation(0).is_none());
+    }
+
+    #[test]
+    fn test_failed_handler_does_not_escalate() {
+        use crate::policy::{EscalationLadder, EscalationStep};
+        use std::sync::atomic::{AtomicBool, Ordering};
+        use std::sync::Arc;
+
+        let ladder = EscalationLadder::new(
+            vec![
+                EscalationStep { after: 1, strategy: RecoveryStrategy::Custom(7) },
+                EscalationStep { after: 2, strategy: RecoveryStrategy::Quarantine },
+            ],
+            3600,
+            3600,
+        )
+        .unwrap();
+        let mut set = PolicySet::new();
+        set.add_policy(DimensionPolicy::new("finance").with_threshold(0).with_escalation(ladder));
+        let available = Arc::new(AtomicBool::new(false));
+        let mut registry = CustomRegistry::new();
+        let handler_available = available.clone();
+        registry
+            .register_recovery_handler(7, move |_: &CustomViolation<'_>| {
+                if !handler_available.load(Ordering::SeqCst) {
+                    return Err(RuntimeError::InvalidState);
+                }
+                Ok(CustomRecovery::Handled("paged operator".to_string()))
+            })
+            .unwrap();
+        let enforcer = PolicyEnforcer::new(set).with_registry(registry);
+
+        let (mut runtime, trusted) = drifted(InMemoryPersistence::new());
+        assert!(enforcer.enforce_at(&mut runtime, &trusted, 1_000).is_err());
+        assert!(runtime.escalation(0).is_none());
+
+        // The retry is still the first violation
+        available.store(true, Ordering::SeqCst);
+        let outcomes = enforcer.enforce_at(&mut runtime, &trusted, 1_100).unwrap();
+        assert_eq!(outcomes[0].escalation, Some(0));
+        assert!(matches!(outcomes[0].action, EnforcementAction::Custom { id: 7, .. }));
+        assert_eq!(runtime.escalation(0).unwrap().recent, vec![1_100]);
+    }
+
+    #[test]
+    fn test_failed_save_reruns_idempotent_handlers() {
+        use crate::policy::{EscalationLadder, EscalationStep};
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use std::sync::Arc;
+
+        let ladder = EscalationLadder::new(
+            vec![
+                EscalationStep { after: 1, strategy: RecoveryStrategy::Custom(7) },
+                EscalationStep { after: 2, strategy: RecoveryStrategy::Quarantine },
+            ],
+            3600,
+            3600,
+        )
+        .unwrap();
+        let mut set = PolicySet::new();
+        set.add_policy(DimensionPolicy::new("finance").with_threshold(0).with_escalation(ladder));
+        let calls = Arc::new(AtomicUsize::new(0));
+        let mut registry = CustomRegistry::new();
+        let handler_calls = calls.clone();
+        registry
+            .register_recovery_handler(7, move |_: &CustomViolation<'_>| {
+                handler_calls.fetch_add(1, Ordering::SeqCst);
+                Ok(CustomRecovery::Handled("paged operator".to_string()))
+            })
+            .unwrap();
+        let enforcer = PolicyEnforcer::new(set).with_registry(registry);
+
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let state_path = temp_dir.path().join("device.state");
+        let mut runtime = DeviceRuntime::new([3u8; 32], crate::FilePersistence::new(&state_path));
+        let trusted = runtime.state().clone();
+        runtime.record_sale(b"unsanctioned sale").unwrap();
+        runtime.save().unwrap();
+
+        // A directory in place of the temporary file makes saving fail
+        let blocker = crate::FilePersistence::new(&state_path).enforcement_path().with_extension("enforcement.tmp");
+        std::fs::create_dir(&blocker).unwrap();
+        assert!(enforcer.enforce_at(&mut runtime, &trusted, 1_000).is_err());
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+        assert!(runtime.escalation(0).is_none());
+        assert!(runtime.quarantine(1).is_err());
+        assert!(!runtime.is_quarantined(1));
+
+        // The retry runs the handler again and records the violation once
+        std::fs::remove_dir(&blocker).unwrap();
+        let outcomes = enforcer.enforce_at(&mut runtime, &trusted, 1_000).unwrap();
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+        assert!(matches!(outcomes[0].action, EnforcementAction::Custom { id: 7, .. }));
+        let runtime = DeviceRuntime::load_or_create([3u8; 32], crate::FilePersistence::new(&state_path)).unwrap();
+        assert_eq!(runtime.escalation(0).unwrap().recent, vec![1_000]);
+    }
+
+    #[test]
+    fn test_full_recovery_supersedes_other_actions() {
+        let (mut runtime, trusted) = drifted(InMemoryPersistence::new());
+        runtime.quarantine(0).unwrap();
+        let enforcer = policies([
+            RecoveryStrategy::Quarantine,
+            RecoveryStrategy::FullRecovery,
+            RecoveryStrategy::MonitorOnly,
+        ]);
+
+        let outcomes = enforcer.enforce(&mut runtime, &trusted).unwrap();
+        assert!(matches!(outcomes[0].action, EnforcementAction::SupersededByFullRecovery));
+        assert!(matches!(outcomes[1].action, EnforcementAction::FullRecovery(_)));
+        assert!(matches!(outcomes[2].action, EnforcementAction::Monitored));
+
+        assert_eq!(runtime.state_vector(), trusted.state_vector_compat());
+        assert!(runtime.quarantined().is_empty());
+        assert!(enforcer.enforce(&mut runtime, &trusted).unwrap().is_empty());
+    }
+}
@@ -1,11 +1,57 @@
 use crate::{Result, RuntimeError};
+use isa_core::entropy::deterministic_entropy_with_suite;
+use isa_core::{EntropyMode, HashSuite};
 use zeroize::Zeroizing;
 
-pub struct EntropySource;
+/// Source of the entropy inputs mixed into each accumulation.
+///
+/// By default entropy comes from the platform RNG. A deterministic source
+/// derives it from the master seed instead (see `isa_core::entropy`), so a
+/// trusted authority with the same seed and event stream can recompute the
+/// device state.
+pub struct EntropySource {
+    master_seed: Option<Zeroizing<[u8; 32]>>,
+}
 
 impl EntropySource {
     pub fn new() -> Self {
-        Self
+        Self { master_seed: None }
+    }
+
+    /// Derive entropy from `master_seed`, the dimension index and its counter.
+    pub fn deterministic(master_seed: [u8; 32]) -> Self {
+        Self {
+            master_seed: Some(Zeroizing::new(master_seed)),
+        }
+    }
+
+    /// Create a source of the given mode; the seed is only kept in deterministic mode.
+    pub fn with_mode(mode: EntropyMode, master_seed: [u8; 32]) -> Self {
+        match mode {
+            EntropyMode::Random => Self::new(),
+            EntropyMode::Deterministic => Self::deterministic(master_seed),
+        }
+    }
+
+    pub fn mode(&self) -> EntropyMode {
+        match self.master_seed {
+            Some(_) => EntropyMode::Deterministic,
+            None => EntropyMode::Random,
+        }
+    }
+
+    /// Entropy for the event that takes dimension `dimension` to counter `counter`.
+    ///
+    /// Random sources ignore the position and return 32 fresh random bytes.
+    pub fn for_event<H: HashSuite>(&self, dimension: usize, counter: u64) -> Result<Zeroizing<Vec<u8>>> {
+        match &self.master_seed {
+            Some(master_seed) => {
+                let dimension = u32::try_from(dimension).map_err(|_| RuntimeError::InvalidState)?;
+                let entropy = deterministic_entropy_with_suite::<H>(master_seed, dimension, counter);
+                Ok(Zeroizing::new(entropy.to_vec()))
+            }
+            None => self.gather(32),
+        }
     }
 
     pub fn gather(&self, size: usize) -> Result<Zeroizing<Vec<u8>>> {
@@ -52,4 +98,25 @@ mod tests {
         
         assert_ne!(entropy1, entropy2);
     }
+
+    #[test]
+    fn test_deterministic_entropy() {
+        let source = EntropySource::deterministic([1u8; 32]);
+        assert_eq!(source.mode(), EntropyMode::Deterministic);
+
+        let entropy = source.for_event::<isa_core::Blake3Suite>(0, 1).unwrap();
+        assert_eq!(&*entropy, &*source.for_event::<isa_core::Blake3Suite>(0, 1).unwrap());
+        assert_ne!(&*entropy, &*source.for_event::<isa_core::Blake3Suite>(0, 2).unwrap());
+        assert_ne!(&*entropy, &*source.for_event::<isa_core::Blake3Suite>(1, 1).unwrap());
+    }
+
+    #[test]
+    fn test_random_source_ignores_position() {
+        let source = EntropySource::with_mode(EntropyMode::Random, [1u8; 32]);
+        assert_eq!(source.mode(), EntropyMode::Random);
+        assert_ne!(
+            &*source.for_event::<isa_core::Blake3Suite>(0, 1).unwrap(),
+            &*source.for_event::<isa_core::Blake3Suite>(0, 1).unwrap()
+        );
+    }
 }
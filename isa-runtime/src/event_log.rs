@@ -0,0 +1,418 @@
+//! Append-only event log and deterministic replay.
+//!
+//! ## Conformance Classification
+//!
+//! **INFORMATIVE** - The log format and replay procedure are provided for
+//! audit and incident response. Conforming implementations MAY record events
+//! differently.
+//!
+//! Every accumulation performed by `DeviceRuntime` is fully determined by the
+//! axis, event data, entropy and Δt it was given. An [`EventLog`] records those
+//! inputs together with the counter the axis reached, so that [`replay`] can
+//! rebuild the state from the master seed and show whether a persisted state
+//! is the honest result of the logged events.
+//!
+//! ## Record Format
+//!
+//! Records are stored back to back, all integers little-endian:
+//!
+//! | Field          | Size           |
+//! |----------------|----------------|
+//! | axis           | 1              |
+//! | counter        | 8              |
+//! | delta_t        | 8              |
+//! | data length    | 4              |
+//! | data           | data length    |
+//! | entropy length | 4              |
+//! | entropy        | entropy length |
+//!
+//! The log holds entropy in the clear and SHALL be protected like the master seed.
+
+use crate::device::EventAxis;
+use crate::{Result, RuntimeError};
+use isa_core::{HashSuite, IntegrityState};
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+/// Inputs of a single accumulation.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EventRecord {
+    /// Axis the event was accumulated into.
+    pub axis: EventAxis,
+    /// Event bytes.
+    pub data: Vec<u8>,
+    /// Entropy mixed into the accumulation.
+    pub entropy: Vec<u8>,
+    /// Time elapsed since the previous event, in milliseconds.
+    pub delta_t: u64,
+    /// Counter of the axis after the event was accumulated.
+    pub counter: u64,
+}
+
+impl EventRecord {
+    /// Encode this record in the log format.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(25 + self.data.len() + self.entropy.len());
+        bytes.push(self.axis.index() as u8);
+        bytes.extend_from_slice(&self.counter.to_le_bytes());
+        bytes.extend_from_slice(&self.delta_t.to_le_bytes());
+        bytes.extend_from_slice(&(self.data.len() as u32).to_le_bytes());
+        bytes.extend_from_slice(&self.data);
+        bytes.extend_from_slice(&(self.entropy.len() as u32).to_le_bytes());
+        bytes.extend_from_slice(&self.entropy);
+        bytes
+    }
+
+    /// Decode one record from the front of `bytes`.
+    ///
+    /// Returns the record and the number of bytes consumed.
+    pub fn from_bytes(bytes: &[u8]) -> Result<(Self, usize)> {
+        let mut reader = Reader { bytes, offset: 0 };
+
+        let axis = EventAxis::from_index(reader.take(1)?[0] as usize).ok_or(RuntimeError::InvalidState)?;
+        let counter = u64::from_le_bytes(reader.array()?);
+        let delta_t = u64::from_le_bytes(reader.array()?);
+        let data_len = u32::from_le_bytes(reader.array()?) as usize;
+        let data = reader.take(data_len)?.to_vec();
+        let entropy_len = u32::from_le_bytes(reader.array()?) as usize;
+        let entropy = reader.take(entropy_len)?.to_vec();
+
+        Ok((Self { axis, data, entropy, delta_t, counter }, reader.offset))
+    }
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+    offset: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8]> {
+        let end = self.offset.checked_add(len).ok_or(RuntimeError::InvalidState)?;
+        let slice = self.bytes.get(self.offset..end).ok_or(RuntimeError::InvalidState)?;
+        self.offset = end;
+        Ok(slice)
+    }
+
+    fn array<const L: usize>(&mut self) -> Result<[u8; L]> {
+        let mut array = [0u8; L];
+        array.copy_from_slice(self.take(L)?);
+        Ok(array)
+    }
+}
+
+/// Append-only file of [`EventRecord`]s.
+pub struct EventLog {
+    path: PathBuf,
+}
+
+impl EventLog {
+    pub fn new<P: AsRef<Path>>(path: P) -> Self {
+        Self {
+            path: path.as_ref().to_path_buf(),
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Append a record and flush it to disk.
+    pub fn append(&self, record: &EventRecord) -> Result<()> {
+        self.append_all(core::slice::from_ref(record))
+    }
+
+    /// Append records in a single write and flush them to disk.
+    pub fn append_all(&self, records: &[EventRecord]) -> Result<()> {
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent)
+                .map_err(|e| RuntimeError::PersistenceFailed(e.to_string()))?;
+        }
+
+        let mut file = fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .map_err(|e| RuntimeError::PersistenceFailed(e.to_string()))?;
+        let bytes: Vec<u8> = records.iter().flat_map(EventRecord::to_bytes).collect();
+        file.write_all(&bytes)
+            .and_then(|_| file.sync_all())
+            .map_err(|e| RuntimeError::PersistenceFailed(e.to_string()))
+    }
+
+    /// Read all records, oldest first.
+    ///
+    /// A missing log is empty. A truncated or malformed log fails with `InvalidState`.
+    pub fn records(&self) -> Result<Vec<EventRecord>> {
+        let bytes = match fs::read(&self.path) {
+            Ok(bytes) => bytes,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(RuntimeError::PersistenceFailed(e.to_string())),
+        };
+
+        let mut records = Vec::new();
+        let mut offset = 0;
+        while offset < bytes.len() {
+            let (record, consumed) = EventRecord::from_bytes(&bytes[offset..])?;
+            records.push(record);
+            offset += consumed;
+        }
+        Ok(records)
+    }
+}
+
+/// Why replay stopped agreeing with the persisted state.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DivergenceKind {
+    /// The record's counter does not follow the replayed counter of its axis,
+    /// so records are missing, duplicated or reordered.
+    CounterMismatch { expected: u64 },
+    /// The replayed state differs from the persisted state at the persisted counter.
+    StateMismatch,
+    /// The record lies beyond the persisted counter of its axis.
+    NotPersisted,
+    /// The persisted counter of the axis is ahead of the log.
+    MissingRecords { persisted_counter: u64 },
+}
+
+/// First point at which replay and the persisted state disagree.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ReplayDivergence {
+    /// Index of the offending record; equal to the log length when the
+    /// disagreement is only visible after the whole log has been replayed.
+    pub index: usize,
+    pub axis: EventAxis,
+    pub kind: DivergenceKind,
+}
+
+/// Result of replaying an event log.
+#[derive(Debug)]
+pub struct ReplayReport<H: HashSuite = isa_core::Blake3Suite> {
+    /// State rebuilt from the seed and the records replayed before any divergence.
+    pub state: IntegrityState<3, H>,
+    /// First divergence, or `None` if the log reproduces the persisted state.
+    pub divergence: Option<ReplayDivergence>,
+}
+
+impl<H: HashSuite> ReplayReport<H> {
+    pub fn is_consistent(&self) -> bool {
+        self.divergence.is_none()
+    }
+}
+
+/// Rebuild a state from `master_seed` and `records` and compare it with `persisted`.
+///
+/// Each axis starts in the accumulation mode of the persisted state. An axis is
+/// compared with the persisted state when its replayed counter reaches the
+/// persisted counter, so a `StateMismatch` names the record that completed the
+/// mismatching axis. States that were migrated to another hash suite or healed
+/// with a convergence constant cannot be reproduced from the seed and report a
+/// `StateMismatch`.
+pub fn replay<H: HashSuite>(
+    master_seed: [u8; 32],
+    records: &[EventRecord],
+    persisted: &IntegrityState<3, H>,
+) -> ReplayReport<H> {
+    let mut state = IntegrityState::<3, H>::from_master_seed_with_suite(master_seed);
+    for (index, stored) in persisted.dimensions().iter().enumerate() {
+        if let Some(dimension) = state.dimension_mut(index) {
+            dimension.set_mode(stored.mode());
+        }
+    }
+
+    let divergence = replay_into(&mut state, records, persisted);
+    ReplayReport { state, divergence }
+}
+
+fn replay_into<H: HashSuite>(
+    state: &mut IntegrityState<3, H>,
+    records: &[EventRecord],
+    persisted: &IntegrityState<3, H>,
+) -> Option<ReplayDivergence> {
+    let diverged = |index, axis, kind| Some(ReplayDivergence { index, axis, kind });
+
+    for (index, record) in records.iter().enumerate() {
+        let axis = record.axis;
+        let stored = &persisted.dimensions()[axis.index()];
+        let dimension = state
+            .dimension_mut(axis.index())
+            .expect("every axis indexes a dimension of a three-axis state");
+
+        let expected = dimension.counter().wrapping_add(1);
+        if record.counter != expected {
+            return diverged(index, axis, DivergenceKind::CounterMismatch { expected });
+        }
+        if dimension.counter() >= stored.counter() {
+            return diverged(index, axis, DivergenceKind::NotPersisted);
+        }
+
+        dimension.accumulate(&record.data, &record.entropy, record.delta_t);
+        if dimension.counter() == stored.counter() && dimension.state() != stored.state() {
+            return diverged(index, axis, DivergenceKind::StateMismatch);
+        }
+    }
+
+    for axis in [EventAxis::Finance, EventAxis::Time, EventAxis::Hardware] {
+        let replayed = &state.dimensions()[axis.index()];
+        let stored = &persisted.dimensions()[axis.index()];
+
+        if replayed.counter() < stored.counter() {
+            let kind = DivergenceKind::MissingRecords { persisted_counter: stored.counter() };
+            return diverged(records.len(), axis, kind);
+        }
+        if replayed.state() != stored.state() {
+            return diverged(records.len(), axis, DivergenceKind::StateMismatch);
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use isa_core::{AccumulationMode, MultiAxisState};
+    use tempfile::TempDir;
+
+    fn record(axis: EventAxis, data: &[u8], counter: u64) -> EventRecord {
+        EventRecord {
+            axis,
+            data: data.to_vec(),
+            entropy: vec![counter as u8; 32],
+            delta_t: 10,
+            counter,
+        }
+    }
+
+    fn apply(state: &mut MultiAxisState, records: &[EventRecord]) {
+        for r in records {
+            state.dimension_mut(r.axis.index()).unwrap().accumulate(&r.data, &r.entropy, r.delta_t);
+        }
+    }
+
+    fn honest_log() -> Vec<EventRecord> {
+        vec![
+            record(EventAxis::Finance, b"sale:1", 1),
+            record(EventAxis::Time, b"tick", 1),
+            record(EventAxis::Finance, b"sale:2", 2),
+            record(EventAxis::Hardware, b"hw", 1),
+        ]
+    }
+
+    #[test]
+    fn test_record_roundtrip() {
+        let r = record(EventAxis::Hardware, b"event", 7);
+        let bytes = r.to_bytes();
+        assert_eq!(EventRecord::from_bytes(&bytes).unwrap(), (r, bytes.len()));
+        assert!(EventRecord::from_bytes(&bytes[..bytes.len() - 1]).is_err());
+    }
+
+    #[test]
+    fn test_log_append_and_read() {
+        let temp_dir = TempDir::new().unwrap();
+        let log = EventLog::new(temp_dir.path().join("nested/events.log"));
+        assert!(log.records().unwrap().is_empty());
+
+        for r in honest_log() {
+            log.append(&r).unwrap();
+        }
+        assert_eq!(log.records().unwrap(), honest_log());
+    }
+
+    #[test]
+    fn test_log_rejects_truncation() {
+        let temp_dir = TempDir::new().unwrap();
+        let log = EventLog::new(temp_dir.path().join("events.log"));
+        log.append(&record(EventAxis::Finance, b"sale:1", 1)).unwrap();
+
+        let bytes = fs::read(log.path()).unwrap();
+        fs::write(log.path(), &bytes[..bytes.len() - 3]).unwrap();
+        assert!(matches!(log.records(), Err(RuntimeError::InvalidState)));
+    }
+
+    #[test]
+    fn test_replay_reproduces_honest_state() {
+        let mut persisted = MultiAxisState::from_master_seed([1u8; 32]);
+        apply(&mut persisted, &honest_log());
+
+        let report = replay([1u8; 32], &honest_log(), &persisted);
+        assert!(report.is_consistent());
+        assert_eq!(report.state.state_vector(), persisted.state_vector());
+    }
+
+    #[test]
+    fn test_replay_uses_persisted_modes() {
+        let mut persisted = MultiAxisState::from_master_seed([1u8; 32])
+            .with_accumulation_mode(AccumulationMode::Additive);
+        apply(&mut persisted, &honest_log());
+
+        assert!(replay([1u8; 32], &honest_log(), &persisted).is_consistent());
+    }
+
+    #[test]
+    fn test_replay_finds_altered_event() {
+        let mut persisted = MultiAxisState::from_master_seed([1u8; 32]);
+        apply(&mut persisted, &honest_log());
+
+        let mut log = honest_log();
+        log[2].data = b"sale:9".to_vec();
+        let report = replay([1u8; 32], &log, &persisted);
+        assert_eq!(report.divergence, Some(ReplayDivergence {
+            index: 2,
+            axis: EventAxis::Finance,
+            kind: DivergenceKind::StateMismatch,
+        }));
+    }
+
+    #[test]
+    fn test_replay_finds_missing_record() {
+        let mut persisted = MultiAxisState::from_master_seed([1u8; 32]);
+        apply(&mut persisted, &honest_log());
+
+        let mut log = honest_log();
+        log.remove(0);
+        let report = replay([1u8; 32], &log, &persisted);
+        assert_eq!(report.divergence, Some(ReplayDivergence {
+            index: 1,
+            axis: EventAxis::Finance,
+            kind: DivergenceKind::CounterMismatch { expected: 1 },
+        }));
+    }
+
+    #[test]
+    fn test_replay_finds_rolled_back_state() {
+        let log = honest_log();
+        let mut persisted = MultiAxisState::from_master_seed([1u8; 32]);
+        apply(&mut persisted, &log[..2]);
+
+        let report = replay([1u8; 32], &log, &persisted);
+        assert_eq!(report.divergence, Some(ReplayDivergence {
+            index: 2,
+            axis: EventAxis::Finance,
+            kind: DivergenceKind::NotPersisted,
+        }));
+
+        let mut ahead = MultiAxisState::from_master_seed([1u8; 32]);
+        apply(&mut ahead, &log);
+        let report = replay([1u8; 32], &log[..2], &ahead);
+        assert_eq!(report.divergence, Some(ReplayDivergence {
+            index: 2,
+            axis: EventAxis::Finance,
+            kind: DivergenceKind::MissingRecords { persisted_counter: 2 },
+        }));
+    }
+
+    #[test]
+    fn test_replay_detects_wrong_seed() {
+        let mut persisted = MultiAxisState::from_master_seed([1u8; 32]);
+        apply(&mut persisted, &honest_log());
+
+        // Finance is first compared at its second record; time at its only one
+        let report = replay([2u8; 32], &honest_log(), &persisted);
+        assert_eq!(report.divergence, Some(ReplayDivergence {
+            index: 1,
+            axis: EventAxis::Time,
+            kind: DivergenceKind::StateMismatch,
+        }));
+    }
+}
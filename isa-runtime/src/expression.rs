@@ -0,0 +1,814 @@
+//! Constraint expression language.
+//!
+//! ## Conformance Classification
+//!
+//! **OPTIONAL** - Expressions are one way to write `crate::constraints`
+//! rules. They are NOT required for conformance with the MA-ISA core
+//! specification.
+//!
+//! An [`Expression`] is a boolean rule over the divergences of a state, for
+//! example `d0 > 2 * d1 && d3 < 5000 || lag(d2) > 0`. It is true when the
+//! constraint is violated. Expressions are parsed and type-checked once and
+//! evaluated with unsigned 256-bit integer arithmetic only, so every platform
+//! reaches the same decision.
+//!
+//! ## Syntax
+//!
+//! | Form                           | Type | Meaning                                             |
+//! |--------------------------------|------|-----------------------------------------------------|
+//! | `d<index>`                     | int  | divergence of a dimension                           |
+//! | `label`, `"Label with spaces"` | int  | divergence of a dimension by label                  |
+//! | `123`, `0xff`                  | int  | literal, up to 256 bits                             |
+//! | `a + b`, `a - b`               | int  | saturating at 0 and 2^256 - 1                       |
+//! | `a * b`, `a / b`               | int  | saturating; division rounds down, `a / 0` saturates |
+//! | `max(a, b, ...)`, `min(..)`    | int  | largest / smallest argument                         |
+//! | `lag(d)`                       | int  | events the dimension is behind the reference        |
+//! | `<` `<=` `>` `>=` `==` `!=`    | bool | comparison of two ints                              |
+//! | `!`, `&&`, `\|\|`              | bool | negation, conjunction, disjunction                  |
+//! | `true`, `false`                | bool | literal                                             |
+//!
+//! Precedence, loosest first: `||`, `&&`, `!`, comparisons (which do not
+//! chain), `+ -`, `* /`. Parentheses group. `lag` needs event counters and is
+//! 0 when an expression is evaluated against divergence values alone.
+
+use crate::{Result, RuntimeError};
+use isa_core::{DivergenceReport, DivergenceVector, Divergence256};
+use std::fmt;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+/// Deepest nesting of parentheses, operators and calls accepted by the parser.
+pub const MAX_EXPRESSION_DEPTH: usize = 64;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum BinaryOp {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    Eq,
+    Ne,
+    And,
+    Or,
+}
+
+impl BinaryOp {
+    fn symbol(self) -> &'static str {
+        match self {
+            BinaryOp::Add => "+",
+            BinaryOp::Sub => "-",
+            BinaryOp::Mul => "*",
+            BinaryOp::Div => "/",
+            BinaryOp::Lt => "<",
+            BinaryOp::Le => "<=",
+            BinaryOp::Gt => ">",
+            BinaryOp::Ge => ">=",
+            BinaryOp::Eq => "==",
+            BinaryOp::Ne => "!=",
+            BinaryOp::And => "&&",
+            BinaryOp::Or => "||",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Function {
+    Max,
+    Min,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Node {
+    Number(Divergence256),
+    Bool(bool),
+    Dimension(usize),
+    Lag(usize),
+    Not(Box<Node>),
+    Binary(BinaryOp, Box<Node>, Box<Node>),
+    Call(Function, Vec<Node>),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Type {
+    Int,
+    Bool,
+}
+
+impl Type {
+    fn name(self) -> &'static str {
+        match self {
+            Type::Int => "an integer",
+            Type::Bool => "a boolean",
+        }
+    }
+}
+
+/// A parsed and type-checked constraint expression.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Expression {
+    root: Node,
+}
+
+impl Expression {
+    /// Parse an expression that refers to dimensions by index only.
+    pub fn parse(source: &str) -> Result<Self> {
+        Self::parse_with_labels(source, &[])
+    }
+
+    /// Parse an expression, resolving dimension labels through `labels`.
+    ///
+    /// Fails with `InvalidConfig` on a syntax error, an unknown label or
+    /// function, or an operand of the wrong type; the whole expression must
+    /// be boolean.
+    pub fn parse_with_labels(source: &str, labels: &[(&str, usize)]) -> Result<Self> {
+        let tokens = tokenize(source)?;
+        let mut parser = Parser { tokens, position: 0, labels, depth: 0 };
+        let (root, ty) = parser.or()?;
+        if let Some(token) = parser.tokens.get(parser.position) {
+            return Err(error(token.column, "unexpected input after the expression"));
+        }
+        if ty != Type::Bool {
+            return Err(error(1, "the expression must be a boolean condition"));
+        }
+        Ok(Self { root })
+    }
+
+    /// Dimensions the expression refers to, in ascending order.
+    pub fn dimensions(&self) -> Vec<usize> {
+        let mut dimensions = Vec::new();
+        collect_dimensions(&self.root, &mut dimensions);
+        dimensions.sort_unstable();
+        dimensions.dedup();
+        dimensions
+    }
+
+    /// Evaluate against divergence values; `lag` is 0. Dimensions beyond
+    /// `divergences` have zero divergence.
+    pub fn evaluate(&self, divergences: &[Divergence256]) -> bool {
+        Inputs::Values(divergences).boolean(&self.root)
+    }
+
+    /// Evaluate against a divergence vector; `lag` is 0.
+    pub fn evaluate_vector<const N: usize>(&self, vector: &DivergenceVector<N>) -> bool {
+        self.evaluate(&vector.values)
+    }
+
+    /// Evaluate against a divergence report, which also provides `lag`.
+    pub fn evaluate_report(&self, report: &DivergenceReport) -> bool {
+        Inputs::Report(report).boolean(&self.root)
+    }
+}
+
+impl fmt::Display for Expression {
+    /// Canonical source: dimensions by index, every operation parenthesised.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write_node(f, &self.root, true)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl Serialize for Expression {
+    fn serialize<S: Serializer>(&self, serializer: S) -> core::result::Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> Deserialize<'de> for Expression {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> core::result::Result<Self, D::Error> {
+        let source = String::deserialize(deserializer)?;
+        Expression::parse(&source).map_err(serde::de::Error::custom)
+    }
+}
+
+fn error(column: usize, message: &str) -> RuntimeError {
+    RuntimeError::InvalidConfig(format!("expression error at column {}: {}", column, message))
+}
+
+fn collect_dimensions(node: &Node, dimensions: &mut Vec<usize>) {
+    match node {
+        Node::Number(_) | Node::Bool(_) => {}
+        Node::Dimension(index) | Node::Lag(index) => dimensions.push(*index),
+        Node::Not(inner) => collect_dimensions(inner, dimensions),
+        Node::Binary(_, left, right) => {
+            collect_dimensions(left, dimensions);
+            collect_dimensions(right, dimensions);
+        }
+        Node::Call(_, arguments) => {
+            for argument in arguments {
+                collect_dimensions(argument, dimensions);
+            }
+        }
+    }
+}
+
+fn write_node(f: &mut fmt::Formatter<'_>, node: &Node, top: bool) -> fmt::Result {
+    match node {
+        Node::Number(value) if value.bits() <= 128 => write!(f, "{}", value.saturating_to_u128()),
+        Node::Number(value) => write!(f, "{:#x}", value),
+        Node::Bool(value) => write!(f, "{}", value),
+        Node::Dimension(index) => write!(f, "d{}", index),
+        Node::Lag(index) => write!(f, "lag(d{})", index),
+        Node::Not(inner) => {
+            write!(f, "!")?;
+            write_node(f, inner, false)
+        }
+        Node::Binary(op, left, right) => {
+            if !top {
+                write!(f, "(")?;
+            }
+            write_node(f, left, false)?;
+            write!(f, " {} ", op.symbol())?;
+            write_node(f, right, false)?;
+            if !top {
+                write!(f, ")")?;
+            }
+            Ok(())
+        }
+        Node::Call(function, arguments) => {
+            let name = match function {
+                Function::Max => "max",
+                Function::Min => "min",
+            };
+            write!(f, "{}(", name)?;
+            for (i, argument) in arguments.iter().enumerate() {
+                if i > 0 {
+                    write!(f, ", ")?;
+                }
+                write_node(f, argument, true)?;
+            }
+            write!(f, ")")
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum TokenKind {
+    Number(Divergence256),
+    Identifier(String),
+    Label(String),
+    Symbol(&'static str),
+}
+
+#[derive(Debug, Clone)]
+struct Token {
+    kind: TokenKind,
+    /// 1-based character column in the source.
+    column: usize,
+}
+
+const SYMBOLS: [&str; 17] = [
+    "&&", "||", "<=", ">=", "==", "!=", "<", ">", "!", "+", "-", "*", "/", "(", ")", ",", "=",
+];
+
+fn tokenize(source: &str) -> Result<Vec<Token>> {
+    let chars: Vec<char> = source.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        let column = i + 1;
+        if c.is_whitespace() {
+            i += 1;
+        } else if c.is_ascii_digit() {
+            let start = i;
+            while i < chars.len() && (chars[i].is_ascii_alphanumeric() || chars[i] == '_') {
+                i += 1;
+            }
+            let literal: String = chars[start..i].iter().filter(|&&c| c != '_').collect();
+            tokens.push(Token { kind: TokenKind::Number(parse_number(&literal, column)?), column });
+        } else if c.is_ascii_alphabetic() || c == '_' {
+            let start = i;
+            while i < chars.len() && (chars[i].is_ascii_alphanumeric() || chars[i] == '_') {
+                i += 1;
+            }
+            tokens.push(Token { kind: TokenKind::Identifier(chars[start..i].iter().collect()), column });
+        } else if c == '"' {
+            let start = i + 1;
+            i = start;
+            while i < chars.len() && chars[i] != '"' {
+                i += 1;
+            }
+            if i == chars.len() {
+                return Err(error(column, "unterminated label"));
+            }
+            tokens.push(Token { kind: TokenKind::Label(chars[start..i].iter().collect()), column });
+            i += 1;
+        } else {
+            let rest: String = chars[i..chars.len().min(i + 2)].iter().collect();
+            let symbol = SYMBOLS
+                .iter()
+                .find(|symbol| rest.starts_with(**symbol))
+                .ok_or_else(|| error(column, &format!("unexpected character '{}'", c)))?;
+            if *symbol == "=" {
+                return Err(error(column, "use '==' to compare"));
+            }
+            tokens.push(Token { kind: TokenKind::Symbol(symbol), column });
+            i += symbol.len();
+        }
+    }
+    Ok(tokens)
+}
+
+fn parse_number(literal: &str, column: usize) -> Result<Divergence256> {
+    let invalid = || error(column, &format!("invalid number '{}'", literal));
+    if let Some(hex) = literal.strip_prefix("0x") {
+        if hex.is_empty() {
+            return Err(invalid());
+        }
+        return Divergence256::from_hex(hex).ok_or_else(invalid);
+    }
+    if !literal.bytes().all(|b| b.is_ascii_digit()) {
+        return Err(invalid());
+    }
+    let too_large = || error(column, "number does not fit in 256 bits");
+    let limit = saturating_div(Divergence256::MAX, Divergence256::from(10u64));
+    let mut value = Divergence256::ZERO;
+    for digit in literal.bytes() {
+        if value > limit {
+            return Err(too_large());
+        }
+        value = value
+            .saturating_mul(10)
+            .checked_add(Divergence256::from((digit - b'0') as u64))
+            .ok_or_else(too_large)?;
+    }
+    Ok(value)
+}
+
+struct Parser<'a> {
+    tokens: Vec<Token>,
+    position: usize,
+    labels: &'a [(&'a str, usize)],
+    depth: usize,
+}
+
+impl Parser<'_> {
+    fn peek(&self) -> Option<&TokenKind> {
+        self.tokens.get(self.position).map(|token| &token.kind)
+    }
+
+    /// Column of the next token, or just past the end of the input.
+    fn column(&self) -> usize {
+        match self.tokens.get(self.position) {
+            Some(token) => token.column,
+            None => self.tokens.last().map_or(1, |token| token.column + 1),
+        }
+    }
+
+    fn at(&self, symbol: &str) -> bool {
+        matches!(self.peek(), Some(TokenKind::Symbol(next)) if *next == symbol)
+    }
+
+    fn eat(&mut self, symbol: &str) -> bool {
+        if self.at(symbol) {
+            self.position += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, symbol: &str) -> Result<()> {
+        if self.eat(symbol) {
+            Ok(())
+        } else {
+            Err(error(self.column(), &format!("expected '{}'", symbol)))
+        }
+    }
+
+    fn descend(&mut self) -> Result<()> {
+        self.depth += 1;
+        if self.depth > MAX_EXPRESSION_DEPTH {
+            return Err(error(self.column(), "expression is nested too deeply"));
+        }
+        Ok(())
+    }
+
+    fn operand(&self, ty: Type, expected: Type, column: usize, op: &str) -> Result<()> {
+        if ty != expected {
+            return Err(error(
+                column,
+                &format!("'{}' expects {}, found {}", op, expected.name(), ty.name()),
+            ));
+        }
+        Ok(())
+    }
+
+    fn or(&mut self) -> Result<(Node, Type)> {
+        self.logical("||", BinaryOp::Or, Self::and)
+    }
+
+    fn and(&mut self) -> Result<(Node, Type)> {
+        self.logical("&&", BinaryOp::And, Self::not)
+    }
+
+    fn logical(
+        &mut self,
+        symbol: &str,
+        op: BinaryOp,
+        next: fn(&mut Self) -> Result<(Node, Type)>,
+    ) -> Result<(Node, Type)> {
+        let column = self.column();
+        let (mut node, ty) = next(self)?;
+        let mut chained = false;
+        while self.eat(symbol) {
+            if !chained {
+                self.operand(ty, Type::Bool, column, symbol)?;
+                chained = true;
+            }
+            let column = self.column();
+            let (right, right_ty) = next(self)?;
+            self.operand(right_ty, Type::Bool, column, symbol)?;
+            node = Node::Binary(op, Box::new(node), Box::new(right));
+        }
+        Ok((node, if chained { Type::Bool } else { ty }))
+    }
+
+    fn not(&mut self) -> Result<(Node, Type)> {
+        if self.eat("!") {
+            self.descend()?;
+            let column = self.column();
+            let (inner, ty) = self.not()?;
+            self.operand(ty, Type::Bool, column, "!")?;
+            self.depth -= 1;
+            return Ok((Node::Not(Box::new(inner)), Type::Bool));
+        }
+        self.comparison()
+    }
+
+    fn comparison(&mut self) -> Result<(Node, Type)> {
+        let column = self.column();
+        let (left, ty) = self.sum()?;
+        let op = match self.peek() {
+            Some(TokenKind::Symbol("<")) => BinaryOp::Lt,
+            Some(TokenKind::Symbol("<=")) => BinaryOp::Le,
+            Some(TokenKind::Symbol(">")) => BinaryOp::Gt,
+            Some(TokenKind::Symbol(">=")) => BinaryOp::Ge,
+            Some(TokenKind::Symbol("==")) => BinaryOp::Eq,
+            Some(TokenKind::Symbol("!=")) => BinaryOp::Ne,
+            _ => return Ok((left, ty)),
+        };
+        self.position += 1;
+        self.operand(ty, Type::Int, column, op.symbol())?;
+        let right_column = self.column();
+        let (right, right_ty) = self.sum()?;
+        self.operand(right_ty, Type::Int, right_column, op.symbol())?;
+        if let Some(TokenKind::Symbol("<" | "<=" | ">" | ">=" | "==" | "!=")) = self.peek() {
+            return Err(error(self.column(), "comparisons do not chain; use '&&'"));
+        }
+        Ok((Node::Binary(op, Box::new(left), Box::new(right)), Type::Bool))
+    }
+
+    fn sum(&mut self) -> Result<(Node, Type)> {
+        self.arithmetic(&[("+", BinaryOp::Add), ("-", BinaryOp::Sub)], Self::product)
+    }
+
+    fn product(&mut self) -> Result<(Node, Type)> {
+        self.arithmetic(&[("*", BinaryOp::Mul), ("/", BinaryOp::Div)], Self::primary)
+    }
+
+    fn arithmetic(
+        &mut self,
+        ops: &[(&str, BinaryOp)],
+        next: fn(&mut Self) -> Result<(Node, Type)>,
+    ) -> Result<(Node, Type)> {
+        let column = self.column();
+        let (mut node, ty) = next(self)?;
+        loop {
+            let Some(&(symbol, op)) = ops.iter().find(|(symbol, _)| self.at(symbol)) else {
+                return Ok((node, ty));
+            };
+            self.position += 1;
+            self.operand(ty, Type::Int, column, symbol)?;
+            let right_column = self.column();
+            let (right, right_ty) = next(self)?;
+            self.operand(right_ty, Type::Int, right_column, symbol)?;
+            node = Node::Binary(op, Box::new(node), Box::new(right));
+        }
+    }
+
+    fn primary(&mut self) -> Result<(Node, Type)> {
+        let column = self.column();
+        let kind = self
+            .peek()
+            .cloned()
+            .ok_or_else(|| error(column, "unexpected end of expression"))?;
+        self.position += 1;
+        match kind {
+            TokenKind::Number(value) => Ok((Node::Number(value), Type::Int)),
+            TokenKind::Label(label) => Ok((Node::Dimension(self.label(&label, column)?), Type::Int)),
+            TokenKind::Symbol("(") => {
+                self.descend()?;
+                let inner = self.or()?;
+                self.expect(")")?;
+                self.depth -= 1;
+                Ok(inner)
+            }
+            TokenKind::Symbol(symbol) => Err(error(column, &format!("unexpected '{}'", symbol))),
+            TokenKind::Identifier(name) => match name.as_str() {
+                "true" => Ok((Node::Bool(true), Type::Bool)),
+                "false" => Ok((Node::Bool(false), Type::Bool)),
+                "max" | "min" | "lag" if self.eat("(") => self.call(&name, column),
+                _ => Ok((Node::Dimension(self.dimension(&name, column)?), Type::Int)),
+            },
+        }
+    }
+
+    fn call(&mut self, name: &str, column: usize) -> Result<(Node, Type)> {
+        self.descend()?;
+        if name == "lag" {
+            let argument_column = self.column();
+            let dimension = match self.peek().cloned() {
+                Some(TokenKind::Identifier(name)) => self.dimension(&name, argument_column)?,
+                Some(TokenKind::Label(label)) => self.label(&label, argument_column)?,
+                _ => return Err(error(argument_column, "'lag' expects a dimension")),
+            };
+            self.position += 1;
+            self.expect(")")?;
+            self.depth -= 1;
+            return Ok((Node::Lag(dimension), Type::Int));
+        }
+
+        let mut arguments = Vec::new();
+        loop {
+            let argument_column = self.column();
+            let (argument, ty) = self.sum()?;
+            self.operand(ty, Type::Int, argument_column, name)?;
+            arguments.push(argument);
+            if !self.eat(",") {
+                break;
+            }
+        }
+        self.expect(")")?;
+        if arguments.len() < 2 {
+            return Err(error(column, &format!("'{}' expects at least two arguments", name)));
+        }
+        self.depth -= 1;
+        let function = if name == "max" { Function::Max } else { Function::Min };
+        Ok((Node::Call(function, arguments), Type::Int))
+    }
+
+    /// Resolve `d<index>` or a bare label.
+    fn dimension(&self, name: &str, column: usize) -> Result<usize> {
+        if let Some(index) = name.strip_prefix('d').filter(|digits| {
+            !digits.is_empty() && digits.bytes().all(|b| b.is_ascii_digit())
+        }) {
+            return index.parse().map_err(|_| error(column, &format!("invalid dimension '{}'", name)));
+        }
+        if matches!(name, "max" | "min" | "lag") {
+            return Err(error(column, &format!("'{}' expects '('", name)));
+        }
+        self.label(name, column)
+    }
+
+    fn label(&self, label: &str, column: usize) -> Result<usize> {
+        self.labels
+            .iter()
+            .find(|(name, _)| *name == label)
+            .map(|&(_, index)| index)
+            .ok_or_else(|| error(column, &format!("unknown dimension '{}'", label)))
+    }
+}
+
+/// Where divergences (and counters) come from during evaluation.
+enum Inputs<'a> {
+    Values(&'a [Divergence256]),
+    Report(&'a DivergenceReport),
+}
+
+impl Inputs<'_> {
+    fn distance(&self, dimension: usize) -> Divergence256 {
+        match self {
+            Inputs::Values(values) => values.get(dimension).copied(),
+            Inputs::Report(report) => report.get(dimension).map(|dim| dim.distance),
+        }
+        .unwrap_or(Divergence256::ZERO)
+    }
+
+    fn lag(&self, dimension: usize) -> Divergence256 {
+        match self {
+            Inputs::Values(_) => Divergence256::ZERO,
+            Inputs::Report(report) => report.get(dimension).map_or(Divergence256::ZERO, |dim| {
+                Divergence256::from(dim.reference_counter.saturating_sub(dim.counter))
+            }),
+        }
+    }
+
+    fn boolean(&self, node: &Node) -> bool {
+        match node {
+            Node::Bool(value) => *value,
+            Node::Not(inner) => !self.boolean(inner),
+            Node::Binary(BinaryOp::And, left, right) => self.boolean(left) && self.boolean(right),
+            Node::Binary(BinaryOp::Or, left, right) => self.boolean(left) || self.boolean(right),
+            Node::Binary(op, left, right) => {
+                let (left, right) = (self.integer(left), self.integer(right));
+                match op {
+                    BinaryOp::Lt => left < right,
+                    BinaryOp::Le => left <= right,
+                    BinaryOp::Gt => left > right,
+                    BinaryOp::Ge => left >= right,
+                    BinaryOp::Eq => left == right,
+                    BinaryOp::Ne => left != right,
+                    // Arithmetic is rejected by the type checker
+                    _ => false,
+                }
+            }
+            // Integers are rejected by the type checker
+            _ => false,
+        }
+    }
+
+    fn integer(&self, node: &Node) -> Divergence256 {
+        match node {
+            Node::Number(value) => *value,
+            Node::Dimension(dimension) => self.distance(*dimension),
+            Node::Lag(dimension) => self.lag(*dimension),
+            Node::Binary(op, left, right) => {
+                let (left, right) = (self.integer(left), self.integer(right));
+                match op {
+                    BinaryOp::Add => left.saturating_add(right),
+                    BinaryOp::Sub => left.saturating_sub(right),
+                    BinaryOp::Mul => saturating_mul(left, right),
+                    BinaryOp::Div => saturating_div(left, right),
+                    // Booleans are rejected by the type checker
+                    _ => Divergence256::ZERO,
+                }
+            }
+            Node::Call(function, arguments) => {
+                let values = arguments.iter().map(|argument| self.integer(argument));
+                match function {
+                    Function::Max => values.max(),
+                    Function::Min => values.min(),
+                }
+                .unwrap_or(Divergence256::ZERO)
+            }
+            Node::Bool(_) | Node::Not(_) => Divergence256::ZERO,
+        }
+    }
+}
+
+fn limbs(value: Divergence256) -> [u64; 4] {
+    let bytes = value.to_le_bytes();
+    let mut limbs = [0u64; 4];
+    for (limb, chunk) in limbs.iter_mut().zip(bytes.chunks(8)) {
+        let mut word = [0u8; 8];
+        word.copy_from_slice(chunk);
+        *limb = u64::from_le_bytes(word);
+    }
+    limbs
+}
+
+fn from_limbs(limbs: [u64; 4]) -> Divergence256 {
+    let mut bytes = [0u8; 32];
+    for (chunk, limb) in bytes.chunks_mut(8).zip(limbs) {
+        chunk.copy_from_slice(&limb.to_le_bytes());
+    }
+    Divergence256::from_le_bytes(bytes)
+}
+
+/// Product of two 256-bit values, clamped to `Divergence256::MAX`.
+fn saturating_mul(a: Divergence256, b: Divergence256) -> Divergence256 {
+    let (a, b) = (limbs(a), limbs(b));
+    let mut product = [0u64; 8];
+    for (i, &x) in a.iter().enumerate() {
+        let mut carry = 0u128;
+        for (j, &y) in b.iter().enumerate() {
+            let wide = x as u128 * y as u128 + product[i + j] as u128 + carry;
+            product[i + j] = wide as u64;
+            carry = wide >> 64;
+        }
+        product[i + 4] = carry as u64;
+    }
+    if product[4..].iter().any(|&limb| limb != 0) {
+        return Divergence256::MAX;
+    }
+    from_limbs([product[0], product[1], product[2], product[3]])
+}
+
+/// Quotient rounded down; division by zero gives `Divergence256::MAX`.
+fn saturating_div(a: Divergence256, b: Divergence256) -> Divergence256 {
+    if b.is_zero() {
+        return Divergence256::MAX;
+    }
+    let dividend = limbs(a);
+    let mut quotient = [0u64; 4];
+    let mut remainder = Divergence256::ZERO;
+    for bit in (0..256).rev() {
+        // remainder = remainder * 2 + bit; `overflow` is the bit shifted out
+        let overflow = remainder.bits() == 256;
+        remainder = remainder.wrapping_add(remainder);
+        if dividend[bit / 64] >> (bit % 64) & 1 == 1 {
+            remainder = remainder.wrapping_add(Divergence256::from(1u64));
+        }
+        if overflow || remainder >= b {
+            remainder = remainder.wrapping_sub(b);
+            quotient[bit / 64] |= 1 << (bit % 64);
+        }
+    }
+    from_limbs(quotient)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use isa_core::DimensionDivergence;
+
+    fn values(values: &[u64]) -> Vec<Divergence256> {
+        values.iter().map(|&value| Divergence256::from(value)).collect()
+    }
+
+    #[test]
+    fn test_precedence_and_evaluation() {
+        let rule = Expression::parse("d0 > 2 * d1 && d3 < 5000 || d2 >= 100").unwrap();
+        assert_eq!(rule.to_string(), "((d0 > (2 * d1)) && (d3 < 5000)) || (d2 >= 100)");
+        assert_eq!(rule.dimensions(), vec![0, 1, 2, 3]);
+
+        assert!(rule.evaluate(&values(&[21, 10, 0, 4999])));
+        assert!(!rule.evaluate(&values(&[20, 10, 0, 4999])));
+        assert!(!rule.evaluate(&values(&[21, 10, 0, 5000])));
+        assert!(rule.evaluate(&values(&[0, 0, 100, 0])));
+
+        let rule = Expression::parse("!(d0 - d1 > 5) && max(d0, d1, 7) / 2 == 3 && min(d0, d1) == 0").unwrap();
+        assert!(rule.evaluate(&values(&[0, 3])));
+        assert!(!rule.evaluate(&values(&[0, 8])));
+
+        // The canonical form parses back to the same expression
+        assert_eq!(Expression::parse(&rule.to_string()).unwrap(), rule);
+    }
+
+    #[test]
+    fn test_labels_and_lag() {
+        let labels = [("finance", 0), ("Hardware Clock", 2)];
+        let rule = Expression::parse_with_labels("finance > 0 || lag(\"Hardware Clock\") > 1", &labels).unwrap();
+        assert_eq!(rule.to_string(), "(d0 > 0) || (lag(d2) > 1)");
+
+        let dimension = |counter, reference_counter| DimensionDivergence {
+            distance: Divergence256::ZERO,
+            counter,
+            reference_counter,
+        };
+        let report = DivergenceReport::new(vec![dimension(5, 5), dimension(5, 5), dimension(3, 5)]);
+        assert!(rule.evaluate_report(&report));
+        let report = DivergenceReport::new(vec![dimension(5, 5), dimension(5, 5), dimension(9, 5)]);
+        assert!(!rule.evaluate_report(&report));
+        // Without counters the dimension is not lagging
+        assert!(!rule.evaluate(&values(&[0, 0, 0])));
+
+        let vector = DivergenceVector { values: [Divergence256::from(1u64), Divergence256::ZERO] };
+        assert!(rule.evaluate_vector(&vector));
+    }
+
+    #[test]
+    fn test_rejected_expressions() {
+        for source in [
+            "d0 + 1",             // not a condition
+            "d0 && d1 > 0",       // integer operand of '&&'
+            "(d0 > 1) + 1 > 0",   // boolean operand of '+'
+            "d0 < d1 < d2",       // chained comparison
+            "d0 = 1",             // assignment
+            "speed > 1",          // unknown label
+            "lag(1) > 0",         // lag of a non-dimension
+            "max(d0) > 1",        // too few arguments
+            "avg(d0, d1) > 1",    // unknown function
+            "d0 > 1 d1",          // trailing input
+            "d0 >",               // missing operand
+            "\"open > 1",         // unterminated label
+            "d0 > 0x",            // empty hex literal
+        ] {
+            assert!(
+                matches!(Expression::parse(source), Err(RuntimeError::InvalidConfig(_))),
+                "accepted {}",
+                source
+            );
+        }
+
+        let nested = |depth: usize| format!("{}d0 > 1{}", "(".repeat(depth), ")".repeat(depth));
+        assert!(Expression::parse(&nested(MAX_EXPRESSION_DEPTH)).is_ok());
+        assert!(Expression::parse(&nested(MAX_EXPRESSION_DEPTH + 1)).is_err());
+    }
+
+    #[test]
+    fn test_wide_arithmetic() {
+        let big = Divergence256::MAX.saturating_sub(Divergence256::from(1u64));
+        assert_eq!(saturating_mul(big, Divergence256::from(2u64)), Divergence256::MAX);
+        assert_eq!(
+            saturating_div(big, Divergence256::from(2u64)),
+            from_limbs([u64::MAX, u64::MAX, u64::MAX, u64::MAX >> 1])
+        );
+        assert_eq!(saturating_div(Divergence256::MAX, big), Divergence256::from(1u64));
+        assert_eq!(saturating_div(Divergence256::from(7u64), Divergence256::ZERO), Divergence256::MAX);
+
+        let a = Divergence256::from(u128::MAX);
+        let square = saturating_mul(a, a);
+        assert_eq!(saturating_div(square, a), a);
+
+        let rule = Expression::parse("d0 * d0 / d0 == d0 && 0xff == 255").unwrap();
+        assert!(rule.evaluate(&[a]));
+    }
+}
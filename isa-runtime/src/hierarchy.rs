@@ -199,13 +199,13 @@ impl DimensionHierarchy {
                 total_weight += weight;
                 
                 // Convert first 32 bytes to u64 chunks and accumulate
-                for i in 0..4 {
+                for (i, sum) in weighted_sum.iter_mut().enumerate() {
                     let offset = i * 8;
                     let value = u64::from_le_bytes([
                         div[offset], div[offset+1], div[offset+2], div[offset+3],
                         div[offset+4], div[offset+5], div[offset+6], div[offset+7],
                     ]);
-                    weighted_sum[i] = weighted_sum[i].saturating_add(
+                    *sum = sum.saturating_add(
                         ((value as f64) * (weight as f64)) as u64
                     );
                 }
@@ -218,8 +218,8 @@ impl DimensionHierarchy {
         
         // Convert back to bytes
         let mut result = [0u8; STATE_SIZE];
-        for i in 0..4 {
-            let avg = ((weighted_sum[i] as f64) / (total_weight as f64)) as u64;
+        for (i, sum) in weighted_sum.iter().enumerate() {
+            let avg = ((*sum as f64) / (total_weight as f64)) as u64;
             let bytes = avg.to_le_bytes();
             let offset = i * 8;
             result[offset..offset+8].copy_from_slice(&bytes);
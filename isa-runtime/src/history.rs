@@ -0,0 +1,285 @@
+//! Sliding-window divergence history and integer correlation.
+//!
+//! ## Conformance Classification
+//!
+//! **OPTIONAL** - History is only needed by `ConstraintType::Correlation`
+//! (see `crate::constraints`). It is NOT required for conformance with the
+//! MA-ISA core specification.
+//!
+//! A [`DivergenceHistory`] keeps the per-dimension divergences of the last
+//! `window` evaluations. [`DivergenceHistory::correlation`] computes the
+//! Pearson or Spearman correlation of two dimensions over that window, in
+//! percent (-100 to 100), with integer arithmetic only so that every platform
+//! reaches the same decision.
+//!
+//! Divergences are 256-bit. Pearson correlation is invariant under scaling,
+//! so each series is reduced to the 16 bits below its largest value's most
+//! significant bit; with at most [`MAX_HISTORY_WINDOW`] samples every
+//! intermediate sum then fits in 128 bits. Spearman correlation works on
+//! ranks and uses the full 256-bit ordering; ties get their average rank.
+
+use crate::{Result, RuntimeError};
+use isa_core::Divergence256;
+use std::collections::VecDeque;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// Largest supported history window.
+pub const MAX_HISTORY_WINDOW: usize = 4096;
+
+/// Fewest samples a correlation is computed from.
+pub const MIN_CORRELATION_SAMPLES: usize = 3;
+
+/// Correlation coefficient used by `ConstraintType::Correlation`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum CorrelationMethod {
+    /// Linear correlation of the divergence values.
+    #[default]
+    Pearson,
+    /// Rank correlation: whether the divergences rise and fall together.
+    Spearman,
+}
+
+impl CorrelationMethod {
+    /// Parse `"pearson"` or `"spearman"` (case-insensitive).
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "pearson" => Some(Self::Pearson),
+            "spearman" => Some(Self::Spearman),
+            _ => None,
+        }
+    }
+}
+
+/// Divergences of the last `window` evaluations, oldest first.
+#[derive(Debug, Clone)]
+pub struct DivergenceHistory {
+    window: usize,
+    samples: VecDeque<Vec<Divergence256>>,
+}
+
+impl DivergenceHistory {
+    /// Create an empty history keeping at most `window` samples.
+    ///
+    /// Fails with `InvalidConfig` unless `window` is between 1 and
+    /// [`MAX_HISTORY_WINDOW`].
+    pub fn new(window: usize) -> Result<Self> {
+        if window == 0 || window > MAX_HISTORY_WINDOW {
+            return Err(RuntimeError::InvalidConfig(format!(
+                "history window {} is not between 1 and {}",
+                window, MAX_HISTORY_WINDOW
+            )));
+        }
+        Ok(Self {
+            window,
+            samples: VecDeque::with_capacity(window),
+        })
+    }
+
+    pub fn window(&self) -> usize {
+        self.window
+    }
+
+    pub fn len(&self) -> usize {
+        self.samples.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.samples.is_empty()
+    }
+
+    /// Append the divergences of one evaluation, dropping the oldest sample
+    /// once the window is full.
+    pub fn record(&mut self, divergences: &[Divergence256]) {
+        if self.samples.len() == self.window {
+            self.samples.pop_front();
+        }
+        self.samples.push_back(divergences.to_vec());
+    }
+
+    /// The most recent sample.
+    pub fn latest(&self) -> Option<&[Divergence256]> {
+        self.samples.back().map(Vec::as_slice)
+    }
+
+    /// Divergences of `dimension`, oldest first. Samples without the
+    /// dimension count as zero.
+    pub fn series(&self, dimension: usize) -> Vec<Divergence256> {
+        self.samples
+            .iter()
+            .map(|sample| sample.get(dimension).copied().unwrap_or(Divergence256::ZERO))
+            .collect()
+    }
+
+    /// Correlation of dimensions `a` and `b` over the window, in percent.
+    ///
+    /// `None` with fewer than [`MIN_CORRELATION_SAMPLES`] samples or when
+    /// either series is constant.
+    pub fn correlation(&self, a: usize, b: usize, method: CorrelationMethod) -> Option<i32> {
+        if self.samples.len() < MIN_CORRELATION_SAMPLES {
+            return None;
+        }
+        let (a, b) = (self.series(a), self.series(b));
+        let (x, y) = match method {
+            CorrelationMethod::Pearson => (scaled(&a), scaled(&b)),
+            CorrelationMethod::Spearman => (doubled_ranks(&a), doubled_ranks(&b)),
+        };
+        pearson_percent(&x, &y)
+    }
+}
+
+/// `values` shifted right so that the largest fits in 16 bits.
+fn scaled(values: &[Divergence256]) -> Vec<i128> {
+    let shift = values.iter().map(Divergence256::bits).max().unwrap_or(0).saturating_sub(16);
+    values.iter().map(|value| bits_at(value, shift) as i128).collect()
+}
+
+/// The 16 bits of `value` starting at bit `shift`.
+fn bits_at(value: &Divergence256, shift: u32) -> u16 {
+    let bytes = value.as_bytes();
+    let first = (shift / 8) as usize;
+    let word = (0..3)
+        .filter_map(|i| bytes.get(first + i).map(|&byte| (byte as u32) << (8 * i)))
+        .fold(0u32, |word, byte| word | byte);
+    (word >> (shift % 8)) as u16
+}
+
+/// Twice the 1-based rank of every value; tied values share their average rank.
+fn doubled_ranks(values: &[Divergence256]) -> Vec<i128> {
+    let mut order: Vec<usize> = (0..values.len()).collect();
+    order.sort_by_key(|&i| values[i]);
+
+    let mut ranks = vec![0i128; values.len()];
+    let mut start = 0;
+    while start < order.len() {
+        let mut end = start;
+        while end + 1 < order.len() && values[order[end + 1]] == values[order[start]] {
+            end += 1;
+        }
+        // Positions start..=end hold equal values: average rank (start + end) / 2 + 1
+        for &i in &order[start..=end] {
+            ranks[i] = (start + end + 2) as i128;
+        }
+        start = end + 1;
+    }
+    ranks
+}
+
+/// Pearson correlation in percent, truncated toward zero.
+fn pearson_percent(x: &[i128], y: &[i128]) -> Option<i32> {
+    let n = x.len() as i128;
+    let (sum_x, sum_y): (i128, i128) = (x.iter().sum(), y.iter().sum());
+    let sum_xy: i128 = x.iter().zip(y).map(|(a, b)| a * b).sum();
+    let sum_xx: i128 = x.iter().map(|a| a * a).sum();
+    let sum_yy: i128 = y.iter().map(|b| b * b).sum();
+
+    let covariance = n * sum_xy - sum_x * sum_y;
+    let variance_x = (n * sum_xx - sum_x * sum_x) as u128;
+    let variance_y = (n * sum_yy - sum_y * sum_y) as u128;
+    let spread = isqrt(variance_x * variance_y) as i128;
+    if spread == 0 {
+        return None;
+    }
+    Some((100 * covariance / spread).clamp(-100, 100) as i32)
+}
+
+/// Square root rounded down, by Newton's method (`u128::isqrt` needs Rust 1.84).
+fn isqrt(n: u128) -> u128 {
+    if n < 2 {
+        return n;
+    }
+    // Start from a power of two at or above the root; the iterates then decrease to it
+    let mut root = 1u128 << ((129 - n.leading_zeros()) / 2);
+    loop {
+        let next = (root + n / root) / 2;
+        if next >= root {
+            return root;
+        }
+        root = next;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_isqrt() {
+        for n in 0..1000u128 {
+            let root = isqrt(n);
+            assert!(root * root <= n && (root + 1) * (root + 1) > n, "isqrt({})", n);
+        }
+        assert_eq!(isqrt(u128::MAX), u64::MAX as u128);
+        assert_eq!(isqrt((1u128 << 100) - 1), (1u128 << 50) - 1);
+    }
+
+    fn history(pairs: &[(u64, u64)]) -> DivergenceHistory {
+        let mut history = DivergenceHistory::new(pairs.len()).unwrap();
+        for &(a, b) in pairs {
+            history.record(&[Divergence256::from(a), Divergence256::from(b)]);
+        }
+        history
+    }
+
+    #[test]
+    fn test_window_slides() {
+        let mut history = DivergenceHistory::new(2).unwrap();
+        for value in 1..=3u64 {
+            history.record(&[Divergence256::from(value)]);
+        }
+        assert_eq!(history.len(), 2);
+        assert_eq!(history.series(0), vec![Divergence256::from(2u64), Divergence256::from(3u64)]);
+        assert_eq!(history.series(4), vec![Divergence256::ZERO; 2]);
+        assert_eq!(history.latest(), Some(&[Divergence256::from(3u64)][..]));
+
+        assert!(DivergenceHistory::new(0).is_err());
+        assert!(DivergenceHistory::new(MAX_HISTORY_WINDOW + 1).is_err());
+    }
+
+    #[test]
+    fn test_pearson_correlation() {
+        let linear = history(&[(1, 10), (2, 20), (3, 30), (4, 40)]);
+        assert_eq!(linear.correlation(0, 1, CorrelationMethod::Pearson), Some(100));
+
+        let inverse = history(&[(1, 40), (2, 30), (3, 20), (4, 10)]);
+        assert_eq!(inverse.correlation(0, 1, CorrelationMethod::Pearson), Some(-100));
+
+        // r = 0.9 exactly
+        let partial = history(&[(1, 1), (2, 3), (3, 2), (4, 4), (5, 5)]);
+        assert_eq!(partial.correlation(0, 1, CorrelationMethod::Pearson), Some(90));
+
+        let constant = history(&[(1, 7), (2, 7), (3, 7)]);
+        assert_eq!(constant.correlation(0, 1, CorrelationMethod::Pearson), None);
+        assert_eq!(history(&[(1, 1), (2, 2)]).correlation(0, 1, CorrelationMethod::Pearson), None);
+    }
+
+    #[test]
+    fn test_pearson_uses_full_width() {
+        // Values above 2^64 that only differ in their high bits
+        let wide = |high: u8| {
+            let mut bytes = [0u8; 32];
+            bytes[31] = high;
+            Divergence256::from_le_bytes(bytes)
+        };
+        let mut history = DivergenceHistory::new(3).unwrap();
+        for (a, b) in [(1, 3), (2, 2), (3, 1)] {
+            history.record(&[wide(a), wide(b)]);
+        }
+        assert_eq!(history.correlation(0, 1, CorrelationMethod::Pearson), Some(-100));
+    }
+
+    #[test]
+    fn test_spearman_correlation() {
+        // Monotonic but not linear
+        let monotonic = history(&[(1, 1), (2, 100), (3, 101), (4, 10_000)]);
+        assert_eq!(monotonic.correlation(0, 1, CorrelationMethod::Spearman), Some(100));
+        assert!(monotonic.correlation(0, 1, CorrelationMethod::Pearson).unwrap() < 100);
+
+        assert_eq!(
+            doubled_ranks(&[5u64, 1, 5, 3].map(Divergence256::from)),
+            vec![7, 2, 7, 4]
+        );
+    }
+}
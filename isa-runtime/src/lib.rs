@@ -7,9 +7,9 @@
 //! This crate contains a mix of conformance levels:
 //!
 //! - **NORMATIVE**: `policy` (threshold evaluation), `config` (configuration loading)
-//! - **OPTIONAL**: `constraints`, `hierarchy`
+//! - **OPTIONAL**: `constraints`, `expression`, `hierarchy`, `history`, `registry`
 //! - **EXPERIMENTAL**: `adaptive`
-//! - **INFORMATIVE**: `device`, `entropy`, `persistence`, `time`, `profile`
+//! - **INFORMATIVE**: `audit_log`, `device`, `enforcement`, `entropy`, `event_log`, `persistence`, `rollback`, `time`, `profile`
 //!
 //! See individual module documentation for detailed conformance requirements.
 //!
@@ -26,27 +26,45 @@
 //! - Expose language bindings (use isa-ffi)
 //! - Violate determinism when given fixed inputs from tests
 
+pub mod audit_log;
 pub mod device;
+pub mod enforcement;
 pub mod entropy;
+pub mod event_log;
 pub mod persistence;
+pub mod rollback;
 pub mod time;
 pub mod profile;
 pub mod policy;
 pub mod constraints;
+pub mod expression;
 pub mod hierarchy;
+pub mod history;
+pub mod registry;
 pub mod adaptive;
 pub mod config;
 
+pub use audit_log::{verify_chain, AuditEntry, AuditLog, ChainBreak, ChainBreakKind};
 pub use device::{DeviceRuntime, EventAxis, RecoveryAudit};
+pub use enforcement::{enforce_violations, EnforcementAction, EnforcementOutcome, EnforcementState, PolicyEnforcer};
 pub use entropy::EntropySource;
-pub use persistence::{Persistence, FilePersistence};
-pub use time::MonotonicClock;
+pub use event_log::{replay, DivergenceKind, EventLog, EventRecord, ReplayDivergence, ReplayReport};
+pub use persistence::{
+    Persistence, PersistedState, FilePersistence, InMemoryPersistence, DirectoryPersistence, DEFAULT_LOCK_TIMEOUT, EncryptedPersistence, JournaledPersistence, Slot, SlotFault, SlotRead, SlotSelection,
+};
+pub use rollback::{RollbackGuard, FileRollbackGuard, InMemoryRollbackGuard};
+pub use time::{unix_seconds, MonotonicClock};
 pub use profile::{DimensionProfile, DimensionMapping, standard_maisa_profile};
-pub use policy::{DimensionPolicy, PolicySet, RecoveryStrategy};
+pub use policy::{DimensionPolicy, EscalationLadder, EscalationState, EscalationStep, PolicySet, RecoveryStrategy};
 pub use constraints::{DimensionConstraint, ConstraintSet, ConstraintType};
+pub use history::{CorrelationMethod, DivergenceHistory, MAX_HISTORY_WINDOW};
+pub use registry::{ConstraintEvaluator, CustomRecovery, CustomRegistry, CustomViolation, RecoveryHandler};
+pub use expression::Expression;
 pub use hierarchy::{DimensionNode, DimensionHierarchy, DimensionMetadata};
 pub use adaptive::{AdaptiveProfile, DimensionObservation, DimensionStats, MLModel, ModelContext, ModelMetadata};
-pub use config::{IsaConfig, GlobalConfig, DimensionConfig, ConstraintConfig, HierarchyConfig, load_from_env};
+pub use config::{IsaConfig, GlobalConfig, DimensionConfig, ConstraintConfig, EscalationConfig, EscalationStepConfig, HierarchyConfig, load_from_env};
+
+use isa_core::suite::suite_name;
 
 pub type Result<T> = core::result::Result<T, RuntimeError>;
 
@@ -56,6 +74,17 @@ pub enum RuntimeError {
     PersistenceFailed(String),
     TimeSourceFailed,
     InvalidState,
+    HashSuiteMismatch { found: u8, expected: u8 },
+    UnknownHashSuite(String),
+    InvalidConfig(String),
+    /// Persisted state failed authentication: tampered, truncated or sealed under another key.
+    StateTampered,
+    /// Persisted state is older than the rollback high-water mark.
+    StateRolledBack { dimension: usize, found: u64, expected: u64 },
+    /// The state file is locked by another process or runtime.
+    StateInUse(String),
+    /// The dimension is quarantined and refuses events.
+    DimensionQuarantined(usize),
 }
 
 impl core::fmt::Display for RuntimeError {
@@ -65,6 +94,22 @@ impl core::fmt::Display for RuntimeError {
             RuntimeError::PersistenceFailed(msg) => write!(f, "Persistence error: {}", msg),
             RuntimeError::TimeSourceFailed => write!(f, "Time source unavailable"),
             RuntimeError::InvalidState => write!(f, "Invalid state"),
+            RuntimeError::HashSuiteMismatch { found, expected } => write!(
+                f,
+                "Persisted state uses hash suite {} but the runtime expects {}",
+                suite_name(*found).unwrap_or("unknown"),
+                suite_name(*expected).unwrap_or("unknown")
+            ),
+            RuntimeError::UnknownHashSuite(name) => write!(f, "Unknown hash suite: {}", name),
+            RuntimeError::InvalidConfig(msg) => write!(f, "Invalid configuration: {}", msg),
+            RuntimeError::StateTampered => write!(f, "Persisted state failed authentication"),
+            RuntimeError::StateRolledBack { dimension, found, expected } => write!(
+                f,
+                "Persisted state was rolled back: dimension {} is at counter {} but {} was already saved",
+                dimension, found, expected
+            ),
+            RuntimeError::StateInUse(path) => write!(f, "State in use by another process: {}", path),
+            RuntimeError::DimensionQuarantined(dimension) => write!(f, "Dimension {} is quarantined", dimension),
         }
     }
 }
@@ -1,24 +1,314 @@
 use crate::{Result, RuntimeError};
-use isa_core::MultiAxisState;
+use chacha20poly1305::aead::{Aead, KeyInit, Payload};
+use chacha20poly1305::{XChaCha20Poly1305, XNonce};
+use isa_core::kdf::Kdf;
+use isa_core::{DynamicIntegrityState, HashSuite, IntegrityState, IntegrityStateError, MigrationRecord};
 use std::fs;
+use std::io::Write;
 use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 use zeroize::Zeroizing;
 
+/// A state type that can be stored through [`Persistence`].
+pub trait PersistedState: Sized {
+    /// Encode the state for storage.
+    fn to_persisted_bytes(&self) -> Vec<u8>;
+
+    /// Decode a stored state.
+    fn from_persisted_bytes(bytes: &[u8]) -> core::result::Result<Self, IntegrityStateError>;
+}
+
+/// Stored in the canonical format (see `isa_core::canonical`); bincode files
+/// written by v0.1.0 still load.
+impl<const N: usize, H: HashSuite> PersistedState for IntegrityState<N, H> {
+    fn to_persisted_bytes(&self) -> Vec<u8> {
+        self.to_canonical_bytes()
+    }
+
+    fn from_persisted_bytes(bytes: &[u8]) -> core::result::Result<Self, IntegrityStateError> {
+        Self::from_bytes(bytes)
+    }
+}
+
+/// Stored in the canonical format with its dimension count.
+impl<H: HashSuite> PersistedState for DynamicIntegrityState<H> {
+    fn to_persisted_bytes(&self) -> Vec<u8> {
+        self.to_canonical_bytes()
+    }
+
+    fn from_persisted_bytes(bytes: &[u8]) -> core::result::Result<Self, IntegrityStateError> {
+        Self::from_bytes(bytes)
+    }
+}
+
+/// Storage for an encoded device state and its migration history.
+///
+/// Implementations store opaque bytes; encoding and decoding of any
+/// [`PersistedState`] is provided on top of `write_bytes` and `read_bytes`.
 pub trait Persistence {
-    fn save(&self, state: &MultiAxisState) -> Result<()>;
-    fn load(&self) -> Result<MultiAxisState>;
+    /// Atomically replace the stored state with `bytes`.
+    fn write_bytes(&self, bytes: &[u8]) -> Result<()>;
+
+    /// Read the stored state.
+    fn read_bytes(&self) -> Result<Zeroizing<Vec<u8>>>;
+
     fn exists(&self) -> bool;
+
+    /// Append a hash-suite migration record to the stored history.
+    fn append_migration_record(&self, record: &MigrationRecord) -> Result<()>;
+
+    /// Read all stored migration records, oldest first.
+    fn migration_records(&self) -> Result<Vec<MigrationRecord>>;
+
+    /// Replace the stored enforcement state (see `crate::enforcement`).
+    ///
+    /// Backends that cannot store it fail, so that a quarantine is never
+    /// silently lost.
+    fn write_enforcement_state(&self, _bytes: &[u8]) -> Result<()> {
+        Err(RuntimeError::PersistenceFailed("enforcement state not supported by this backend".to_string()))
+    }
+
+    /// Read the stored enforcement state, if any.
+    fn read_enforcement_state(&self) -> Result<Option<Zeroizing<Vec<u8>>>> {
+        Ok(None)
+    }
+
+    /// Encode and store a state.
+    fn save<S: PersistedState>(&self, state: &S) -> Result<()> {
+        self.write_bytes(&state.to_persisted_bytes())
+    }
+
+    /// Read and decode the stored state as `S`.
+    fn load<S: PersistedState>(&self) -> Result<S> {
+        S::from_persisted_bytes(&self.read_bytes()?)
+            .map_err(|_| RuntimeError::InvalidState)
+    }
+}
+
+/// Persistence held in memory, for tests and ephemeral devices.
+///
+/// Clones share the same storage, so a test can hand one clone to a runtime
+/// and keep another to inspect what was saved or to simulate a restart.
+#[derive(Debug, Clone, Default)]
+pub struct InMemoryPersistence {
+    inner: Arc<Mutex<InMemoryStore>>,
+}
+
+#[derive(Debug, Default)]
+struct InMemoryStore {
+    state: Option<Zeroizing<Vec<u8>>>,
+    migrations: Vec<MigrationRecord>,
+    enforcement: Option<Zeroizing<Vec<u8>>>,
+}
+
+impl InMemoryPersistence {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn store(&self) -> Result<std::sync::MutexGuard<'_, InMemoryStore>> {
+        self.inner.lock().map_err(|_| RuntimeError::PersistenceFailed("storage lock poisoned".to_string()))
+    }
+}
+
+impl Persistence for InMemoryPersistence {
+    fn write_bytes(&self, bytes: &[u8]) -> Result<()> {
+        self.store()?.state = Some(Zeroizing::new(bytes.to_vec()));
+        Ok(())
+    }
+
+    fn read_bytes(&self) -> Result<Zeroizing<Vec<u8>>> {
+        self.store()?
+            .state
+            .clone()
+            .ok_or_else(|| RuntimeError::PersistenceFailed("no state stored".to_string()))
+    }
+
+    fn exists(&self) -> bool {
+        self.store().map(|store| store.state.is_some()).unwrap_or(false)
+    }
+
+    /// Appending the same record as the last stored one is a no-op, as for [`FilePersistence`].
+    fn append_migration_record(&self, record: &MigrationRecord) -> Result<()> {
+        let mut store = self.store()?;
+        if store.migrations.last() != Some(record) {
+            store.migrations.push(*record);
+        }
+        Ok(())
+    }
+
+    fn migration_records(&self) -> Result<Vec<MigrationRecord>> {
+        Ok(self.store()?.migrations.clone())
+    }
+
+    fn write_enforcement_state(&self, bytes: &[u8]) -> Result<()> {
+        self.store()?.enforcement = Some(Zeroizing::new(bytes.to_vec()));
+        Ok(())
+    }
+
+    fn read_enforcement_state(&self) -> Result<Option<Zeroizing<Vec<u8>>>> {
+        Ok(self.store()?.enforcement.clone())
+    }
+}
+
+/// Extension of device state files in a [`DirectoryPersistence`].
+pub const DEVICE_STATE_EXTENSION: &str = "state";
+
+/// Many device states in one directory, keyed by device id.
+///
+/// Device `id` is stored in `<root>/<id>.state` by a [`FilePersistence`], with
+/// its migration history and lock file next to it. Ids are limited to ASCII
+/// letters, digits, `-`, `_` and `.`, and may not start with `.`, so that an id
+/// always names a file inside the directory.
+pub struct DirectoryPersistence {
+    root: PathBuf,
+    lock_timeout: Duration,
+}
+
+impl DirectoryPersistence {
+    pub fn new<P: AsRef<Path>>(root: P) -> Self {
+        Self {
+            root: root.as_ref().to_path_buf(),
+            lock_timeout: DEFAULT_LOCK_TIMEOUT,
+        }
+    }
+
+    /// Lock timeout of the persistences returned by [`DirectoryPersistence::device`].
+    pub fn with_lock_timeout(mut self, timeout: Duration) -> Self {
+        self.lock_timeout = timeout;
+        self
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Persistence for the state of device `id`.
+    pub fn device(&self, id: &str) -> Result<FilePersistence> {
+        Ok(FilePersistence::new(self.device_path(id)?).with_lock_timeout(self.lock_timeout))
+    }
+
+    /// Path of the state file of device `id`.
+    pub fn device_path(&self, id: &str) -> Result<PathBuf> {
+        let valid = !id.is_empty()
+            && !id.starts_with('.')
+            && id.bytes().all(|b| b.is_ascii_alphanumeric() || matches!(b, b'-' | b'_' | b'.'));
+        if !valid {
+            return Err(RuntimeError::PersistenceFailed(format!("invalid device id: {:?}", id)));
+        }
+        Ok(self.root.join(format!("{}.{}", id, DEVICE_STATE_EXTENSION)))
+    }
+
+    /// Ids of all devices with a stored state, sorted.
+    pub fn device_ids(&self) -> Result<Vec<String>> {
+        let entries = match fs::read_dir(&self.root) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(RuntimeError::PersistenceFailed(e.to_string())),
+        };
+        
+        let mut ids = Vec::new();
+        for entry in entries {
+            let path = entry.map_err(|e| RuntimeError::PersistenceFailed(e.to_string()))?.path();
+            if path.extension().and_then(|ext| ext.to_str()) != Some(DEVICE_STATE_EXTENSION) {
+                continue;
+            }
+            if let Some(id) = path.file_stem().and_then(|stem| stem.to_str()) {
+                if self.device_path(id).is_ok() {
+                    ids.push(id.to_string());
+                }
+            }
+        }
+        ids.sort();
+        Ok(ids)
+    }
+
+    /// Delete the state, migration history and enforcement state of device `id`.
+    ///
+    /// A device created later under the same id starts without quarantines.
+    /// The lock file is deleted last, while still held, so runtimes of the
+    /// device must be stopped first.
+    pub fn remove(&self, id: &str) -> Result<()> {
+        let persistence = self.device(id)?.with_exclusive_lock()?;
+        for path in [
+            persistence.path.clone(),
+            persistence.migrations_path(),
+            persistence.enforcement_path(),
+            persistence.lock_path(),
+        ] {
+            match fs::remove_file(&path) {
+                Ok(()) => {}
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+                Err(e) => return Err(RuntimeError::PersistenceFailed(e.to_string())),
+            }
+        }
+        Ok(())
+    }
 }
 
+/// How long file persistence waits for another process to release the state lock.
+pub const DEFAULT_LOCK_TIMEOUT: Duration = Duration::from_secs(5);
+
+const LOCK_POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+/// Atomic file persistence with cross-process advisory locking.
+///
+/// Every operation takes an advisory lock (`flock` on Unix) on a lock file
+/// next to the state file: shared for reads, exclusive for writes. A lock held
+/// elsewhere is retried until the lock timeout, then the operation fails with
+/// `RuntimeError::StateInUse`.
+///
+/// Per-operation locks keep single reads and writes whole, but two processes
+/// can still interleave load and save and lose events. A process that writes
+/// the state should hold the lock for its whole lifetime with
+/// [`FilePersistence::with_exclusive_lock`]; a second writer then fails to
+/// start with `StateInUse`.
 pub struct FilePersistence {
     path: PathBuf,
+    lock_timeout: Duration,
+    held_lock: Option<fs::File>,
 }
 
 impl FilePersistence {
     pub fn new<P: AsRef<Path>>(path: P) -> Self {
         Self {
             path: path.as_ref().to_path_buf(),
+            lock_timeout: DEFAULT_LOCK_TIMEOUT,
+            held_lock: None,
+        }
+    }
+
+    /// Wait at most `timeout` for the state lock (default [`DEFAULT_LOCK_TIMEOUT`]).
+    pub fn with_lock_timeout(mut self, timeout: Duration) -> Self {
+        self.lock_timeout = timeout;
+        self
+    }
+
+    /// Take the exclusive state lock and hold it until this persistence is dropped.
+    ///
+    /// Fails with `StateInUse` if another process or persistence holds the
+    /// lock for longer than the lock timeout.
+    pub fn with_exclusive_lock(mut self) -> Result<Self> {
+        if self.held_lock.is_none() {
+            self.held_lock = self.lock(LockKind::Exclusive)?;
         }
+        Ok(self)
+    }
+
+    /// Path of the migration history kept next to the state file.
+    pub fn migrations_path(&self) -> PathBuf {
+        self.path.with_extension("migrations")
+    }
+
+    /// Path of the enforcement state kept next to the state file.
+    pub fn enforcement_path(&self) -> PathBuf {
+        self.path.with_extension("enforcement")
+    }
+
+    /// Path of the lock file kept next to the state file.
+    pub fn lock_path(&self) -> PathBuf {
+        self.path.with_extension("lock")
     }
 
     fn ensure_parent_dir(&self) -> Result<()> {
@@ -28,43 +318,530 @@ impl FilePersistence {
         }
         Ok(())
     }
-}
 
-impl Persistence for FilePersistence {
-    fn save(&self, state: &MultiAxisState) -> Result<()> {
-        self.ensure_parent_dir()?;
+    /// Take the state lock for one operation.
+    ///
+    /// Returns `None` when the exclusive lock is already held for the lifetime
+    /// of this persistence. The lock is released when the file is dropped.
+    fn lock(&self, kind: LockKind) -> Result<Option<fs::File>> {
+        if self.held_lock.is_some() {
+            return Ok(None);
+        }
         
-        let bytes = state.to_bytes()
+        self.ensure_parent_dir()?;
+        let file = fs::OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(self.lock_path())
             .map_err(|e| RuntimeError::PersistenceFailed(e.to_string()))?;
         
+        let deadline = Instant::now() + self.lock_timeout;
+        loop {
+            match try_lock(&file, kind) {
+                Ok(true) => return Ok(Some(file)),
+                Ok(false) if Instant::now() >= deadline => {
+                    return Err(RuntimeError::StateInUse(self.path.display().to_string()));
+                }
+                Ok(false) => std::thread::sleep(LOCK_POLL_INTERVAL),
+                Err(e) => return Err(RuntimeError::PersistenceFailed(e.to_string())),
+            }
+        }
+    }
+
+    fn read_migration_records(&self) -> Result<Vec<MigrationRecord>> {
+        let bytes = match fs::read(self.migrations_path()) {
+            Ok(bytes) => bytes,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(RuntimeError::PersistenceFailed(e.to_string())),
+        };
+        
+        if bytes.len() % MigrationRecord::ENCODED_LEN != 0 {
+            return Err(RuntimeError::InvalidState);
+        }
+        bytes
+            .chunks(MigrationRecord::ENCODED_LEN)
+            .map(|chunk| MigrationRecord::from_bytes(chunk).map_err(|_| RuntimeError::InvalidState))
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum LockKind {
+    Shared,
+    Exclusive,
+}
+
+/// Try to lock `file` without blocking; `Ok(false)` if the lock is held elsewhere.
+#[cfg(not(target_arch = "wasm32"))]
+fn try_lock(file: &fs::File, kind: LockKind) -> std::io::Result<bool> {
+    let result = match kind {
+        LockKind::Shared => fs2::FileExt::try_lock_shared(file),
+        LockKind::Exclusive => fs2::FileExt::try_lock_exclusive(file),
+    };
+    match result {
+        Ok(()) => Ok(true),
+        Err(e) if e.kind() == std::io::ErrorKind::WouldBlock
+            || e.raw_os_error() == fs2::lock_contended_error().raw_os_error() => Ok(false),
+        Err(e) => Err(e),
+    }
+}
+
+/// There are no other processes to exclude on wasm.
+#[cfg(target_arch = "wasm32")]
+fn try_lock(_file: &fs::File, _kind: LockKind) -> std::io::Result<bool> {
+    Ok(true)
+}
+
+impl Persistence for FilePersistence {
+    /// Writes a temporary file, syncs it, renames it over the state file and
+    /// syncs the directory.
+    fn write_bytes(&self, bytes: &[u8]) -> Result<()> {
+        let _lock = self.lock(LockKind::Exclusive)?;
+        
         let temp_path = self.path.with_extension("tmp");
-        fs::write(&temp_path, &bytes)
-            .map_err(|e| RuntimeError::PersistenceFailed(e.to_string()))?;
+        write_synced(&temp_path, bytes)?;
         
         fs::rename(&temp_path, &self.path)
             .map_err(|e| RuntimeError::PersistenceFailed(e.to_string()))?;
+        sync_parent_dir(&self.path)
+    }
+
+    fn read_bytes(&self) -> Result<Zeroizing<Vec<u8>>> {
+        let _lock = self.lock(LockKind::Shared)?;
+        fs::read(&self.path)
+            .map(Zeroizing::new)
+            .map_err(|e| RuntimeError::PersistenceFailed(e.to_string()))
+    }
+
+    fn exists(&self) -> bool {
+        self.path.exists()
+    }
+
+    /// Records are stored back to back in their fixed-size encoding.
+    ///
+    /// Appending the same record as the last stored one is a no-op, so a
+    /// migration interrupted before the new state was written can be rerun.
+    fn append_migration_record(&self, record: &MigrationRecord) -> Result<()> {
+        let _lock = self.lock(LockKind::Exclusive)?;
+        if self.read_migration_records()?.last() == Some(record) {
+            return Ok(());
+        }
         
-        Ok(())
+        let mut file = fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(self.migrations_path())
+            .map_err(|e| RuntimeError::PersistenceFailed(e.to_string()))?;
+        file.write_all(&record.to_bytes())
+            .and_then(|_| file.sync_all())
+            .map_err(|e| RuntimeError::PersistenceFailed(e.to_string()))
     }
 
-    fn load(&self) -> Result<MultiAxisState> {
-        let bytes = Zeroizing::new(
-            fs::read(&self.path)
-                .map_err(|e| RuntimeError::PersistenceFailed(e.to_string()))?
-        );
+    fn migration_records(&self) -> Result<Vec<MigrationRecord>> {
+        let _lock = self.lock(LockKind::Shared)?;
+        self.read_migration_records()
+    }
+
+    fn write_enforcement_state(&self, bytes: &[u8]) -> Result<()> {
+        let _lock = self.lock(LockKind::Exclusive)?;
+        let path = self.enforcement_path();
+        let temp_path = path.with_extension("enforcement.tmp");
+        write_synced(&temp_path, bytes)?;
+        fs::rename(&temp_path, &path)
+            .map_err(|e| RuntimeError::PersistenceFailed(e.to_string()))?;
+        sync_parent_dir(&path)
+    }
+
+    fn read_enforcement_state(&self) -> Result<Option<Zeroizing<Vec<u8>>>> {
+        let _lock = self.lock(LockKind::Shared)?;
+        match fs::read(self.enforcement_path()) {
+            Ok(bytes) => Ok(Some(Zeroizing::new(bytes))),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(RuntimeError::PersistenceFailed(e.to_string())),
+        }
+    }
+}
+
+/// Write `bytes` to `path` and flush them to the device.
+pub(crate) fn write_synced(path: &Path, bytes: &[u8]) -> Result<()> {
+    let mut file = fs::File::create(path)
+        .map_err(|e| RuntimeError::PersistenceFailed(e.to_string()))?;
+    file.write_all(bytes)
+        .and_then(|_| file.sync_all())
+        .map_err(|e| RuntimeError::PersistenceFailed(e.to_string()))
+}
+
+/// Flush the directory entry of `path`, so a created or renamed file survives a power cut.
+pub(crate) fn sync_parent_dir(path: &Path) -> Result<()> {
+    #[cfg(unix)]
+    if let Some(parent) = path.parent().filter(|parent| !parent.as_os_str().is_empty()) {
+        fs::File::open(parent)
+            .and_then(|dir| dir.sync_all())
+            .map_err(|e| RuntimeError::PersistenceFailed(e.to_string()))?;
+    }
+    #[cfg(not(unix))]
+    let _ = path;
+    Ok(())
+}
+
+/// Magic bytes at the start of a journal slot.
+pub const SLOT_MAGIC: [u8; 4] = *b"MISJ";
+
+const SLOT_HEADER_LEN: usize = SLOT_MAGIC.len() + 8 + 4;
+
+/// One of the two slots of a [`JournaledPersistence`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Slot {
+    A,
+    B,
+}
+
+impl Slot {
+    pub fn name(self) -> &'static str {
+        match self {
+            Slot::A => "a",
+            Slot::B => "b",
+        }
+    }
+
+    fn other(self) -> Slot {
+        match self {
+            Slot::A => Slot::B,
+            Slot::B => Slot::A,
+        }
+    }
+}
+
+/// Reason a journal slot could not be used.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SlotFault {
+    /// The slot has never been written.
+    Missing,
+    /// The slot file could not be read.
+    Unreadable(String),
+    /// The slot does not start with [`SLOT_MAGIC`].
+    BadMagic,
+    /// The slot is shorter than its header says.
+    Truncated,
+    /// The CRC-32 trailer does not match the slot contents.
+    ChecksumMismatch,
+}
+
+impl core::fmt::Display for SlotFault {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            SlotFault::Missing => write!(f, "missing"),
+            SlotFault::Unreadable(msg) => write!(f, "unreadable ({})", msg),
+            SlotFault::BadMagic => write!(f, "bad magic"),
+            SlotFault::Truncated => write!(f, "truncated"),
+            SlotFault::ChecksumMismatch => write!(f, "checksum mismatch"),
+        }
+    }
+}
+
+/// Why a slot was chosen when loading a [`JournaledPersistence`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SlotSelection {
+    /// Both slots are valid and the chosen one has the higher generation.
+    Newest,
+    /// The other slot could not be used.
+    Fallback(SlotFault),
+}
+
+/// State read from a [`JournaledPersistence`] and where it came from.
+#[derive(Debug)]
+pub struct SlotRead {
+    pub slot: Slot,
+    pub generation: u64,
+    pub selection: SlotSelection,
+    pub bytes: Zeroizing<Vec<u8>>,
+}
+
+impl core::fmt::Display for SlotRead {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "slot {} (generation {})", self.slot.name(), self.generation)?;
+        match &self.selection {
+            SlotSelection::Newest => write!(f, ": newest valid slot"),
+            SlotSelection::Fallback(fault) => write!(f, ": slot {} {}", self.slot.other().name(), fault),
+        }
+    }
+}
+
+/// Crash-safe persistence that alternates between two slot files.
+///
+/// Each write goes to the slot not holding the newest valid state, with the
+/// next generation number, and is synced together with its directory before
+/// returning. The previous generation stays intact in the other slot, so a
+/// write cut short by a power loss leaves at most one damaged slot and loading
+/// falls back to the other. A slot is
+///
+/// ```text
+/// magic "MISJ" (4) || generation u64 LE (8) || length u32 LE (4) || payload || CRC-32 (4)
+/// ```
+///
+/// with the CRC-32 (`isa_core::canonical::crc32`) covering everything before it.
+/// Slots are stored next to `path` with the extensions `a` and `b`; migration
+/// records and locking are as for [`FilePersistence`].
+pub struct JournaledPersistence {
+    file: FilePersistence,
+}
+
+impl JournaledPersistence {
+    pub fn new<P: AsRef<Path>>(path: P) -> Self {
+        Self {
+            file: FilePersistence::new(path),
+        }
+    }
+
+    /// See [`FilePersistence::with_lock_timeout`].
+    pub fn with_lock_timeout(mut self, timeout: Duration) -> Self {
+        self.file = self.file.with_lock_timeout(timeout);
+        self
+    }
+
+    /// See [`FilePersistence::with_exclusive_lock`].
+    pub fn with_exclusive_lock(mut self) -> Result<Self> {
+        self.file = self.file.with_exclusive_lock()?;
+        Ok(self)
+    }
+
+    pub fn slot_path(&self, slot: Slot) -> PathBuf {
+        self.file.path.with_extension(slot.name())
+    }
+
+    /// Read the newest valid slot, reporting which slot was used and why.
+    pub fn read_slot(&self) -> Result<SlotRead> {
+        let _lock = self.file.lock(LockKind::Shared)?;
+        self.read_newest()
+    }
+
+    fn read_newest(&self) -> Result<SlotRead> {
+        let (a, b) = (self.read_valid(Slot::A), self.read_valid(Slot::B));
+        let (slot, (generation, bytes), selection) = match (a, b) {
+            (Ok(a), Ok(b)) if b.0 > a.0 => (Slot::B, b, SlotSelection::Newest),
+            (Ok(a), Ok(_)) => (Slot::A, a, SlotSelection::Newest),
+            (Ok(a), Err(fault)) => (Slot::A, a, SlotSelection::Fallback(fault)),
+            (Err(fault), Ok(b)) => (Slot::B, b, SlotSelection::Fallback(fault)),
+            (Err(a), Err(b)) => {
+                return Err(RuntimeError::PersistenceFailed(format!(
+                    "no valid state slot (a: {}, b: {})",
+                    a, b
+                )));
+            }
+        };
+        Ok(SlotRead { slot, generation, selection, bytes })
+    }
+
+    /// Generation and payload of a valid slot.
+    fn read_valid(&self, slot: Slot) -> core::result::Result<(u64, Zeroizing<Vec<u8>>), SlotFault> {
+        let bytes = match fs::read(self.slot_path(slot)) {
+            Ok(bytes) => Zeroizing::new(bytes),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Err(SlotFault::Missing),
+            Err(e) => return Err(SlotFault::Unreadable(e.to_string())),
+        };
         
-        MultiAxisState::from_bytes(&bytes)
-            .map_err(|_| RuntimeError::InvalidState)
+        if bytes.len() < SLOT_MAGIC.len() || bytes[..SLOT_MAGIC.len()] != SLOT_MAGIC {
+            return Err(SlotFault::BadMagic);
+        }
+        if bytes.len() < SLOT_HEADER_LEN {
+            return Err(SlotFault::Truncated);
+        }
+        let mut generation = [0u8; 8];
+        generation.copy_from_slice(&bytes[4..12]);
+        let mut len = [0u8; 4];
+        len.copy_from_slice(&bytes[12..16]);
+        let body_len = SLOT_HEADER_LEN + u32::from_le_bytes(len) as usize;
+        if bytes.len() != body_len + 4 {
+            return Err(SlotFault::Truncated);
+        }
+        
+        let mut checksum = [0u8; 4];
+        checksum.copy_from_slice(&bytes[body_len..]);
+        if isa_core::canonical::crc32(&bytes[..body_len]) != u32::from_le_bytes(checksum) {
+            return Err(SlotFault::ChecksumMismatch);
+        }
+        Ok((u64::from_le_bytes(generation), Zeroizing::new(bytes[SLOT_HEADER_LEN..body_len].to_vec())))
+    }
+}
+
+impl Persistence for JournaledPersistence {
+    fn write_bytes(&self, bytes: &[u8]) -> Result<()> {
+        let len = u32::try_from(bytes.len())
+            .map_err(|_| RuntimeError::PersistenceFailed("state too large for a slot".to_string()))?;
+        let _lock = self.file.lock(LockKind::Exclusive)?;
+        let (slot, generation) = match self.read_newest() {
+            Ok(current) => (current.slot.other(), current.generation.wrapping_add(1)),
+            Err(_) => (Slot::A, 1),
+        };
+        
+        let mut encoded = Zeroizing::new(Vec::with_capacity(SLOT_HEADER_LEN + bytes.len() + 4));
+        encoded.extend_from_slice(&SLOT_MAGIC);
+        encoded.extend_from_slice(&generation.to_le_bytes());
+        encoded.extend_from_slice(&len.to_le_bytes());
+        encoded.extend_from_slice(bytes);
+        let checksum = isa_core::canonical::crc32(&encoded);
+        encoded.extend_from_slice(&checksum.to_le_bytes());
+        
+        let path = self.slot_path(slot);
+        write_synced(&path, &encoded)?;
+        sync_parent_dir(&path)
+    }
+
+    fn read_bytes(&self) -> Result<Zeroizing<Vec<u8>>> {
+        self.read_slot().map(|read| read.bytes)
     }
 
     fn exists(&self) -> bool {
-        self.path.exists()
+        self.slot_path(Slot::A).exists() || self.slot_path(Slot::B).exists()
+    }
+
+    fn append_migration_record(&self, record: &MigrationRecord) -> Result<()> {
+        self.file.append_migration_record(record)
+    }
+
+    fn migration_records(&self) -> Result<Vec<MigrationRecord>> {
+        self.file.migration_records()
+    }
+
+    fn write_enforcement_state(&self, bytes: &[u8]) -> Result<()> {
+        self.file.write_enforcement_state(bytes)
+    }
+
+    fn read_enforcement_state(&self) -> Result<Option<Zeroizing<Vec<u8>>>> {
+        self.file.read_enforcement_state()
+    }
+}
+
+/// Key derivation context for the state encryption key.
+pub const ENCRYPTION_KEY_CONTEXT: &[u8] = b"isa.persistence.aead";
+
+/// Magic bytes at the start of an encrypted state.
+pub const ENCRYPTED_MAGIC: [u8; 4] = *b"MISE";
+
+/// Encrypted state format version.
+pub const ENCRYPTED_FORMAT_VERSION: u8 = 1;
+
+/// AEAD identifier for XChaCha20-Poly1305.
+pub const AEAD_XCHACHA20_POLY1305: u8 = 1;
+
+const NONCE_LEN: usize = 24;
+const HEADER_LEN: usize = ENCRYPTED_MAGIC.len() + 2 + NONCE_LEN;
+
+/// Persistence that encrypts the state before handing it to another backend.
+///
+/// The state is sealed with XChaCha20-Poly1305 under a key derived from the
+/// master seed (`Kdf` with context [`ENCRYPTION_KEY_CONTEXT`]). Each write uses
+/// a fresh random nonce. The stored bytes are
+///
+/// ```text
+/// magic "MISE" (4) || format version (1) || AEAD id (1) || nonce (24) || ciphertext || tag (16)
+/// ```
+///
+/// and the 30-byte header is authenticated as associated data. Reading bytes
+/// that do not authenticate (tampered, truncated, unencrypted or sealed under
+/// another seed) fails with `RuntimeError::StateTampered`.
+///
+/// The enforcement state is sealed the same way. Migration records are passed
+/// to the inner backend unencrypted; they hold only commitments.
+pub struct EncryptedPersistence<P: Persistence> {
+    inner: P,
+    key: Zeroizing<[u8; 32]>,
+}
+
+impl<P: Persistence> EncryptedPersistence<P> {
+    pub fn new(inner: P, master_seed: &[u8; 32]) -> Self {
+        Self {
+            inner,
+            key: Zeroizing::new(Kdf::derive_key(ENCRYPTION_KEY_CONTEXT, &[master_seed])),
+        }
+    }
+
+    pub fn inner(&self) -> &P {
+        &self.inner
+    }
+
+    fn cipher(&self) -> XChaCha20Poly1305 {
+        XChaCha20Poly1305::new(self.key.as_ref().into())
+    }
+
+    fn seal(&self, bytes: &[u8]) -> Result<Vec<u8>> {
+        let mut nonce = [0u8; NONCE_LEN];
+        getrandom::getrandom(&mut nonce)
+            .map_err(|_| RuntimeError::EntropyGenerationFailed)?;
+
+        let mut sealed = Vec::with_capacity(HEADER_LEN + bytes.len() + 16);
+        sealed.extend_from_slice(&ENCRYPTED_MAGIC);
+        sealed.push(ENCRYPTED_FORMAT_VERSION);
+        sealed.push(AEAD_XCHACHA20_POLY1305);
+        sealed.extend_from_slice(&nonce);
+
+        let ciphertext = self
+            .cipher()
+            .encrypt(XNonce::from_slice(&nonce), Payload { msg: bytes, aad: &sealed })
+            .map_err(|_| RuntimeError::PersistenceFailed("encryption failed".to_string()))?;
+        sealed.extend_from_slice(&ciphertext);
+        Ok(sealed)
+    }
+
+    fn open(&self, sealed: &[u8]) -> Result<Zeroizing<Vec<u8>>> {
+        if sealed.len() < HEADER_LEN {
+            return Err(RuntimeError::StateTampered);
+        }
+
+        let (header, ciphertext) = sealed.split_at(HEADER_LEN);
+        if header[..4] != ENCRYPTED_MAGIC
+            || header[4] != ENCRYPTED_FORMAT_VERSION
+            || header[5] != AEAD_XCHACHA20_POLY1305
+        {
+            return Err(RuntimeError::StateTampered);
+        }
+
+        self.cipher()
+            .decrypt(XNonce::from_slice(&header[6..]), Payload { msg: ciphertext, aad: header })
+            .map(Zeroizing::new)
+            .map_err(|_| RuntimeError::StateTampered)
+    }
+}
+
+impl<P: Persistence> Persistence for EncryptedPersistence<P> {
+    fn write_bytes(&self, bytes: &[u8]) -> Result<()> {
+        self.inner.write_bytes(&self.seal(bytes)?)
+    }
+
+    fn read_bytes(&self) -> Result<Zeroizing<Vec<u8>>> {
+        self.open(&self.inner.read_bytes()?)
+    }
+
+    fn exists(&self) -> bool {
+        self.inner.exists()
+    }
+
+    fn append_migration_record(&self, record: &MigrationRecord) -> Result<()> {
+        self.inner.append_migration_record(record)
+    }
+
+    fn migration_records(&self) -> Result<Vec<MigrationRecord>> {
+        self.inner.migration_records()
+    }
+
+    fn write_enforcement_state(&self, bytes: &[u8]) -> Result<()> {
+        self.inner.write_enforcement_state(&self.seal(bytes)?)
+    }
+
+    fn read_enforcement_state(&self) -> Result<Option<Zeroizing<Vec<u8>>>> {
+        self.inner
+            .read_enforcement_state()?
+            .map(|sealed| self.open(&sealed))
+            .transpose()
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use isa_core::MultiAxisState;
     use tempfile::TempDir;
 
     #[test]
@@ -84,7 +861,7 @@ mod tests {
         persistence.save(&state1).unwrap();
         assert!(persistence.exists());
 
-        let state2 = persistence.load().unwrap();
+        let state2 = persistence.load::<MultiAxisState>().unwrap();
         assert_eq!(state1.state_vector(), state2.state_vector());
     }
 
@@ -100,4 +877,309 @@ mod tests {
         persistence.save(&state).unwrap();
         assert!(persistence.exists());
     }
+
+    #[test]
+    fn test_file_persistence_writes_canonical_format() {
+        let temp_dir = TempDir::new().unwrap();
+        let state_path = temp_dir.path().join("state.bin");
+        let persistence = FilePersistence::new(&state_path);
+
+        let state = MultiAxisState::from_master_seed([1u8; 32]);
+        persistence.save(&state).unwrap();
+
+        let bytes = std::fs::read(&state_path).unwrap();
+        assert!(isa_core::canonical::is_canonical(&bytes));
+        assert_eq!(bytes.len(), isa_core::canonical::encoded_len(3));
+    }
+
+    #[test]
+    fn test_file_persistence_loads_bincode_files() {
+        let temp_dir = TempDir::new().unwrap();
+        let state_path = temp_dir.path().join("state.bin");
+        let persistence = FilePersistence::new(&state_path);
+
+        let state = MultiAxisState::from_master_seed([1u8; 32]);
+        std::fs::write(&state_path, state.to_bytes().unwrap()).unwrap();
+
+        let loaded = persistence.load::<MultiAxisState>().unwrap();
+        assert_eq!(state.state_vector(), loaded.state_vector());
+    }
+
+    #[test]
+    fn test_file_persistence_migration_records() {
+        let temp_dir = TempDir::new().unwrap();
+        let persistence = FilePersistence::new(temp_dir.path().join("state.bin"));
+        assert!(persistence.migration_records().unwrap().is_empty());
+
+        let state = MultiAxisState::from_master_seed([1u8; 32]);
+        let (_, record) = isa_core::migrate::<3, isa_core::Blake3Suite, isa_core::Shake256Suite>(&state, &[1u8; 32]);
+
+        persistence.append_migration_record(&record).unwrap();
+        persistence.append_migration_record(&record).unwrap();
+        assert_eq!(persistence.migration_records().unwrap(), vec![record]);
+    }
+
+    fn encrypted(temp_dir: &TempDir, seed: u8) -> EncryptedPersistence<FilePersistence> {
+        EncryptedPersistence::new(FilePersistence::new(temp_dir.path().join("state.bin")), &[seed; 32])
+    }
+
+    #[test]
+    fn test_encrypted_persistence_roundtrip() {
+        let temp_dir = TempDir::new().unwrap();
+        let persistence = encrypted(&temp_dir, 1);
+        let state = MultiAxisState::from_master_seed([1u8; 32]);
+
+        persistence.save(&state).unwrap();
+        let loaded = persistence.load::<MultiAxisState>().unwrap();
+        assert_eq!(state.state_vector(), loaded.state_vector());
+
+        // The file holds no plaintext
+        let bytes = std::fs::read(temp_dir.path().join("state.bin")).unwrap();
+        assert_eq!(&bytes[..4], b"MISE");
+        assert!(!isa_core::canonical::is_canonical(&bytes));
+        let plaintext = state.to_canonical_bytes();
+        assert!(!bytes.windows(32).any(|w| plaintext.windows(32).any(|p| p == w)));
+    }
+
+    #[test]
+    fn test_encrypted_persistence_detects_tampering() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("state.bin");
+        let persistence = encrypted(&temp_dir, 1);
+        persistence.save(&MultiAxisState::from_master_seed([1u8; 32])).unwrap();
+        let original = std::fs::read(&path).unwrap();
+
+        // Header byte (nonce), ciphertext byte, tag byte, and truncation
+        for index in [10, HEADER_LEN + 5, original.len() - 1] {
+            let mut bytes = original.clone();
+            bytes[index] ^= 0x01;
+            std::fs::write(&path, &bytes).unwrap();
+            assert!(matches!(persistence.load::<MultiAxisState>(), Err(RuntimeError::StateTampered)));
+        }
+        std::fs::write(&path, &original[..original.len() - 1]).unwrap();
+        assert!(matches!(persistence.load::<MultiAxisState>(), Err(RuntimeError::StateTampered)));
+
+        std::fs::write(&path, &original).unwrap();
+        assert!(persistence.load::<MultiAxisState>().is_ok());
+    }
+
+    #[test]
+    fn test_encrypted_persistence_rejects_wrong_seed_and_plaintext() {
+        let temp_dir = TempDir::new().unwrap();
+        let state = MultiAxisState::from_master_seed([1u8; 32]);
+        encrypted(&temp_dir, 1).save(&state).unwrap();
+        assert!(matches!(encrypted(&temp_dir, 2).load::<MultiAxisState>(), Err(RuntimeError::StateTampered)));
+
+        FilePersistence::new(temp_dir.path().join("state.bin")).save(&state).unwrap();
+        assert!(matches!(encrypted(&temp_dir, 1).load::<MultiAxisState>(), Err(RuntimeError::StateTampered)));
+    }
+
+    #[test]
+    fn test_encrypted_persistence_with_device_runtime() {
+        let temp_dir = TempDir::new().unwrap();
+        let seed = [7u8; 32];
+        let persistence = EncryptedPersistence::new(FilePersistence::new(temp_dir.path().join("state.bin")), &seed);
+
+        let mut runtime = crate::DeviceRuntime::load_or_create(seed, persistence).unwrap();
+        runtime.record_sale(b"sale:1").unwrap();
+        runtime.save().unwrap();
+        let vector = runtime.state_vector();
+
+        let persistence = EncryptedPersistence::new(FilePersistence::new(temp_dir.path().join("state.bin")), &seed);
+        let reloaded = crate::DeviceRuntime::load_or_create(seed, persistence).unwrap();
+        assert_eq!(reloaded.state_vector(), vector);
+    }
+
+    #[test]
+    fn test_journaled_persistence_alternates_slots() {
+        let temp_dir = TempDir::new().unwrap();
+        let persistence = JournaledPersistence::new(temp_dir.path().join("state.bin"));
+        assert!(!persistence.exists());
+
+        for generation in 1..=3u64 {
+            persistence.write_bytes(&generation.to_le_bytes()).unwrap();
+            let read = persistence.read_slot().unwrap();
+            assert_eq!(read.generation, generation);
+            assert_eq!(read.slot, if generation % 2 == 1 { Slot::A } else { Slot::B });
+            assert_eq!(&read.bytes[..], &generation.to_le_bytes());
+        }
+        assert_eq!(persistence.read_slot().unwrap().selection, SlotSelection::Newest);
+    }
+
+    #[test]
+    fn test_journaled_persistence_falls_back_to_last_good_slot() {
+        let temp_dir = TempDir::new().unwrap();
+        let persistence = JournaledPersistence::new(temp_dir.path().join("state.bin"));
+        let state1 = MultiAxisState::from_master_seed([1u8; 32]);
+        let state2 = MultiAxisState::from_master_seed([2u8; 32]);
+        persistence.save(&state1).unwrap();
+        persistence.save(&state2).unwrap();
+
+        // Torn write of generation 2 into slot b
+        let slot_b = persistence.slot_path(Slot::B);
+        let bytes = std::fs::read(&slot_b).unwrap();
+        std::fs::write(&slot_b, &bytes[..bytes.len() / 2]).unwrap();
+
+        let read = persistence.read_slot().unwrap();
+        assert_eq!((read.slot, read.generation), (Slot::A, 1));
+        assert_eq!(read.selection, SlotSelection::Fallback(SlotFault::Truncated));
+        assert_eq!(read.to_string(), "slot a (generation 1): slot b truncated");
+        assert_eq!(persistence.load::<MultiAxisState>().unwrap().state_vector(), state1.state_vector());
+
+        // Bit flip in slot a as well
+        let slot_a = persistence.slot_path(Slot::A);
+        let mut bytes = std::fs::read(&slot_a).unwrap();
+        bytes[20] ^= 0x01;
+        std::fs::write(&slot_a, &bytes).unwrap();
+        assert!(matches!(persistence.load::<MultiAxisState>(), Err(RuntimeError::PersistenceFailed(_))));
+
+        // The next write replaces the damaged slot a and starts over
+        persistence.save(&state2).unwrap();
+        let read = persistence.read_slot().unwrap();
+        assert_eq!((read.slot, read.generation), (Slot::A, 1));
+        assert_eq!(read.selection, SlotSelection::Fallback(SlotFault::Truncated));
+    }
+
+    #[test]
+    fn test_journaled_persistence_rewrites_older_slot() {
+        let temp_dir = TempDir::new().unwrap();
+        let persistence = JournaledPersistence::new(temp_dir.path().join("state.bin"));
+        persistence.write_bytes(b"one").unwrap();
+        persistence.write_bytes(b"two").unwrap();
+
+        // Slot b is damaged: the next write must not overwrite slot a
+        std::fs::write(persistence.slot_path(Slot::B), b"MISX").unwrap();
+        assert_eq!(
+            persistence.read_slot().unwrap().selection,
+            SlotSelection::Fallback(SlotFault::BadMagic)
+        );
+        persistence.write_bytes(b"three").unwrap();
+        let read = persistence.read_slot().unwrap();
+        assert_eq!((read.slot, read.generation), (Slot::B, 2));
+        assert_eq!(&std::fs::read(persistence.slot_path(Slot::A)).unwrap()[16..19], b"one");
+    }
+
+    #[test]
+    fn test_exclusive_lock_rejects_second_writer() {
+        let temp_dir = TempDir::new().unwrap();
+        let state_path = temp_dir.path().join("state.bin");
+        let state = MultiAxisState::from_master_seed([1u8; 32]);
+
+        let owner = FilePersistence::new(&state_path).with_exclusive_lock().unwrap();
+        owner.save(&state).unwrap();
+        assert!(owner.load::<MultiAxisState>().is_ok());
+
+        let other = FilePersistence::new(&state_path).with_lock_timeout(Duration::from_millis(50));
+        assert!(matches!(other.load::<MultiAxisState>(), Err(RuntimeError::StateInUse(_))));
+        assert!(matches!(other.save(&state), Err(RuntimeError::StateInUse(_))));
+        let second = FilePersistence::new(&state_path)
+            .with_lock_timeout(Duration::from_millis(50))
+            .with_exclusive_lock();
+        assert!(matches!(second, Err(RuntimeError::StateInUse(_))));
+
+        drop(owner);
+        assert!(other.load::<MultiAxisState>().is_ok());
+        assert!(other.with_exclusive_lock().is_ok());
+    }
+
+    #[test]
+    fn test_lock_waits_for_release() {
+        let temp_dir = TempDir::new().unwrap();
+        let state_path = temp_dir.path().join("state.bin");
+        let owner = FilePersistence::new(&state_path).with_exclusive_lock().unwrap();
+
+        let waiter = std::thread::spawn({
+            let state_path = state_path.clone();
+            move || {
+                FilePersistence::new(&state_path)
+                    .with_lock_timeout(Duration::from_secs(10))
+                    .write_bytes(b"state")
+            }
+        });
+        std::thread::sleep(Duration::from_millis(50));
+        drop(owner);
+        waiter.join().unwrap().unwrap();
+        assert_eq!(std::fs::read(&state_path).unwrap(), b"state");
+    }
+
+    #[test]
+    fn test_journaled_persistence_exclusive_lock() {
+        let temp_dir = TempDir::new().unwrap();
+        let state_path = temp_dir.path().join("state.bin");
+        let owner = JournaledPersistence::new(&state_path).with_exclusive_lock().unwrap();
+        owner.write_bytes(b"one").unwrap();
+
+        let other = JournaledPersistence::new(&state_path).with_lock_timeout(Duration::from_millis(50));
+        assert!(matches!(other.read_slot(), Err(RuntimeError::StateInUse(_))));
+        drop(owner);
+        assert_eq!(&other.read_bytes().unwrap()[..], b"one");
+    }
+
+    #[test]
+    fn test_in_memory_persistence_roundtrip() {
+        let persistence = InMemoryPersistence::new();
+        assert!(!persistence.exists());
+        assert!(persistence.load::<MultiAxisState>().is_err());
+
+        let state = MultiAxisState::from_master_seed([1u8; 32]);
+        persistence.clone().save(&state).unwrap();
+        assert!(persistence.exists());
+        assert_eq!(persistence.load::<MultiAxisState>().unwrap().state_vector(), state.state_vector());
+
+        let (_, record) = isa_core::migrate::<3, isa_core::Blake3Suite, isa_core::Shake256Suite>(&state, &[1u8; 32]);
+        persistence.append_migration_record(&record).unwrap();
+        persistence.append_migration_record(&record).unwrap();
+        assert_eq!(persistence.migration_records().unwrap(), vec![record]);
+    }
+
+    #[test]
+    fn test_persistence_is_generic_over_state_type() {
+        let persistence = InMemoryPersistence::new();
+
+        let state = IntegrityState::<5>::from_master_seed([2u8; 32]);
+        persistence.save(&state).unwrap();
+        assert_eq!(persistence.load::<IntegrityState<5>>().unwrap().commitment(), state.commitment());
+        assert!(persistence.load::<IntegrityState<3>>().is_err());
+
+        let dynamic = persistence.load::<DynamicIntegrityState>().unwrap();
+        assert_eq!(dynamic.dimension_count(), 5);
+
+        let mut dynamic = DynamicIntegrityState::new(7, [3u8; 32]);
+        dynamic.dimension_mut(6).unwrap().accumulate(b"event", b"entropy", 1);
+        persistence.save(&dynamic).unwrap();
+        assert_eq!(persistence.load::<DynamicIntegrityState>().unwrap().commitment(), dynamic.commitment());
+    }
+
+    #[test]
+    fn test_directory_persistence_keys_by_device_id() {
+        let temp_dir = TempDir::new().unwrap();
+        let directory = DirectoryPersistence::new(temp_dir.path().join("devices"));
+        assert!(directory.device_ids().unwrap().is_empty());
+
+        for (id, seed) in [("pos-2", 2u8), ("pos-1", 1u8)] {
+            directory.device(id).unwrap().save(&MultiAxisState::from_master_seed([seed; 32])).unwrap();
+        }
+        assert_eq!(directory.device_ids().unwrap(), vec!["pos-1", "pos-2"]);
+        assert_eq!(
+            directory.device("pos-2").unwrap().load::<MultiAxisState>().unwrap().state_vector(),
+            MultiAxisState::from_master_seed([2u8; 32]).state_vector()
+        );
+
+        // States saved without a runtime get an enforcement state on first load
+        let mut runtime = crate::DeviceRuntime::load_or_create([1u8; 32], directory.device("pos-1").unwrap()).unwrap();
+        runtime.quarantine(0).unwrap();
+        drop(runtime);
+        directory.remove("pos-1").unwrap();
+        assert_eq!(directory.device_ids().unwrap(), vec!["pos-2"]);
+        let device = directory.device("pos-1").unwrap();
+        for path in [device.enforcement_path(), device.lock_path()] {
+            assert!(!path.exists(), "{} left behind", path.display());
+        }
+        let runtime = crate::DeviceRuntime::load_or_create([1u8; 32], device).unwrap();
+        assert!(runtime.quarantined().is_empty());
+
+        for id in ["", ".hidden", "../escape", "a/b", "pos 3"] {
+            assert!(directory.device(id).is_err(), "{:?} accepted", id);
+        }
+    }
 }
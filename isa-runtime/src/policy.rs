@@ -9,17 +9,39 @@
 //! - Divergence thresholds (when to trigger reconciliation) - **NORMATIVE**
 //! - State reconciliation strategies (how to restore integrity) - **INFORMATIVE**
 //! - Monitoring and alerting rules - **INFORMATIVE**
+//! - Escalation of repeated violations - **INFORMATIVE**
+//!
+//! ## Escalation
+//!
+//! An [`EscalationLadder`] replaces a policy's fixed strategy with steps
+//! reached by repeated violations, for example `MonitorOnly` on the first
+//! violation, `Quarantine` after three within an hour and `FullRecovery` on
+//! the fourth. Each violation (timestamped in seconds) is counted while it is
+//! within the ladder's window; the step reached is the last whose `after`
+//! count is met. A step once reached stays in force until the dimension has
+//! had no violation for the cooldown period, so a ladder with a long cooldown
+//! does not drop back as soon as old violations leave the window. Below the
+//! first step the policy's own strategy applies.
 //!
 //! ## Terminology
 //!
 //! - "Reconciliation" replaces informal term "healing"
 //! - "Safety-relevant dimension" replaces "critical dimension"
 
-use isa_core::STATE_SIZE;
+use crate::{Result, RuntimeError};
+use isa_core::{Divergence256, STATE_SIZE};
 
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 
+/// Default divergence threshold: 2^254, half of the largest circular
+/// distance (2^255), so unrelated states exceed it half of the time.
+const HALF_MAX_DIVERGENCE: Divergence256 = {
+    let mut bytes = [0; STATE_SIZE];
+    bytes[STATE_SIZE - 1] = 0x40;
+    Divergence256::from_le_bytes(bytes)
+};
+
 /// Recovery strategy for a dimension when divergence exceeds threshold.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
@@ -51,8 +73,8 @@ pub struct DimensionPolicy {
     /// If the first N bytes of divergence exceed this value, trigger recovery.
     pub threshold_bytes: usize,
     
-    /// Maximum allowed divergence value (as u64 from first 8 bytes).
-    pub max_divergence: u64,
+    /// Maximum allowed divergence value.
+    pub max_divergence: Divergence256,
     
     /// Recovery strategy to use when threshold is exceeded.
     pub recovery_strategy: RecoveryStrategy,
@@ -65,6 +87,130 @@ pub struct DimensionPolicy {
     
     /// Whether accumulation is currently enabled for this dimension.
     pub enabled: bool,
+    
+    /// Escalation of repeated violations; overrides `recovery_strategy` once
+    /// its first step is reached.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub escalation: Option<EscalationLadder>,
+}
+
+/// One step of an escalation ladder.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct EscalationStep {
+    /// Violations within the window that reach this step.
+    pub after: u32,
+    
+    /// Strategy applied from this step on.
+    pub strategy: RecoveryStrategy,
+}
+
+/// Strategies applied to repeated violations of one policy (see the module docs).
+///
+/// Deserialized ladders are checked by [`EscalationLadder::new`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(try_from = "UncheckedEscalationLadder"))]
+pub struct EscalationLadder {
+    steps: Vec<EscalationStep>,
+    window_secs: u64,
+    cooldown_secs: u64,
+}
+
+/// Fields of a deserialized [`EscalationLadder`] before they are checked.
+#[cfg(feature = "serde")]
+#[derive(Deserialize)]
+struct UncheckedEscalationLadder {
+    steps: Vec<EscalationStep>,
+    window_secs: u64,
+    cooldown_secs: u64,
+}
+
+#[cfg(feature = "serde")]
+impl TryFrom<UncheckedEscalationLadder> for EscalationLadder {
+    type Error = RuntimeError;
+
+    fn try_from(ladder: UncheckedEscalationLadder) -> Result<Self> {
+        Self::new(ladder.steps, ladder.window_secs, ladder.cooldown_secs)
+    }
+}
+
+impl EscalationLadder {
+    /// Create a ladder counting violations over `window_secs` seconds and
+    /// dropping back after `cooldown_secs` seconds without a violation.
+    ///
+    /// Fails with `InvalidConfig` unless there is at least one step, every
+    /// `after` is positive and strictly increasing, and the window is not empty.
+    pub fn new(steps: Vec<EscalationStep>, window_secs: u64, cooldown_secs: u64) -> Result<Self> {
+        if steps.is_empty() {
+            return Err(RuntimeError::InvalidConfig("escalation ladder has no steps".to_string()));
+        }
+        if window_secs == 0 {
+            return Err(RuntimeError::InvalidConfig("escalation window must not be empty".to_string()));
+        }
+        let mut previous = 0;
+        for step in &steps {
+            if step.after <= previous {
+                return Err(RuntimeError::InvalidConfig(format!(
+                    "escalation steps need positive, increasing violation counts (found {} after {})",
+                    step.after, previous
+                )));
+            }
+            previous = step.after;
+        }
+        Ok(Self { steps, window_secs, cooldown_secs })
+    }
+    
+    pub fn steps(&self) -> &[EscalationStep] {
+        &self.steps
+    }
+    
+    pub fn window_secs(&self) -> u64 {
+        self.window_secs
+    }
+    
+    pub fn cooldown_secs(&self) -> u64 {
+        self.cooldown_secs
+    }
+    
+    /// Record a violation at `now` (seconds) in `state` and return the index
+    /// of the step in force, or `None` below the first step.
+    pub fn record(&self, state: &mut EscalationState, now: u64) -> Option<usize> {
+        if now.saturating_sub(state.last_violation) >= self.cooldown_secs {
+            state.level = 0;
+        }
+        state.recent.retain(|&time| now.saturating_sub(time) < self.window_secs);
+        state.recent.push(now);
+        
+        // Only the count up to the last step matters
+        let keep = self.steps.last().map_or(0, |step| step.after as usize);
+        if state.recent.len() > keep {
+            state.recent.drain(..state.recent.len() - keep);
+        }
+        
+        let reached = self.steps.iter().filter(|step| step.after as usize <= state.recent.len()).count();
+        state.level = (state.level as usize).max(reached).min(self.steps.len()) as u32;
+        state.last_violation = now;
+        (state.level as usize).checked_sub(1)
+    }
+    
+    /// Strategy of the step `index` returned by [`EscalationLadder::record`].
+    pub fn strategy(&self, index: usize) -> Option<RecoveryStrategy> {
+        self.steps.get(index).map(|step| step.strategy)
+    }
+}
+
+/// Violation history driving the escalation of one dimension.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct EscalationState {
+    /// Number of steps reached (0 below the first step).
+    pub level: u32,
+    
+    /// Time of the last violation, in seconds.
+    pub last_violation: u64,
+    
+    /// Times of the violations still within the window, oldest first.
+    pub recent: Vec<u64>,
 }
 
 impl DimensionPolicy {
@@ -73,16 +219,22 @@ impl DimensionPolicy {
         Self {
             name: name.into(),
             threshold_bytes: 8,
-            max_divergence: u64::MAX / 2, // 50% of state space
+            max_divergence: HALF_MAX_DIVERGENCE,
             recovery_strategy: RecoveryStrategy::ImmediateHeal,
             is_critical: false,
             weight: 1.0,
             enabled: true,
+            escalation: None,
         }
     }
     
     /// Set the divergence threshold.
-    pub fn with_threshold(mut self, max_divergence: u64) -> Self {
+    pub fn with_threshold(self, max_divergence: u64) -> Self {
+        self.with_max_divergence(Divergence256::from(max_divergence))
+    }
+    
+    /// Set a divergence threshold wider than 64 bits.
+    pub fn with_max_divergence(mut self, max_divergence: Divergence256) -> Self {
         self.max_divergence = max_divergence;
         self
     }
@@ -93,6 +245,12 @@ impl DimensionPolicy {
         self
     }
     
+    /// Escalate repeated violations along `ladder`.
+    pub fn with_escalation(mut self, ladder: EscalationLadder) -> Self {
+        self.escalation = Some(ladder);
+        self
+    }
+    
     /// Mark this dimension as critical.
     pub fn critical(mut self) -> Self {
         self.is_critical = true;
@@ -106,18 +264,12 @@ impl DimensionPolicy {
     }
     
     /// Check if a divergence value exceeds this policy's threshold.
-    pub fn exceeds_threshold(&self, divergence: &[u8; STATE_SIZE]) -> bool {
+    pub fn exceeds_threshold(&self, divergence: &Divergence256) -> bool {
         if !self.enabled {
             return false;
         }
         
-        // Convert first 8 bytes to u64 for comparison
-        let div_value = u64::from_le_bytes([
-            divergence[0], divergence[1], divergence[2], divergence[3],
-            divergence[4], divergence[5], divergence[6], divergence[7],
-        ]);
-        
-        div_value > self.max_divergence
+        *divergence > self.max_divergence
     }
 }
 
@@ -165,7 +317,7 @@ impl PolicySet {
     ///
     /// Returns a vector of (dimension_index, policy) pairs for dimensions
     /// that exceed their thresholds.
-    pub fn evaluate(&self, divergences: &[[u8; STATE_SIZE]]) -> Vec<(usize, &DimensionPolicy)> {
+    pub fn evaluate(&self, divergences: &[Divergence256]) -> Vec<(usize, &DimensionPolicy)> {
         let mut violations = Vec::new();
         
         for (i, div) in divergences.iter().enumerate() {
@@ -198,7 +350,7 @@ mod tests {
             .with_weight(0.8);
         
         assert_eq!(policy.name, "test");
-        assert_eq!(policy.max_divergence, 1000);
+        assert_eq!(policy.max_divergence, Divergence256::from(1000u64));
         assert!(policy.is_critical);
         assert_eq!(policy.weight, 0.8);
     }
@@ -207,13 +359,45 @@ mod tests {
     fn test_threshold_check() {
         let policy = DimensionPolicy::new("test").with_threshold(100);
         
-        let low_div = [50u8, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0];
-        let high_div = [200u8, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0];
+        let low_div = Divergence256::from(50u64);
+        let high_div = Divergence256::from(200u64);
         
         assert!(!policy.exceeds_threshold(&low_div));
         assert!(policy.exceeds_threshold(&high_div));
     }
     
+    #[test]
+    fn test_threshold_compares_full_width() {
+        let policy = DimensionPolicy::new("test").with_threshold(100);
+        
+        // Low bytes below the threshold, high bytes set.
+        let mut bytes = [0u8; 32];
+        bytes[0] = 50;
+        bytes[31] = 1;
+        assert!(policy.exceeds_threshold(&Divergence256::from(bytes)));
+        
+        let wide = DimensionPolicy::new("test").with_max_divergence(Divergence256::from(bytes));
+        assert!(!wide.exceeds_threshold(&Divergence256::from(u64::MAX)));
+    }
+    
+    #[test]
+    fn test_default_threshold_is_reachable() {
+        let policy = DimensionPolicy::new("test");
+        
+        // The largest circular distance, 2^255
+        let mut bytes = [0u8; 32];
+        bytes[31] = 0x80;
+        assert!(policy.exceeds_threshold(&Divergence256::from(bytes)));
+        
+        // 3 * 2^253, between the threshold and the largest distance
+        bytes[31] = 0x60;
+        assert!(policy.exceeds_threshold(&Divergence256::from(bytes)));
+        
+        bytes[31] = 0x40;
+        assert!(!policy.exceeds_threshold(&Divergence256::from(bytes)));
+        assert!(!policy.exceeds_threshold(&Divergence256::from(u64::MAX)));
+    }
+    
     #[test]
     fn test_policy_set() {
         let mut policy_set = PolicySet::new();
@@ -223,4 +407,69 @@ mod tests {
         assert_eq!(policy_set.len(), 2);
         assert_eq!(policy_set.get(0).unwrap().name, "dim0");
     }
+    
+    fn ladder(cooldown_secs: u64) -> EscalationLadder {
+        EscalationLadder::new(
+            vec![
+                EscalationStep { after: 1, strategy: RecoveryStrategy::MonitorOnly },
+                EscalationStep { after: 3, strategy: RecoveryStrategy::Quarantine },
+                EscalationStep { after: 4, strategy: RecoveryStrategy::FullRecovery },
+            ],
+            3600,
+            cooldown_secs,
+        ).unwrap()
+    }
+    
+    #[test]
+    fn test_escalation_within_window() {
+        let ladder = ladder(7200);
+        let mut state = EscalationState::default();
+        
+        assert_eq!(ladder.record(&mut state, 1000), Some(0));
+        assert_eq!(ladder.record(&mut state, 1500), Some(0));
+        assert_eq!(ladder.record(&mut state, 2000), Some(1));
+        assert_eq!(ladder.strategy(1), Some(RecoveryStrategy::Quarantine));
+        assert_eq!(ladder.record(&mut state, 2500), Some(2));
+        assert_eq!(state.recent.len(), 4);
+        
+        // Older violations leave the window, but the step holds until the cooldown
+        assert_eq!(ladder.record(&mut state, 6500), Some(2));
+        assert_eq!(state.recent, vec![6500]);
+        assert_eq!(ladder.record(&mut state, 6500 + 7200), Some(0));
+    }
+    
+    #[test]
+    fn test_escalation_without_cooldown_follows_window() {
+        let ladder = ladder(0);
+        let mut state = EscalationState::default();
+        for time in [0, 10, 20] {
+            ladder.record(&mut state, time);
+        }
+        assert_eq!(state.level, 2);
+        // Two of the three violations have left the window
+        assert_eq!(ladder.record(&mut state, 3615), Some(0));
+    }
+    
+    #[test]
+    fn test_escalation_ladder_validation() {
+        let step = |after| EscalationStep { after, strategy: RecoveryStrategy::Quarantine };
+        assert!(EscalationLadder::new(Vec::new(), 60, 60).is_err());
+        assert!(EscalationLadder::new(vec![step(1)], 0, 60).is_err());
+        assert!(EscalationLadder::new(vec![step(0)], 60, 60).is_err());
+        assert!(EscalationLadder::new(vec![step(2), step(2)], 60, 60).is_err());
+        assert!(EscalationLadder::new(vec![step(2), step(5)], 60, 60).is_ok());
+    }
+    
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_deserialized_ladder_is_validated() {
+        let step = |after| EscalationStep { after, strategy: RecoveryStrategy::Quarantine };
+        let ladder = EscalationLadder::new(vec![step(2), step(5)], 60, 60).unwrap();
+        let bytes = bincode::serialize(&ladder).unwrap();
+        assert_eq!(bincode::deserialize::<EscalationLadder>(&bytes).unwrap(), ladder);
+        
+        // Same layout, but the steps are out of order
+        let bytes = bincode::serialize(&(vec![step(5), step(2)], 60u64, 60u64)).unwrap();
+        assert!(bincode::deserialize::<EscalationLadder>(&bytes).is_err());
+    }
 }
@@ -0,0 +1,191 @@
+//! Application-provided recovery handlers and constraint evaluators.
+//!
+//! ## Conformance Classification
+//!
+//! **OPTIONAL** - `RecoveryStrategy::Custom` and `ConstraintType::Custom` are
+//! extension points; implementations MAY resolve them through this registry.
+//!
+//! A [`CustomRegistry`] maps the ids of `RecoveryStrategy::Custom(id)` and
+//! `ConstraintType::Custom(id)` to code registered by the application.
+//! Configuration files refer to them as `custom:<id>`;
+//! [`IsaConfig::to_policy_set`](crate::config::IsaConfig::to_policy_set) and
+//! [`IsaConfig::to_constraint_set`](crate::config::IsaConfig::to_constraint_set)
+//! take the registry and reject ids that have nothing registered.
+//!
+//! A recovery handler does not touch the runtime itself: it either asks the
+//! [`PolicyEnforcer`](crate::enforcement::PolicyEnforcer) to apply one of the
+//! built-in strategies or reports that it dealt with the violation.
+
+use crate::constraints::DimensionConstraint;
+use crate::policy::{DimensionPolicy, RecoveryStrategy};
+use crate::{Result, RuntimeError};
+use isa_core::{DimensionDivergence, Divergence256};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::sync::Arc;
+
+/// A violated policy whose strategy is `RecoveryStrategy::Custom(id)`.
+#[derive(Debug, Clone, Copy)]
+pub struct CustomViolation<'a> {
+    pub id: u32,
+    pub dimension: usize,
+    pub policy: &'a DimensionPolicy,
+    /// Divergence from the trusted state before enforcement.
+    pub divergence: &'a DimensionDivergence,
+}
+
+/// What a recovery handler decided.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CustomRecovery {
+    /// Apply a built-in strategy. `Custom` strategies are not chained and
+    /// leave the violation unhandled.
+    Apply(RecoveryStrategy),
+    /// The handler dealt with the violation; `note` is kept in the outcome.
+    Handled(String),
+}
+
+/// Recovery for `RecoveryStrategy::Custom(id)`.
+///
+/// A handler may be consulted again for the same violation if enforcing it
+/// fails afterwards (see [`crate::enforce_violations`]), so its side effects
+/// must be idempotent.
+pub trait RecoveryHandler: Send + Sync {
+    fn recover(&self, violation: &CustomViolation<'_>) -> Result<CustomRecovery>;
+}
+
+impl<F> RecoveryHandler for F
+where
+    F: Fn(&CustomViolation<'_>) -> Result<CustomRecovery> + Send + Sync,
+{
+    fn recover(&self, violation: &CustomViolation<'_>) -> Result<CustomRecovery> {
+        self(violation)
+    }
+}
+
+/// Evaluation of `ConstraintType::Custom(id)`.
+pub trait ConstraintEvaluator: Send + Sync {
+    /// Returns true if `constraint` is violated.
+    fn evaluate(&self, constraint: &DimensionConstraint, divergences: &[Divergence256]) -> bool;
+}
+
+impl<F> ConstraintEvaluator for F
+where
+    F: Fn(&DimensionConstraint, &[Divergence256]) -> bool + Send + Sync,
+{
+    fn evaluate(&self, constraint: &DimensionConstraint, divergences: &[Divergence256]) -> bool {
+        self(constraint, divergences)
+    }
+}
+
+/// Recovery handlers and constraint evaluators by custom id.
+///
+/// Recovery and constraint ids are separate namespaces. Clones share the
+/// registered handlers.
+#[derive(Clone, Default)]
+pub struct CustomRegistry {
+    recovery: BTreeMap<u32, Arc<dyn RecoveryHandler>>,
+    constraints: BTreeMap<u32, Arc<dyn ConstraintEvaluator>>,
+}
+
+impl CustomRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register the handler of `RecoveryStrategy::Custom(id)`.
+    ///
+    /// Fails with `InvalidConfig` if `id` already has a handler.
+    pub fn register_recovery_handler(
+        &mut self,
+        id: u32,
+        handler: impl RecoveryHandler + 'static,
+    ) -> Result<()> {
+        if self.recovery.contains_key(&id) {
+            return Err(RuntimeError::InvalidConfig(format!(
+                "recovery handler custom:{} is already registered",
+                id
+            )));
+        }
+        self.recovery.insert(id, Arc::new(handler));
+        Ok(())
+    }
+
+    /// Register the evaluator of `ConstraintType::Custom(id)`.
+    ///
+    /// Fails with `InvalidConfig` if `id` already has an evaluator.
+    pub fn register_constraint_evaluator(
+        &mut self,
+        id: u32,
+        evaluator: impl ConstraintEvaluator + 'static,
+    ) -> Result<()> {
+        if self.constraints.contains_key(&id) {
+            return Err(RuntimeError::InvalidConfig(format!(
+                "constraint evaluator custom:{} is already registered",
+                id
+            )));
+        }
+        self.constraints.insert(id, Arc::new(evaluator));
+        Ok(())
+    }
+
+    pub fn recovery_handler(&self, id: u32) -> Option<&dyn RecoveryHandler> {
+        self.recovery.get(&id).map(|handler| handler.as_ref())
+    }
+
+    pub fn constraint_evaluator(&self, id: u32) -> Option<&dyn ConstraintEvaluator> {
+        self.constraints.get(&id).map(|evaluator| evaluator.as_ref())
+    }
+}
+
+impl fmt::Debug for CustomRegistry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CustomRegistry")
+            .field("recovery", &self.recovery.keys().collect::<Vec<_>>())
+            .field("constraints", &self.constraints.keys().collect::<Vec<_>>())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::constraints::{ConstraintSet, ConstraintType};
+
+    #[test]
+    fn test_duplicate_registration_is_rejected() {
+        let mut registry = CustomRegistry::new();
+        let handler = |_: &CustomViolation<'_>| Ok(CustomRecovery::Apply(RecoveryStrategy::MonitorOnly));
+        registry.register_recovery_handler(1, handler).unwrap();
+        assert!(matches!(
+            registry.register_recovery_handler(1, handler),
+            Err(RuntimeError::InvalidConfig(_))
+        ));
+
+        // Constraint ids are a separate namespace
+        registry.register_constraint_evaluator(1, |_: &DimensionConstraint, _: &[Divergence256]| true).unwrap();
+        assert!(registry.recovery_handler(1).is_some());
+        assert!(registry.constraint_evaluator(2).is_none());
+    }
+
+    #[test]
+    fn test_custom_constraint_dispatch() {
+        let mut registry = CustomRegistry::new();
+        registry
+            .register_constraint_evaluator(9, |constraint: &DimensionConstraint, divergences: &[Divergence256]| {
+                constraint.dimensions.iter().all(|&i| divergences.get(i).is_some_and(|d| !d.is_zero()))
+            })
+            .unwrap();
+
+        let mut set = ConstraintSet::new();
+        set.add_constraint(DimensionConstraint::new("both", vec![0, 1], ConstraintType::Custom(9)));
+        set.add_constraint(DimensionConstraint::new("unknown", vec![0], ConstraintType::Custom(4)));
+
+        let divergences = [Divergence256::from(3u64), Divergence256::from(5u64)];
+        assert!(set.evaluate(&divergences).is_empty());
+        let violations = set.evaluate_with(&divergences, &registry);
+        assert_eq!(violations.iter().map(|(i, _)| *i).collect::<Vec<_>>(), vec![0]);
+
+        let divergences = [Divergence256::from(3u64), Divergence256::ZERO];
+        assert!(set.evaluate_with(&divergences, &registry).is_empty());
+    }
+}
@@ -0,0 +1,218 @@
+//! Anti-rollback high-water marks.
+//!
+//! ## Conformance Classification
+//!
+//! **INFORMATIVE** - Guards are provided as a defence against restoring an old
+//! state file. Conforming implementations MAY protect against rollback by
+//! other means (for example a hardware monotonic counter).
+//!
+//! A state file copied back over the current one decodes and verifies like any
+//! other state. A [`RollbackGuard`] keeps the highest counter each dimension
+//! has been saved with, outside the state file. `DeviceRuntime` refuses a
+//! loaded state whose counters fall below that mark
+//! (`RuntimeError::StateRolledBack`) and raises the mark on every save.
+//!
+//! The guard is only as strong as its storage: a mark kept next to the state
+//! file can be rolled back together with it. Keep it on separate media, in a
+//! secure element or with a remote authority where possible.
+//!
+//! ## File Format
+//!
+//! ```text
+//! magic "MISR" (4) || count u32 LE (4) || counter u64 LE (8 each) || CRC-32 (4)
+//! ```
+
+use crate::persistence::{sync_parent_dir, write_synced};
+use crate::{Result, RuntimeError};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+/// Magic bytes at the start of a rollback mark file.
+pub const MARK_MAGIC: [u8; 4] = *b"MISR";
+
+/// Monotonic store for the highest counters a state has been saved with.
+pub trait RollbackGuard: Send {
+    /// The stored high-water mark, one counter per dimension, or `None` if none was stored.
+    fn high_water_mark(&self) -> Result<Option<Vec<u64>>>;
+
+    /// Raise the mark to `counters`. Entries never decrease.
+    fn advance(&self, counters: &[u64]) -> Result<()>;
+
+    /// Fail with `StateRolledBack` if any counter is below the mark.
+    fn check(&self, counters: &[u64]) -> Result<()> {
+        let mark = match self.high_water_mark()? {
+            Some(mark) => mark,
+            None => return Ok(()),
+        };
+        for (dimension, (&found, &expected)) in counters.iter().zip(&mark).enumerate() {
+            if found < expected {
+                return Err(RuntimeError::StateRolledBack { dimension, found, expected });
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Entry-wise maximum of a stored mark and new counters.
+fn raise(mark: Option<Vec<u64>>, counters: &[u64]) -> Vec<u64> {
+    let mut raised = mark.unwrap_or_default();
+    if raised.len() < counters.len() {
+        raised.resize(counters.len(), 0);
+    }
+    for (entry, &counter) in raised.iter_mut().zip(counters) {
+        *entry = (*entry).max(counter);
+    }
+    raised
+}
+
+/// Guard that keeps the mark in a file, replaced atomically and synced on every advance.
+pub struct FileRollbackGuard {
+    path: PathBuf,
+}
+
+impl FileRollbackGuard {
+    pub fn new<P: AsRef<Path>>(path: P) -> Self {
+        Self {
+            path: path.as_ref().to_path_buf(),
+        }
+    }
+}
+
+impl RollbackGuard for FileRollbackGuard {
+    fn high_water_mark(&self) -> Result<Option<Vec<u64>>> {
+        let bytes = match fs::read(&self.path) {
+            Ok(bytes) => bytes,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(RuntimeError::PersistenceFailed(e.to_string())),
+        };
+        
+        // A damaged mark must not silently disable the guard
+        if bytes.len() < 12 || bytes[..4] != MARK_MAGIC {
+            return Err(RuntimeError::InvalidState);
+        }
+        let mut count = [0u8; 4];
+        count.copy_from_slice(&bytes[4..8]);
+        let body_len = 8 + 8 * u32::from_le_bytes(count) as usize;
+        if bytes.len() != body_len + 4 {
+            return Err(RuntimeError::InvalidState);
+        }
+        let mut checksum = [0u8; 4];
+        checksum.copy_from_slice(&bytes[body_len..]);
+        if isa_core::canonical::crc32(&bytes[..body_len]) != u32::from_le_bytes(checksum) {
+            return Err(RuntimeError::InvalidState);
+        }
+        
+        let counters = bytes[8..body_len]
+            .chunks(8)
+            .map(|chunk| {
+                let mut counter = [0u8; 8];
+                counter.copy_from_slice(chunk);
+                u64::from_le_bytes(counter)
+            })
+            .collect();
+        Ok(Some(counters))
+    }
+
+    fn advance(&self, counters: &[u64]) -> Result<()> {
+        let current = self.high_water_mark()?;
+        let mark = raise(current.clone(), counters);
+        if current.as_ref() == Some(&mark) {
+            return Ok(());
+        }
+        
+        let mut bytes = Vec::with_capacity(12 + 8 * mark.len());
+        bytes.extend_from_slice(&MARK_MAGIC);
+        bytes.extend_from_slice(&(mark.len() as u32).to_le_bytes());
+        for counter in &mark {
+            bytes.extend_from_slice(&counter.to_le_bytes());
+        }
+        let checksum = isa_core::canonical::crc32(&bytes);
+        bytes.extend_from_slice(&checksum.to_le_bytes());
+        
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent)
+                .map_err(|e| RuntimeError::PersistenceFailed(e.to_string()))?;
+        }
+        let temp_path = self.path.with_extension("tmp");
+        write_synced(&temp_path, &bytes)?;
+        fs::rename(&temp_path, &self.path)
+            .map_err(|e| RuntimeError::PersistenceFailed(e.to_string()))?;
+        sync_parent_dir(&self.path)
+    }
+}
+
+/// Guard that keeps the mark in memory.
+///
+/// Clones share the same mark, so a test can hand one clone to a runtime and
+/// keep another to simulate a restart.
+#[derive(Debug, Clone, Default)]
+pub struct InMemoryRollbackGuard {
+    mark: Arc<Mutex<Option<Vec<u64>>>>,
+}
+
+impl InMemoryRollbackGuard {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl RollbackGuard for InMemoryRollbackGuard {
+    fn high_water_mark(&self) -> Result<Option<Vec<u64>>> {
+        Ok(self.mark.lock().map_err(|_| RuntimeError::InvalidState)?.clone())
+    }
+
+    fn advance(&self, counters: &[u64]) -> Result<()> {
+        let mut mark = self.mark.lock().map_err(|_| RuntimeError::InvalidState)?;
+        *mark = Some(raise(mark.take(), counters));
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_mark_never_decreases() {
+        let guard = InMemoryRollbackGuard::new();
+        assert_eq!(guard.high_water_mark().unwrap(), None);
+        guard.check(&[0, 0, 0]).unwrap();
+
+        guard.advance(&[3, 1, 2]).unwrap();
+        guard.advance(&[2, 4, 2]).unwrap();
+        assert_eq!(guard.high_water_mark().unwrap(), Some(vec![3, 4, 2]));
+
+        guard.check(&[3, 5, 2]).unwrap();
+        assert!(matches!(
+            guard.check(&[3, 3, 2]),
+            Err(RuntimeError::StateRolledBack { dimension: 1, found: 3, expected: 4 })
+        ));
+    }
+
+    #[test]
+    fn test_file_guard_roundtrip() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("state.mark");
+        FileRollbackGuard::new(&path).advance(&[1, 2, 3]).unwrap();
+        FileRollbackGuard::new(&path).advance(&[0, 5, 0]).unwrap();
+
+        let guard = FileRollbackGuard::new(&path);
+        assert_eq!(guard.high_water_mark().unwrap(), Some(vec![1, 5, 3]));
+        assert!(matches!(guard.check(&[1, 4, 3]), Err(RuntimeError::StateRolledBack { .. })));
+    }
+
+    #[test]
+    fn test_file_guard_rejects_damaged_mark() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("state.mark");
+        let guard = FileRollbackGuard::new(&path);
+        guard.advance(&[7]).unwrap();
+
+        let mut bytes = fs::read(&path).unwrap();
+        bytes[8] = 0;
+        fs::write(&path, &bytes).unwrap();
+        assert!(matches!(guard.check(&[0]), Err(RuntimeError::InvalidState)));
+    }
+}
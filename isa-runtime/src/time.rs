@@ -32,6 +32,14 @@ impl MonotonicClock {
     }
 }
 
+/// Wall-clock seconds since the Unix epoch, for state that must outlive the process.
+pub fn unix_seconds() -> Result<u64> {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_secs())
+        .map_err(|_| RuntimeError::TimeSourceFailed)
+}
+
 impl Default for MonotonicClock {
     fn default() -> Self {
         Self::new()